@@ -0,0 +1,34 @@
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(has_num_saturating)");
+    println!("cargo:rustc-check-cfg=cfg(has_diagnostic_namespace)");
+
+    let minor = rustc_minor_version().unwrap_or(0);
+
+    // `core::num::Saturating` is stable since Rust 1.74
+    if minor >= 74 {
+        println!("cargo:rustc-cfg=has_num_saturating");
+    }
+
+    // The `#[diagnostic]` attribute namespace is stable since Rust 1.78
+    if minor >= 78 {
+        println!("cargo:rustc-cfg=has_diagnostic_namespace");
+    }
+}
+
+/// Returns the minor version of the current compiler
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+
+    let mut pieces = version.split('.');
+    if pieces.next() != Some("rustc 1") {
+        return None;
+    }
+
+    pieces.next()?.parse().ok()
+}
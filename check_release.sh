@@ -6,6 +6,8 @@ run() {
     cargo +$RUSTC test
     sh -c "cd custom-format-macros && cargo +$RUSTC test"
     sh -c "cd custom-format-tests  && cargo +$RUSTC test"
+    sh -c "cd custom-format-tests  && cargo +$RUSTC test --no-default-features --features compile-time"
+    sh -c "cd custom-format-tests  && cargo +$RUSTC test --no-default-features --features runtime"
 }
 
 run 1.56
@@ -0,0 +1,28 @@
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(has_span_local_file)");
+
+    let minor = rustc_minor_version().unwrap_or(0);
+
+    // `proc_macro::Span::local_file` is stable since Rust 1.88
+    if minor >= 88 {
+        println!("cargo:rustc-cfg=has_span_local_file");
+    }
+}
+
+/// Returns the minor version of the current compiler
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+
+    let mut pieces = version.split('.');
+    if pieces.next() != Some("rustc 1") {
+        return None;
+    }
+
+    pieces.next()?.parse().ok()
+}
@@ -0,0 +1,513 @@
+//! Module containing the `CustomFormat` derive macro code.
+
+use crate::fmt::{compile_error, spec_value};
+
+#[cfg(not(test))]
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+#[cfg(test)]
+use proc_macro2::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Error type for the derive macro, with the span of the error
+type Error = (&'static str, Span);
+
+/// Compile-time format specifier mapped to a formatting function
+#[derive(Debug)]
+struct SpecImpl {
+    /// Compile-time format specifier
+    spec: String,
+    /// Span of the format specifier
+    span: Span,
+    /// Body of the generated `fmt` method
+    body: TokenStream,
+}
+
+/// Arguments of a `#[custom_format(...)]` attribute
+#[derive(Debug, Default)]
+struct AttrArgs {
+    /// Compile-time format specifier, with its span
+    spec: Option<(String, Span)>,
+    /// Compile-time format specifier replacing the name of the field or of the formatting function, with its span
+    rename: Option<(String, Span)>,
+    /// Path of the formatting function
+    with: Option<TokenStream>,
+    /// Path of the `custom-format` crate
+    crate_path: Option<TokenStream>,
+}
+
+/// Parse a string containing valid Rust tokens
+fn tokens(s: &str) -> TokenStream {
+    s.parse().unwrap()
+}
+
+/// Split a list of token trees on the commas which are not inside angle brackets
+fn split_commas(token_trees: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut previous_is_dash = false;
+
+    for (index, tt) in token_trees.iter().enumerate() {
+        if let TokenTree::Punct(punct) = tt {
+            match punct.as_char() {
+                '<' => depth += 1,
+                // Skip the `>` char of the `->` token
+                '>' if !previous_is_dash => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    items.push(&token_trees[start..index]);
+                    start = index + 1;
+                }
+                _ => (),
+            }
+            previous_is_dash = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+        } else {
+            previous_is_dash = false;
+        }
+    }
+
+    if start < token_trees.len() {
+        items.push(&token_trees[start..]);
+    }
+
+    items
+}
+
+/// Parse the arguments of a `#[custom_format(...)]` attribute, returning `None` for other attributes
+fn parse_attr(group: &Group) -> Result<Option<AttrArgs>, Error> {
+    let inner: Vec<_> = group.stream().into_iter().collect();
+
+    let args = match inner.as_slice() {
+        [TokenTree::Ident(ident), TokenTree::Group(args)] if &ident.to_string() == "custom_format" && args.delimiter() == Delimiter::Parenthesis => args,
+        [TokenTree::Ident(ident), ..] if &ident.to_string() == "custom_format" => {
+            return Err(("invalid `custom_format` attribute, expected `#[custom_format(...)]`", ident.span()))
+        }
+        _ => return Ok(None),
+    };
+
+    let mut attr_args = AttrArgs::default();
+    let token_trees: Vec<_> = args.stream().into_iter().collect();
+
+    for arg in split_commas(&token_trees) {
+        let (key, value) = match arg {
+            [TokenTree::Ident(key), TokenTree::Punct(punct), value @ ..] if punct.as_char() == '=' && !value.is_empty() => (key, value),
+            [tt, ..] => return Err(("invalid `custom_format` argument, expected `key = value`", tt.span())),
+            [] => continue,
+        };
+
+        let parse_string = || match value {
+            [tt] => litrs::StringLit::parse(tt.to_string())
+                .map(|lit| (lit.into_value().into_owned(), tt.span()))
+                .map_err(|_| ("expected a string literal", tt.span())),
+            _ => Err(("expected a string literal", value[0].span())),
+        };
+
+        match key.to_string().as_str() {
+            "spec" if attr_args.spec.is_none() => attr_args.spec = Some(parse_string()?),
+            "rename" if attr_args.rename.is_none() => attr_args.rename = Some(parse_string()?),
+            "with" if attr_args.with.is_none() => {
+                let (path, span) = parse_string()?;
+                attr_args.with = Some(path.parse().map_err(|_| ("invalid path for the `with` argument", span))?);
+            }
+            "crate" if attr_args.crate_path.is_none() => attr_args.crate_path = Some(value.iter().cloned().collect()),
+            "spec" | "rename" | "with" | "crate" => return Err(("duplicate `custom_format` argument", key.span())),
+            _ => return Err(("unknown `custom_format` argument, expected `spec`, `rename`, `with` or `crate`", key.span())),
+        }
+    }
+
+    if let (Some(_), Some((_, span))) = (&attr_args.spec, &attr_args.rename) {
+        return Err(("the `spec` and `rename` arguments cannot be used together", *span));
+    }
+
+    attr_args.spec = attr_args.spec.or(attr_args.rename.take());
+
+    Ok(Some(attr_args))
+}
+
+/// Extract the `#[custom_format(...)]` attributes from the beginning of a list of token trees,
+/// returning their arguments and the position of the first token tree after the attributes
+fn parse_attrs(token_trees: &[TokenTree]) -> Result<(Vec<AttrArgs>, usize), Error> {
+    let mut attrs = Vec::new();
+    let mut index = 0;
+
+    while let [TokenTree::Punct(punct), TokenTree::Group(group), ..] = &token_trees[index..] {
+        if punct.as_char() != '#' || group.delimiter() != Delimiter::Bracket {
+            break;
+        }
+
+        attrs.extend(parse_attr(group)?);
+        index += 2;
+    }
+
+    Ok((attrs, index))
+}
+
+/// Skip the visibility at the beginning of a list of token trees, returning the position of the next token tree
+fn skip_visibility(token_trees: &[TokenTree]) -> usize {
+    match token_trees {
+        [TokenTree::Ident(ident), TokenTree::Group(group), ..] if &ident.to_string() == "pub" && group.delimiter() == Delimiter::Parenthesis => 2,
+        [TokenTree::Ident(ident), ..] if &ident.to_string() == "pub" => 1,
+        _ => 0,
+    }
+}
+
+/// Split the generic parameters of the type, returning the parameters of the impl block and the arguments of the type
+fn split_generics(params: &[TokenTree]) -> (Vec<TokenTree>, Vec<TokenTree>) {
+    let mut impl_params = Vec::new();
+    let mut type_args = Vec::new();
+
+    for param in split_commas(params) {
+        // Default values are not allowed in the impl block
+        let param = match param.iter().position(|tt| matches!(tt, TokenTree::Punct(punct) if punct.as_char() == '=')) {
+            Some(position) => &param[..position],
+            None => param,
+        };
+
+        let name = match param {
+            [TokenTree::Punct(punct), lifetime, ..] if punct.as_char() == '\'' => vec![punct.clone().into(), lifetime.clone()],
+            [TokenTree::Ident(ident), name, ..] if &ident.to_string() == "const" => vec![name.clone()],
+            [name, ..] => vec![name.clone()],
+            [] => continue,
+        };
+
+        impl_params.extend(param.iter().cloned());
+        impl_params.push(Punct::new(',', Spacing::Alone).into());
+        type_args.extend(name);
+        type_args.push(Punct::new(',', Spacing::Alone).into());
+    }
+
+    (impl_params, type_args)
+}
+
+/// Compute the default format specifier of a formatting function, which is the last identifier of its path
+fn function_spec(with: &TokenStream) -> Option<(String, Span)> {
+    with.clone()
+        .into_iter()
+        .take_while(|tt| !matches!(tt, TokenTree::Punct(punct) if punct.as_char() == '<'))
+        .filter_map(|tt| match tt {
+            TokenTree::Ident(ident) => Some((ident.to_string(), ident.span())),
+            _ => None,
+        })
+        .last()
+}
+
+/// Compute the body of the `fmt` method for a value, which is formatted with its `Display` implementation if no function is provided
+fn compute_body(with: Option<TokenStream>, value: TokenStream) -> TokenStream {
+    let mut body = with.unwrap_or_else(|| tokens("::core::fmt::Display::fmt"));
+
+    let mut args = value;
+    args.extend(tokens(", f"));
+    body.extend(Some(TokenTree::from(Group::new(Delimiter::Parenthesis, args))));
+
+    body
+}
+
+/// Process the fields of a struct, returning the format specifiers mapped to a field
+fn process_fields(group: &Group) -> Result<Vec<SpecImpl>, Error> {
+    let token_trees: Vec<_> = group.stream().into_iter().collect();
+    let mut spec_impls = Vec::new();
+
+    for (index, field) in split_commas(&token_trees).into_iter().enumerate() {
+        let (attrs, position) = parse_attrs(field)?;
+
+        if attrs.is_empty() {
+            continue;
+        }
+
+        let field = &field[position..];
+        let field = &field[skip_visibility(field)..];
+
+        let (member, name) = match (group.delimiter(), field) {
+            (Delimiter::Brace, [TokenTree::Ident(name), ..]) => {
+                let spec = name.to_string();
+                let spec = spec.strip_prefix("r#").map_or(spec.clone(), str::to_owned);
+                (TokenTree::from(name.clone()), Some((spec, name.span())))
+            }
+            _ => (TokenTree::from(Literal::usize_unsuffixed(index)), None),
+        };
+
+        for attr in attrs {
+            if let Some(crate_path) = attr.crate_path {
+                return Err(("the `crate` argument can only be used on the type", crate_path.into_iter().next().map_or(Span::call_site(), |tt| tt.span())));
+            }
+
+            // The format specifier defaults to the name of the field
+            let (spec, span) = attr
+                .spec
+                .or_else(|| name.clone())
+                .ok_or(("missing `spec` or `rename` argument in `custom_format` attribute on a tuple field", group.span()))?;
+
+            let mut value = tokens("&self.");
+            value.extend(Some(member.clone()));
+
+            spec_impls.push(SpecImpl { spec, span, body: compute_body(attr.with, value) });
+        }
+    }
+
+    Ok(spec_impls)
+}
+
+/// Compute the `CustomFormat` impl block for a format specifier
+fn compute_impl(
+    crate_path: &TokenStream,
+    name: &Ident,
+    generics: &(Vec<TokenTree>, Vec<TokenTree>),
+    where_clause: &[TokenTree],
+    spec_impl: SpecImpl,
+) -> TokenStream {
+    let mut output = Vec::<TokenTree>::new();
+
+    output.extend(tokens("impl"));
+    output.push(Punct::new('<', Spacing::Alone).into());
+    output.extend(generics.0.iter().cloned());
+    output.push(Punct::new('>', Spacing::Alone).into());
+
+    output.extend(crate_path.clone());
+    output.extend(tokens("::compile_time::CustomFormat<"));
+
+    output.push(TokenTree::from(Group::new(Delimiter::Brace, {
+        let mut spec = crate_path.clone().into_iter().collect::<Vec<_>>();
+        spec.extend(tokens("::compile_time::spec"));
+        spec.push(Group::new(Delimiter::Parenthesis, TokenTree::from(Literal::string(&spec_impl.spec)).into()).into());
+        spec.into_iter().collect()
+    })));
+
+    output.push(Punct::new('>', Spacing::Alone).into());
+    output.push(Ident::new("for", Span::call_site()).into());
+    output.push(name.clone().into());
+    output.push(Punct::new('<', Spacing::Alone).into());
+    output.extend(generics.1.iter().cloned());
+    output.push(Punct::new('>', Spacing::Alone).into());
+    output.extend(where_clause.iter().cloned());
+
+    output.push(TokenTree::from(Group::new(Delimiter::Brace, {
+        let mut method = tokens("fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result");
+        method.extend(Some(TokenTree::from(Group::new(Delimiter::Brace, spec_impl.body))));
+        method
+    })));
+
+    output.into_iter().collect()
+}
+
+/// Process the derive input
+fn process(input: TokenStream) -> Result<TokenStream, Error> {
+    let token_trees: Vec<_> = input.into_iter().collect();
+
+    let (type_attrs, position) = parse_attrs(&token_trees)?;
+    let token_trees = &token_trees[position..];
+    let token_trees = &token_trees[skip_visibility(token_trees)..];
+
+    let (is_struct, name, token_trees) = match token_trees {
+        [TokenTree::Ident(keyword), TokenTree::Ident(name), rest @ ..] => match keyword.to_string().as_str() {
+            "struct" => (true, name, rest),
+            "enum" | "union" => (false, name, rest),
+            _ => return Err(("expected a struct, an enum or a union", keyword.span())),
+        },
+        _ => return Err(("expected a struct, an enum or a union", Span::call_site())),
+    };
+
+    let mut generics_len = 0;
+    if matches!(token_trees.first(), Some(TokenTree::Punct(punct)) if punct.as_char() == '<') {
+        let mut depth = 0;
+        let mut previous_is_dash = false;
+
+        for (index, tt) in token_trees.iter().enumerate() {
+            if let TokenTree::Punct(punct) = tt {
+                match punct.as_char() {
+                    '<' => depth += 1,
+                    // Skip the `>` char of the `->` token
+                    '>' if !previous_is_dash => depth -= 1,
+                    _ => (),
+                }
+                previous_is_dash = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+            } else {
+                previous_is_dash = false;
+            }
+
+            if depth == 0 {
+                generics_len = index + 1;
+                break;
+            }
+        }
+    }
+
+    let generics = match generics_len {
+        0 => (Vec::new(), Vec::new()),
+        len => split_generics(&token_trees[1..len - 1]),
+    };
+
+    // The where clause is before the body for a struct with named fields or an enum, and after it for a tuple struct
+    let token_trees = &token_trees[generics_len..];
+    let body_position = token_trees.iter().position(|tt| matches!(tt, TokenTree::Group(group) if group.delimiter() != Delimiter::Bracket));
+
+    let (where_clause, body) = match body_position.map(|position| (position, &token_trees[position])) {
+        Some((position, TokenTree::Group(group))) if group.delimiter() == Delimiter::Parenthesis => (&token_trees[position + 1..], Some(group)),
+        Some((position, TokenTree::Group(group))) => (&token_trees[..position], Some(group)),
+        _ => (token_trees, None),
+    };
+
+    let where_clause = match where_clause.last() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ';' => &where_clause[..where_clause.len() - 1],
+        _ => where_clause,
+    };
+
+    let mut crate_path = None;
+    let mut spec_impls = Vec::new();
+
+    for attr in type_attrs {
+        if let Some(path) = attr.crate_path {
+            if crate_path.replace(path).is_some() {
+                return Err(("duplicate `crate` argument", Span::call_site()));
+            }
+        }
+
+        match (attr.spec, attr.with) {
+            (Some((spec, span)), Some(with)) => spec_impls.push(SpecImpl { spec, span, body: compute_body(Some(with), tokens("self")) }),
+            (Some((_, span)), None) => return Err(("missing `with` argument in `custom_format` attribute on the type", span)),
+            // The format specifier defaults to the name of the formatting function
+            (None, Some(with)) => match function_spec(&with) {
+                Some((spec, span)) => spec_impls.push(SpecImpl { spec, span, body: compute_body(Some(with), tokens("self")) }),
+                None => {
+                    return Err((
+                        "missing `spec` or `rename` argument in `custom_format` attribute",
+                        with.into_iter().next().map_or(Span::call_site(), |tt| tt.span()),
+                    ))
+                }
+            },
+            (None, None) => (),
+        }
+    }
+
+    if let (true, Some(body)) = (is_struct, body) {
+        spec_impls.extend(process_fields(body)?);
+    }
+
+    for (index, spec_impl) in spec_impls.iter().enumerate() {
+        if spec_impls[..index].iter().any(|other| other.spec == spec_impl.spec) {
+            return Err(("duplicate format specifier", spec_impl.span));
+        }
+
+        if spec_impls[..index].iter().any(|other| spec_value(&other.spec) == spec_value(&spec_impl.spec)) {
+            return Err(("format specifier has the same compile-time value as another format specifier, rename one of them", spec_impl.span));
+        }
+    }
+
+    let crate_path = crate_path.unwrap_or_else(|| tokens("::custom_format"));
+
+    Ok(spec_impls.into_iter().flat_map(|spec_impl| compute_impl(&crate_path, name, &generics, where_clause, spec_impl)).collect())
+}
+
+/// Main function, working with both [`proc_macro::TokenStream`] and `proc_macro2::TokenStream`
+pub(crate) fn derive_custom_format(input: TokenStream) -> TokenStream {
+    match process(input) {
+        Ok(output) => output,
+        Err((msg, span)) => {
+            // The error is emitted in item position, where the macro call must be followed by a semicolon
+            let mut semicolon = Punct::new(';', Spacing::Alone);
+            semicolon.set_span(span);
+
+            let mut output = compile_error(msg, span);
+            output.extend(Some(TokenTree::from(semicolon)));
+            output
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Remove whitespace, since the spacing of the generated tokens can differ from the parsed ones
+    fn normalize(s: &str) -> String {
+        s.split_whitespace().collect()
+    }
+
+    #[test]
+    fn test_derive() -> Result<(), Box<dyn std::error::Error>> {
+        let item = r#"
+            #[derive(Debug)]
+            #[custom_format(crate = cfmt, spec = "%D", with = "fmt_date")]
+            pub struct Date<'a, T: Copy = u8, const N: usize = 2> where T: Clone {
+                #[custom_format(spec = "%Y")]
+                pub year: &'a HashMap<T, [u8; N]>,
+                #[doc = "Month"]
+                #[custom_format(spec = "%m", with = "fmt::fmt_month")]
+                pub(crate) month: T,
+                day: T,
+            }
+        "#;
+
+        let result = r#"
+            impl<'a, T: Copy, const N: usize,> cfmt::compile_time::CustomFormat<{ cfmt::compile_time::spec("%D") }> for Date<'a, T, N,> where T: Clone {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result { fmt_date(self, f) }
+            }
+
+            impl<'a, T: Copy, const N: usize,> cfmt::compile_time::CustomFormat<{ cfmt::compile_time::spec("%Y") }> for Date<'a, T, N,> where T: Clone {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result { ::core::fmt::Display::fmt(&self.year, f) }
+            }
+
+            impl<'a, T: Copy, const N: usize,> cfmt::compile_time::CustomFormat<{ cfmt::compile_time::spec("%m") }> for Date<'a, T, N,> where T: Clone {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result { fmt::fmt_month(&self.month, f) }
+            }
+        "#;
+
+        let output = derive_custom_format(item.parse()?);
+        assert_eq!(normalize(&output.to_string()), normalize(&result.parse::<TokenStream>()?.to_string()));
+
+        let output = derive_custom_format(r#"struct Pair(u8, #[custom_format(spec = "b")] u16) where u16: Copy;"#.parse()?);
+        let result = r#"
+            impl<> ::custom_format::compile_time::CustomFormat<{ ::custom_format::compile_time::spec("b") }> for Pair<> where u16: Copy {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result { ::core::fmt::Display::fmt(&self.1, f) }
+            }
+        "#;
+        assert_eq!(normalize(&output.to_string()), normalize(&result.parse::<TokenStream>()?.to_string()));
+
+        let output = derive_custom_format(r#"#[custom_format(spec = "x", with = "hex")] enum Kind { A(u8), B }"#.parse()?);
+        assert!(normalize(&output.to_string()).contains("forKind<>"));
+
+        let item = r#"
+            #[custom_format(with = "fmt::iso_date")]
+            #[custom_format(rename = "%D", with = "fmt_date")]
+            struct Date { #[custom_format()] year: i32, #[custom_format(rename = "%m")] month: u8, #[custom_format()] r#type: u8 }
+        "#;
+
+        let output = normalize(&derive_custom_format(item.parse()?).to_string());
+        for spec in ["iso_date", "%D", "year", "%m", "type"] {
+            assert!(output.contains(&format!("spec({:?})", spec)), "{}", spec);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let data = [
+            ("fn f() {}", "expected a struct, an enum or a union"),
+            (r#"#[custom_format(spec = "a")] struct A;"#, "missing `with` argument in `custom_format` attribute on the type"),
+            (r#"#[custom_format(with = "<f>")] struct A;"#, "missing `spec` or `rename` argument in `custom_format` attribute"),
+            (r#"#[custom_format(spec = 1)] struct A;"#, "expected a string literal"),
+            (r#"#[custom_format(spec = "a", spec = "b")] struct A;"#, "duplicate `custom_format` argument"),
+            (r#"#[custom_format(name = "a")] struct A;"#, "unknown `custom_format` argument, expected `spec`, `rename`, `with` or `crate`"),
+            (r#"#[custom_format(spec = "a", rename = "b", with = "f")] struct A;"#, "the `spec` and `rename` arguments cannot be used together"),
+            (r#"#[custom_format(spec)] struct A;"#, "invalid `custom_format` argument, expected `key = value`"),
+            (r#"#[custom_format] struct A;"#, "invalid `custom_format` attribute, expected `#[custom_format(...)]`"),
+            (r#"struct A { #[custom_format(spec = "a")] a: u8, #[custom_format(spec = "a")] b: u8 }"#, "duplicate format specifier"),
+            (r#"struct A { #[custom_format(crate = cfmt, spec = "a")] a: u8 }"#, "the `crate` argument can only be used on the type"),
+            (r#"struct A(#[custom_format(with = "f")] u8);"#, "missing `spec` or `rename` argument in `custom_format` attribute on a tuple field"),
+            (r#"struct A { #[custom_format(rename = "b")] a: u8, #[custom_format()] b: u8 }"#, "duplicate format specifier"),
+            (
+                r#"#[custom_format(with = "a")] struct A { #[custom_format(rename = "a\0")] a: u8 }"#,
+                "format specifier has the same compile-time value as another format specifier, rename one of them",
+            ),
+            (
+                r#"struct A { #[custom_format(rename = "0123456789abcdefg")] a: u8, #[custom_format(rename = "0123456789abcdefg")] b: u8 }"#,
+                "duplicate format specifier",
+            ),
+        ];
+
+        for &(item, msg) in &data {
+            let output = derive_custom_format(item.parse()?);
+            assert_eq!(output.to_string(), format!("compile_error ! ({:?}) ;", msg));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+//! Functions used for formatting literal arguments at compile-time.
+
+use super::utils::StrCursor;
+use super::*;
+
+/// Literal argument value
+#[derive(Debug, PartialEq)]
+enum Value {
+    /// String literal
+    String(String),
+    /// Character literal
+    Char(char),
+    /// Boolean literal
+    Bool(bool),
+    /// Integer literal, with its sign
+    Integer { negative: bool, value: u128 },
+    /// Floating-point literal
+    Float(f64),
+    /// Floating-point literal with the `f32` suffix
+    F32(f32),
+}
+
+/// Returns the maximum magnitudes of the positive and negative values of an integer type, from the suffix of a literal.
+///
+/// The negative bound is `None` for unsigned types, which cannot be negated.
+fn integer_bounds(suffix: &str) -> Option<(u128, Option<u128>)> {
+    // The target pointer width is unknown to the procedural macro, so the largest one is used
+    let (bits, signed) = match suffix {
+        "u8" => (8, false),
+        "u16" => (16, false),
+        "u32" => (32, false),
+        "u64" | "usize" => (64, false),
+        "u128" => (128, false),
+        "i8" => (8, true),
+        "i16" => (16, true),
+        "i32" => (32, true),
+        "i64" | "isize" => (64, true),
+        "i128" => (128, true),
+        _ => return None,
+    };
+
+    Some(if signed { (u128::MAX >> (129 - bits), Some(1 << (bits - 1))) } else { (u128::MAX >> (128 - bits), None) })
+}
+
+impl Value {
+    /// Parse a literal argument
+    fn parse(expr: &Group) -> Result<Self, Error> {
+        let error = || "arguments of `concat_format!` must be string, character, boolean, integer or floating-point literals".into();
+
+        let (negative, tt) = match unwrap_groups(expr.stream()).as_slice() {
+            [TokenTree::Punct(punct), tt] if punct.as_char() == '-' => (true, tt.clone()),
+            [tt] => (false, tt.clone()),
+            _ => return Err(error()),
+        };
+
+        let value = match litrs::Literal::parse(tt.to_string()).map_err(|_| error())? {
+            litrs::Literal::Integer(lit) => {
+                let value = lit.value::<u128>().ok_or("integer literal is too large")?;
+
+                if let Some((max_positive, max_negative)) = integer_bounds(lit.suffix()) {
+                    let max = match (negative, max_negative) {
+                        (false, _) => max_positive,
+                        (true, Some(max_negative)) => max_negative,
+                        (true, None) => return Err(format!("cannot negate a literal of unsigned type `{}`", lit.suffix()).into()),
+                    };
+
+                    if value > max {
+                        return Err(format!("literal out of range for `{}`", lit.suffix()).into());
+                    }
+                }
+
+                // Negative zero is formatted like zero
+                Value::Integer { negative: negative && value != 0, value }
+            }
+            litrs::Literal::Float(lit) => {
+                let number = lit.number_part().replace('_', "");
+
+                // Values are rounded like at runtime, before being formatted with the shortest representation of their type
+                match lit.suffix() {
+                    "f32" => {
+                        let value = number.parse::<f32>().map_err(|_| error())?;
+                        Value::F32(if negative { -value } else { value })
+                    }
+                    _ => {
+                        let value = number.parse::<f64>().map_err(|_| error())?;
+                        Value::Float(if negative { -value } else { value })
+                    }
+                }
+            }
+            _ if negative => return Err(error()),
+            litrs::Literal::String(lit) => Value::String(lit.into_value().into_owned()),
+            litrs::Literal::Char(lit) => Value::Char(lit.value()),
+            litrs::Literal::Bool(lit) => Value::Bool(lit.value()),
+            _ => return Err(error()),
+        };
+
+        Ok(value)
+    }
+
+    /// Format the value with a standard format specifier
+    fn format(&self, spec: &str, output: &mut String) -> Result<(), Error> {
+        use std::fmt::Write;
+
+        let result = match (self, spec) {
+            (Value::String(value), "") => write!(output, "{}", value),
+            (Value::String(value), "?") => write!(output, "{:?}", value),
+            (Value::Char(value), "") => write!(output, "{}", value),
+            (Value::Char(value), "?") => write!(output, "{:?}", value),
+            (Value::Bool(value), "" | "?") => write!(output, "{}", value),
+            (Value::Integer { negative, value }, "" | "?") => write!(output, "{}{}", if *negative { "-" } else { "" }, value),
+            (Value::Integer { negative: false, value }, "x") => write!(output, "{:x}", value),
+            (Value::Integer { negative: false, value }, "X") => write!(output, "{:X}", value),
+            (Value::Integer { negative: false, value }, "o") => write!(output, "{:o}", value),
+            (Value::Integer { negative: false, value }, "b") => write!(output, "{:b}", value),
+            (Value::Float(value), "") => write!(output, "{}", value),
+            (Value::Float(value), "?") => write!(output, "{:?}", value),
+            (Value::F32(value), "") => write!(output, "{}", value),
+            (Value::F32(value), "?") => write!(output, "{:?}", value),
+            _ => return Err(format!("unsupported format specifier `{{:{}}}` for this argument in `concat_format!`", spec).into()),
+        };
+
+        result.map_err(|_| "formatting error".into())
+    }
+}
+
+/// Format the literal arguments at compile-time, returning the resulting string
+pub(super) fn compute_concat(arguments: &[Argument], new_format_string: &str, processed_pieces: ProcessedPieces) -> Result<String, Error> {
+    let ProcessedPieces { arg_indices, new_args, ambient_args, .. } = processed_pieces;
+
+    if !new_args.is_empty() {
+        return Err("captured identifiers are not supported in `concat_format!`, since arguments must be literals".into());
+    }
+
+    if !ambient_args.is_empty() || arg_indices.iter().any(|(_, spec)| spec.is_some()) {
+        return Err("custom format specifiers are not supported in `concat_format!`".into());
+    }
+
+    let values = arg_indices.iter().map(|&(index, _)| Value::parse(&arguments[index].expr)).collect::<Result<Vec<_>, _>>()?;
+
+    let mut output = String::new();
+    let mut cursor = StrCursor::new(new_format_string);
+
+    loop {
+        output.push_str(cursor.read_until(|c| c == '{' || c == '}'));
+
+        match cursor.next() {
+            None => return Ok(output),
+            Some(c) if cursor.remaining().starts_with(c) => {
+                cursor.next();
+                output.push(c);
+            }
+            Some(_) => {
+                let piece = cursor.read_until_included(|c| c == '}');
+                let piece = piece.strip_suffix('}').unwrap_or(piece);
+
+                let (index, spec) = match piece.find(':') {
+                    Some(position) => (&piece[..position], &piece[position + 1..]),
+                    None => (piece, ""),
+                };
+
+                let value = index.parse::<usize>().ok().and_then(|index| values.get(index)).ok_or("invalid format string")?;
+                value.format(spec, &mut output)?;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn group(s: &str) -> Group {
+        Group::new(Delimiter::Parenthesis, s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_value_parse() -> Result<(), Error> {
+        assert_eq!(Value::parse(&group(r#""a\nb""#))?, Value::String("a\nb".into()));
+        assert_eq!(Value::parse(&group("'c'"))?, Value::Char('c'));
+        assert_eq!(Value::parse(&group("true"))?, Value::Bool(true));
+        assert_eq!(Value::parse(&group("0x1F_u8"))?, Value::Integer { negative: false, value: 31 });
+        assert_eq!(Value::parse(&group("-42"))?, Value::Integer { negative: true, value: 42 });
+        assert_eq!(Value::parse(&group("-1.5e3"))?, Value::Float(-1500.0));
+        assert_eq!(Value::parse(&group("0.1f32"))?, Value::F32(0.1));
+        assert_eq!(Value::parse(&group("-0"))?, Value::Integer { negative: false, value: 0 });
+        assert_eq!(Value::parse(&group("-0i8"))?, Value::Integer { negative: false, value: 0 });
+        assert_eq!(Value::parse(&group("255u8"))?, Value::Integer { negative: false, value: 255 });
+        assert_eq!(Value::parse(&group("-128i8"))?, Value::Integer { negative: true, value: 128 });
+        assert_eq!(Value::parse(&group("0xFFFF_FFFF_FFFF_FFFFusize"))?, Value::Integer { negative: false, value: u64::MAX.into() });
+        assert_eq!(Value::parse(&group("-170141183460469231731687303715884105728i128"))?, Value::Integer { negative: true, value: 1 << 127 });
+
+        assert!(Value::parse(&group("x")).is_err());
+        assert!(Value::parse(&group("1 + 1")).is_err());
+        assert!(Value::parse(&group(r#"-"a""#)).is_err());
+        assert!(Value::parse(&group(r#"b"a""#)).is_err());
+        assert_eq!(Value::parse(&group("300u8")).unwrap_err(), "literal out of range for `u8`");
+        assert_eq!(Value::parse(&group("128i8")).unwrap_err(), "literal out of range for `i8`");
+        assert_eq!(Value::parse(&group("-129i8")).unwrap_err(), "literal out of range for `i8`");
+        assert_eq!(Value::parse(&group("0x1_0000_0000u32")).unwrap_err(), "literal out of range for `u32`");
+        assert_eq!(Value::parse(&group("-3u32")).unwrap_err(), "cannot negate a literal of unsigned type `u32`");
+        assert_eq!(Value::parse(&group("-0u8")).unwrap_err(), "cannot negate a literal of unsigned type `u8`");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_value_format() -> Result<(), Error> {
+        let data = [
+            (Value::String("a\"b".into()), "", "a\"b"),
+            (Value::String("a\"b".into()), "?", r#""a\"b""#),
+            (Value::Char('\''), "?", r"'\''"),
+            (Value::Bool(false), "", "false"),
+            (Value::Integer { negative: true, value: 7 }, "", "-7"),
+            (Value::Integer { negative: false, value: 255 }, "x", "ff"),
+            (Value::Integer { negative: false, value: 255 }, "X", "FF"),
+            (Value::Integer { negative: false, value: 8 }, "o", "10"),
+            (Value::Integer { negative: false, value: 5 }, "b", "101"),
+            (Value::Float(1.0), "", "1"),
+            (Value::Float(1.0), "?", "1.0"),
+            (Value::F32(0.1), "", "0.1"),
+            (Value::F32(-2.0), "?", "-2.0"),
+        ];
+
+        for (value, spec, result) in &data {
+            let mut output = String::new();
+            value.format(spec, &mut output)?;
+            assert_eq!(output, *result);
+        }
+
+        assert!(Value::Integer { negative: true, value: 1 }.format("x", &mut String::new()).is_err());
+        assert!(Value::String("a".into()).format(">5", &mut String::new()).is_err());
+
+        Ok(())
+    }
+}
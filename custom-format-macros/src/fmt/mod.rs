@@ -1,13 +1,18 @@
 //! Module containing procedural macros common code.
 
+mod concat;
 mod output;
 mod parse;
 mod process;
 mod utils;
 
+use concat::*;
 use output::*;
 use process::*;
 
+#[cfg(feature = "compile-time")]
+pub(crate) use utils::spec_value;
+
 #[cfg(not(test))]
 use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
 #[cfg(test)]
@@ -41,6 +46,18 @@ struct ParsedInput {
     arguments: Vec<Argument>,
     /// Span of the format string
     span: Span,
+    /// Kind of custom format specifiers allowed in the format string, if restricted
+    only: Option<SpecKind>,
+    /// Interpret compile-time specifiers as ANSI styles
+    style: bool,
+    /// Bind the arguments by value instead of by reference
+    owned: bool,
+    /// Write runtime specifiers to the byte sink given as the first argument
+    bytes: bool,
+    /// Format the literal arguments at compile-time into a string literal
+    concat: bool,
+    /// Path of the file containing the format string, if loaded with `include_str!`
+    included_file: Option<String>,
 }
 
 /// Identifier normalized in Unicode NFC
@@ -100,8 +117,31 @@ enum Precision<'a> {
 enum Spec<'a> {
     /// Format specifier checked at compile-time
     CompileTime(&'a str),
+    /// Format specifier written like `prefix(rest)` checked at compile-time, with its prefix and the remaining text.
+    ///
+    /// It uses the implementation for the whole format specifier if it exists, or the implementation for the prefix otherwise.
+    CompileTimePrefix(&'a str, &'a str, &'a str),
+    /// Format specifier checked at compile-time if implemented, with a fallback to a runtime specifier otherwise
+    CompileTimeOrRuntime(&'a str),
     /// Format specifier checked at runtime
     Runtime(&'a str),
+    /// Format specifier checked at runtime, with a fallback to the `Debug` trait if the custom formatting fails
+    RuntimeOrDebug(&'a str),
+    /// Format specifier checked at runtime, with the custom output quoted and escaped like a `Debug` string
+    RuntimeQuoted(&'a str),
+    /// Format specifier checked at runtime for a pair of arguments, with the index of the second argument.
+    ///
+    /// It is only created when processing the format string pieces.
+    RuntimePair(&'a str, usize),
+}
+
+/// Kind of custom format specifier, used for restricting the allowed specifiers with the `only` option
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum SpecKind {
+    /// Format specifiers checked at compile-time
+    CompileTime,
+    /// Format specifiers checked at runtime
+    Runtime,
 }
 
 /// Piece of a format string
@@ -120,22 +160,48 @@ enum Piece<'a> {
     CustomFmt {
         /// Kind of the positional argument
         arg_kind: ArgKind<'a>,
+        /// Optional kind of the width argument
+        arg_kind_width: Option<ArgKind<'a>>,
+        /// Optional kind of the precision argument
+        arg_kind_precision: Option<ArgKind<'a>>,
         /// Custom format specifier
         spec: Spec<'a>,
     },
+    /// Custom format specifier data for a pair of arguments, like `{a, b :<ratio>}`
+    CustomFmtPair {
+        /// Kinds of the two positional arguments
+        arg_kinds: [ArgKind<'a>; 2],
+        /// Optional kind of the width argument
+        arg_kind_width: Option<ArgKind<'a>>,
+        /// Optional kind of the precision argument
+        arg_kind_precision: Option<ArgKind<'a>>,
+        /// Custom format specifier, which is always checked at runtime
+        spec: &'a str,
+    },
+    /// Ambient format specifier data, formatted by a registered provider without argument
+    Ambient {
+        /// Name of the provider
+        name: &'a str,
+    },
+    /// Custom format specifier data with standard padding flags applied to the whole custom output, like `{x:>10! :%Y}`
+    Padded(Box<Piece<'a>>),
 }
 
 /// Processed elements of the format string pieces
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct ProcessedPieces<'a> {
     /// Argument indices associated to the format string pieces, with custom format specifiers if applicable
     arg_indices: Vec<(usize, Option<Spec<'a>>)>,
+    /// Positions in the argument indices of the custom format specifiers padded by a wrapper
+    padded_positions: Vec<usize>,
     /// List of new arguments to be added from captured identifiers in the format string, if not already existing
     new_args: Vec<&'a str>,
+    /// List of ambient provider names to be added as new arguments, after the captured identifiers
+    ambient_args: Vec<&'a str>,
 }
 
 /// Create tokens representing a compilation error
-fn compile_error(msg: &str, span: Span) -> TokenStream {
+pub(crate) fn compile_error(msg: &str, span: Span) -> TokenStream {
     let mut tokens = vec![
         TokenTree::from(Ident::new("compile_error", span)),
         TokenTree::from(Punct::new('!', Spacing::Alone)),
@@ -151,12 +217,12 @@ fn compile_error(msg: &str, span: Span) -> TokenStream {
 
 /// Main function, working with both [`proc_macro::TokenStream`] and `proc_macro2::TokenStream`
 pub(crate) fn fmt(input: TokenStream) -> TokenStream {
-    let (format_string, parsed_input) = match parse_tokens(input) {
+    let (format_string, mut parsed_input) = match parse_tokens(input) {
         Err(compile_error) => return compile_error,
         Ok(x) => x,
     };
 
-    let (new_format_string, pieces) = match parse_format_string(&format_string) {
+    let (new_format_string, pieces) = match parse_format_string(&format_string, parsed_input.only) {
         Err(error) => return compile_error(&error, parsed_input.span),
         Ok(x) => x,
     };
@@ -166,5 +232,135 @@ pub(crate) fn fmt(input: TokenStream) -> TokenStream {
         Ok(x) => x,
     };
 
-    compute_output(parsed_input, &new_format_string, processed_pieces)
+    let included_file = parsed_input.included_file.take();
+
+    let mut output = if parsed_input.concat {
+        match compute_concat(&parsed_input.arguments, &new_format_string, processed_pieces) {
+            Err(error) => return compile_error(&error, parsed_input.span),
+            Ok(x) => TokenTree::from(Literal::string(&x)).into(),
+        }
+    } else {
+        compute_output(parsed_input, &new_format_string, processed_pieces)
+    };
+
+    // Keep the `include_str!` call in the output, so that the compiler tracks the file for recompilation
+    if let Some(included_file) = included_file {
+        output = add_include_str_dependency(output, &included_file);
+    }
+
+    if cfg!(feature = "debug-expansion") {
+        add_format_string_note(output, &format_string)
+    } else {
+        output
+    }
+}
+
+/// Expand the procedural macro input, returning the output tokens as a string.
+///
+/// The expected output can be normalized with [`normalize`] before comparing.
+#[cfg(test)]
+pub(crate) fn expand(input: &str) -> String {
+    fmt(input.parse().unwrap()).to_string()
+}
+
+/// Normalize Rust tokens as a string, for comparison with the output of [`expand`]
+#[cfg(test)]
+pub(crate) fn normalize(tokens: &str) -> String {
+    tokens.parse::<TokenStream>().unwrap().to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let data = [
+            (
+                r#"crate, [::std::println!], [], [("{x :%a}, {} {0:?}"), (1)]"#,
+                r#"match (&(1), &x) { (arg0, arg1) => ::std::println!("{0}, {1} {2:?}", crate::checked_custom_formatter!("%a", "type `{Self}` does not support the custom format specifier `%a`", arg1), arg0, arg0), }"#,
+            ),
+            (
+                r#"crate, [::std::writeln!], [f], [("{:>5 :<x>}"), (&y)]"#,
+                r#"match ((&y)) { (arg0) => ::std::writeln!(f, "{0:>5}", crate::runtime::CustomFormatter::new("x", arg0)), }"#,
+            ),
+            (
+                r#"crate, [::std::format!], [], [("{0 :<x>} {1 :<x>} {2 :<x>}"), (&x.y), (&a..b), (&mut z)]"#,
+                r#"match ((&x.y), &(&a..b), &(&mut z)) { (arg0, arg1, arg2) => ::std::format!("{0} {1} {2}", crate::runtime::CustomFormatter::new("x", arg0), crate::runtime::CustomFormatter::new("x", arg1), crate::runtime::CustomFormatter::new("x", arg2)), }"#,
+            ),
+            (
+                r#"crate, [::core::format_args!], [], [("{a :%a}"), ((a) = (g()))]"#,
+                r#"::core::format_args!("{0}", crate::checked_custom_formatter!("%a", "type `{Self}` does not support the custom format specifier `%a`", &(g())))"#,
+            ),
+            (r#"crate, [::std::format!], [], [("{}")]"#, r#"compile_error!("invalid positional argument index: 0")"#),
+            (
+                r#"crate, [::std::format!], [], [("{x :>8:%a} {x :<6:b>}")]"#,
+                r#"match (&x) { (arg0) => ::std::format!("{0} {1}", crate::checked_custom_formatter!(">8:%a", "type `{Self}` does not support the custom format specifier `>8:%a`", arg0), crate::runtime::CustomFormatter::new("6:b", arg0)), }"#,
+            ),
+            (
+                r#"crate, [::std::format!], [], [("{x :<%a>|?}")]"#,
+                r#"match (&x) { (arg0) => ::std::format!("{0}", crate::runtime::DebugFallback::new("%a", arg0)), }"#,
+            ),
+            (
+                r#"crate, [::std::format!], [], [("{0 :%a}"), (1)], [only = "compile-time"]"#,
+                r#"match (&(1)) { (arg0) => ::std::format!("{0}", crate::checked_custom_formatter!("%a", "type `{Self}` does not support the custom format specifier `%a`", arg0)), }"#,
+            ),
+            (
+                r#"crate, [::std::format!], [], [("{0 :%a}"), (1)], [only = "runtime"]"#,
+                r#"compile_error!("compile-time specifier used but only runtime specifiers are allowed")"#,
+            ),
+            (
+                r#"crate, [::std::format!], [], [("{0 :%a}"), (1)], [only = "std"]"#,
+                r#"compile_error!("invalid value for the `only` option, expected \"compile-time\" or \"runtime\"")"#,
+            ),
+            (
+                r#"crate, [::core::format_args!], [], [("{0 :<x>}"), (1)], [only = "runtime", newline]"#,
+                r#"::core::format_args!("{0}\n", crate::runtime::CustomFormatter::new("x", &(1)))"#,
+            ),
+            (r#"crate, [::core::format_args!], [], [("{{}}")], [newline,]"#, r#"::core::format_args!("{{}}\n")"#),
+            (
+                r#"crate, [::std::println!], [], [("{0 :red} {0:?}"), (1)], [style]"#,
+                r#"match (&(1)) { (arg0) => ::std::println!("{0} {1:?}", crate::checked_custom_formatter!("red", "type `{Self}` does not support the custom format specifier `red`", &crate::style::Styled(arg0)), arg0), }"#,
+            ),
+            (
+                r#"crate, [crate::owned_format_args_fmt!], [], [("{0:?} {0 :<x>} {1} {y}"), (f()), (&z)], [owned]"#,
+                r#"match ((f()), (&z), y) { (arg0, arg1, arg2) => crate::owned_format_args_fmt!("{0:?} {1} {2} {3}", &arg0, crate::runtime::CustomFormatter::new("x", &arg0), arg1, &arg2), }"#,
+            ),
+            (
+                r#"crate, [crate::write_bytes_fmt!], [sink], [("{0 :<hex>} {0:?} {0 :x}"), (1)], [bytes]"#,
+                r#"match (&(1)) { (arg0) => crate::write_bytes_fmt!(sink, "{0} {1:?} {2}", crate::bytes::BytesFormatter::new("hex", arg0, &sink), arg0, crate::checked_custom_formatter!("x", "type `{Self}` does not support the custom format specifier `x`", arg0)), }"#,
+            ),
+            (r#"crate, [], [], [("{{{}}} {0:?} {1:x} {2:?}"), ("a"), (255), (-1.0)], [concat]"#, r#""{a} \"a\" ff -1.0""#),
+            (
+                r#"crate, [], [], [("{0} {x}"), (1)], [concat]"#,
+                r#"compile_error!("captured identifiers are not supported in `concat_format!`, since arguments must be literals")"#,
+            ),
+            (r#"crate, [], [], [("{0 :x}"), (1)], [concat]"#, r#"compile_error!("custom format specifiers are not supported in `concat_format!`")"#),
+            (
+                r#"crate, [], [], [("{0:>5}"), (1)], [concat]"#,
+                r#"compile_error!("unsupported format specifier `{:>5}` for this argument in `concat_format!`")"#,
+            ),
+            (
+                r#"crate, [::core::panic!], [], [("{x :<%a>} is not {0 :%b}"), (y)]"#,
+                r#"match (&(y), &x) { (arg0, arg1) => ::core::panic!("{0} is not {1}", crate::runtime::CustomFormatter::new("%a", arg1), crate::checked_custom_formatter!("%b", "type `{Self}` does not support the custom format specifier `%b`", arg0)), }"#,
+            ),
+            (
+                r#"crate, [::core::format_args!], [], [("value = {0 :<upper>}"), (format_args!("{:#x}", value))]"#,
+                r#"::core::format_args!("value = {0}", crate::runtime::CustomFormatter::new("upper", &(format_args!("{:#x}", value))))"#,
+            ),
+            (
+                r#"crate, [::std::eprintln!], [], [("[{}:{}] {x :%a}"), (file!()), (line!())]"#,
+                r#"match (&(file!()), &(line!()), &x) { (arg0, arg1, arg2) => ::std::eprintln!("[{0}:{1}] {2}", arg0, arg1, crate::checked_custom_formatter!("%a", "type `{Self}` does not support the custom format specifier `%a`", arg2)), }"#,
+            ),
+            (r#"crate, [::std::format!], [], [("")], [newline = true]"#, r#"compile_error!("invalid tokens")"#),
+            (
+                r#"crate, [::std::format!], [], [("{type :%a} {match}"), ((r#type) = (1))]"#,
+                r#"match (&(1), &r#match) { (arg0, arg1) => ::std::format!("{0} {1}", crate::checked_custom_formatter!("%a", "type `{Self}` does not support the custom format specifier `%a`", arg0), arg1), }"#,
+            ),
+        ];
+
+        for &(input, output) in &data {
+            assert_eq!(expand(input), normalize(output));
+        }
+    }
 }
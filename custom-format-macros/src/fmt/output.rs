@@ -8,26 +8,123 @@ fn push_two_colons(v: &mut Vec<TokenTree>) {
     v.push(Punct::new(':', Spacing::Alone).into());
 }
 
-/// Push `$crate::custom_formatter!` to the list of token trees
-fn push_compile_time_formatter(v: &mut Vec<TokenTree>, crate_ident: &Ident) {
+/// Create an identifier, which is raw if the name is a keyword
+fn new_ident(name: &str, span: Span) -> Ident {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn", "else", "enum", "extern", "false", "final", "fn",
+        "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+        "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+
+    if KEYWORDS.contains(&name) {
+        Ident::new_raw(name, span)
+    } else {
+        Ident::new(name, span)
+    }
+}
+
+/// Push `$crate::{name}!` to the list of token trees
+fn push_root_macro(v: &mut Vec<TokenTree>, crate_ident: &Ident, name: &str) {
     v.push(crate_ident.clone().into());
     push_two_colons(v);
-    v.push(Ident::new("custom_formatter", Span::call_site()).into());
+    v.push(Ident::new(name, Span::call_site()).into());
     v.push(Punct::new('!', Spacing::Alone).into());
 }
 
-/// Push `$crate::runtime::CustomFormatter::new` to the list of token trees
-fn push_runtime_formatter(v: &mut Vec<TokenTree>, crate_ident: &Ident) {
+/// Push `$crate::runtime::{formatter}::new` to the list of token trees
+fn push_runtime_formatter(v: &mut Vec<TokenTree>, crate_ident: &Ident, formatter: &str) {
+    v.push(crate_ident.clone().into());
+    push_two_colons(v);
+    v.push(Ident::new("runtime", Span::call_site()).into());
+    push_two_colons(v);
+    v.push(Ident::new(formatter, Span::call_site()).into());
+    push_two_colons(v);
+    v.push(Ident::new("new", Span::call_site()).into());
+}
+
+/// Push `$crate::bytes::BytesFormatter::new` to the list of token trees
+fn push_bytes_formatter(v: &mut Vec<TokenTree>, crate_ident: &Ident) {
+    v.push(crate_ident.clone().into());
+    push_two_colons(v);
+    v.push(Ident::new("bytes", Span::call_site()).into());
+    push_two_colons(v);
+    v.push(Ident::new("BytesFormatter", Span::call_site()).into());
+    push_two_colons(v);
+    v.push(Ident::new("new", Span::call_site()).into());
+}
+
+/// Push `$crate::runtime::AmbientFormatter::new(name)` to the list of token trees
+fn push_ambient_formatter(v: &mut Vec<TokenTree>, crate_ident: &Ident, name: &str) {
     v.push(crate_ident.clone().into());
     push_two_colons(v);
     v.push(Ident::new("runtime", Span::call_site()).into());
     push_two_colons(v);
-    v.push(Ident::new("CustomFormatter", Span::call_site()).into());
+    v.push(Ident::new("AmbientFormatter", Span::call_site()).into());
     push_two_colons(v);
     v.push(Ident::new("new", Span::call_site()).into());
+    v.push(Group::new(Delimiter::Parenthesis, TokenTree::from(Literal::string(name)).into()).into());
+}
+
+/// Check if an argument expression is already a shared reference to a simple expression, like `&x`, `&x.y` or `&f(x)`.
+///
+/// Expressions containing operators are excluded, since `&a == b` or `&a & b` are not references.
+/// This includes range operators, since `&a..b` is parsed as `(&a)..b`.
+fn is_reference(expr: &Group) -> bool {
+    let mut has_reference = false;
+    let mut is_prefix = true;
+    let mut previous_dot = false;
+
+    for tt in expr.stream() {
+        let is_dot = matches!(&tt, TokenTree::Punct(punct) if punct.as_char() == '.');
+
+        match tt {
+            TokenTree::Punct(punct) if is_prefix && punct.as_char() == '&' => has_reference = true,
+            TokenTree::Punct(punct) if is_dot && (previous_dot || punct.spacing() == Spacing::Joint) => return false,
+            TokenTree::Punct(punct) if !matches!(punct.as_char(), '.' | ':') => return false,
+            TokenTree::Ident(ident) if is_prefix && &ident.to_string() == "mut" => return false,
+            TokenTree::Ident(ident) if &ident.to_string() == "as" => return false,
+            _ => is_prefix = false,
+        }
+
+        if !has_reference {
+            return false;
+        }
+
+        previous_dot = is_dot;
+    }
+
+    has_reference && !is_prefix
+}
+
+/// Push `&$crate::style::Styled(arg)` to the list of token trees
+fn push_styled_arg(v: &mut Vec<TokenTree>, crate_ident: &Ident, arg: TokenStream) {
+    v.push(Punct::new('&', Spacing::Alone).into());
+    v.push(crate_ident.clone().into());
+    push_two_colons(v);
+    v.push(Ident::new("style", Span::call_site()).into());
+    push_two_colons(v);
+    v.push(Ident::new("Styled", Span::call_site()).into());
+    v.push(Group::new(Delimiter::Parenthesis, arg).into());
+}
+
+/// Push `&$crate::Padded(formatter)` to the list of token trees
+fn push_padded_arg(v: &mut Vec<TokenTree>, crate_ident: &Ident, formatter: TokenStream) {
+    v.push(Punct::new('&', Spacing::Alone).into());
+    v.push(crate_ident.clone().into());
+    push_two_colons(v);
+    v.push(Ident::new("Padded", Span::call_site()).into());
+    v.push(Group::new(Delimiter::Parenthesis, formatter).into());
+}
+
+/// Compute the message of the compilation error reported when a compile-time format specifier is not implemented
+fn missing_impl_message(spec: &str) -> String {
+    // Braces are used for the parameters of the message
+    let spec = spec.replace('{', "{{").replace('}', "}}");
+    format!("type `{{Self}}` does not support the custom format specifier `{}`", spec)
 }
 
 /// Push the whole macro call to the list of token trees
+#[allow(clippy::too_many_arguments)]
 fn push_macro_call(
     v: &mut Vec<TokenTree>,
     crate_ident: Ident,
@@ -35,10 +132,15 @@ fn push_macro_call(
     first_arg: Option<TokenStream>,
     new_format_string: &str,
     arg_indices: Vec<(usize, Option<Spec>)>,
+    padded_positions: &[usize],
     args: &[TokenStream],
+    style: bool,
+    bytes: bool,
 ) {
     v.extend(root_macro);
 
+    let sink = if bytes { first_arg.clone() } else { None };
+
     v.push(TokenTree::from(Group::new(Delimiter::Parenthesis, {
         let mut fmt_args = Vec::<TokenTree>::new();
 
@@ -49,28 +151,101 @@ fn push_macro_call(
 
         fmt_args.push(TokenTree::from(Literal::string(new_format_string)));
 
-        for (index, spec) in arg_indices {
+        for (position, (index, spec)) in arg_indices.into_iter().enumerate() {
             fmt_args.push(Punct::new(',', Spacing::Alone).into());
+            let start = fmt_args.len();
 
             match spec {
                 None => fmt_args.extend(args[index].clone()),
                 Some(spec) => {
-                    let spec_literal = match spec {
+                    let is_compile_time = matches!(spec, Spec::CompileTime(_));
+                    let mut message = None;
+
+                    let (spec_literal, second_index) = match spec {
+                        // The empty specifier falls back to the `Display` trait if it is not implemented
+                        Spec::CompileTime("") if !style => {
+                            push_root_macro(&mut fmt_args, &crate_ident, "empty_spec_formatter");
+                            (Literal::string(""), None)
+                        }
+                        Spec::Runtime("") if sink.is_none() => {
+                            push_root_macro(&mut fmt_args, &crate_ident, "runtime_empty_spec_formatter");
+                            (Literal::string(""), None)
+                        }
                         Spec::CompileTime(spec) => {
-                            push_compile_time_formatter(&mut fmt_args, &crate_ident);
-                            Literal::string(spec)
+                            push_root_macro(&mut fmt_args, &crate_ident, "checked_custom_formatter");
+                            message = Some(missing_impl_message(spec));
+                            (Literal::string(spec), None)
+                        }
+                        Spec::CompileTimePrefix(spec, ..) => {
+                            push_root_macro(&mut fmt_args, &crate_ident, "checked_prefix_formatter");
+                            message = Some(missing_impl_message(spec));
+                            (Literal::string(spec), None)
+                        }
+                        Spec::CompileTimeOrRuntime(spec) => {
+                            push_root_macro(&mut fmt_args, &crate_ident, "fallback_formatter");
+                            (Literal::string(spec), None)
+                        }
+                        Spec::Runtime(spec) if sink.is_some() => {
+                            push_bytes_formatter(&mut fmt_args, &crate_ident);
+                            (Literal::string(spec), None)
                         }
                         Spec::Runtime(spec) => {
-                            push_runtime_formatter(&mut fmt_args, &crate_ident);
-                            Literal::string(spec)
+                            push_runtime_formatter(&mut fmt_args, &crate_ident, "CustomFormatter");
+                            (Literal::string(spec), None)
+                        }
+                        Spec::RuntimeOrDebug(spec) => {
+                            push_runtime_formatter(&mut fmt_args, &crate_ident, "DebugFallback");
+                            (Literal::string(spec), None)
+                        }
+                        Spec::RuntimeQuoted(spec) => {
+                            push_runtime_formatter(&mut fmt_args, &crate_ident, "DebugQuoted");
+                            (Literal::string(spec), None)
+                        }
+                        Spec::RuntimePair(spec, second_index) => {
+                            push_runtime_formatter(&mut fmt_args, &crate_ident, "CustomFormatter2");
+                            (Literal::string(spec), Some(second_index))
                         }
                     };
 
                     fmt_args.push(TokenTree::from(Group::new(Delimiter::Parenthesis, {
                         let mut stream = vec![spec_literal.into(), Punct::new(',', Spacing::Alone).into()];
-                        stream.extend(args[index].clone());
+
+                        if let Spec::CompileTimePrefix(_, prefix, rest) = spec {
+                            stream.push(Literal::string(prefix).into());
+                            stream.push(Punct::new(',', Spacing::Alone).into());
+                            stream.push(Literal::string(rest).into());
+                            stream.push(Punct::new(',', Spacing::Alone).into());
+                        }
+
+                        if let Some(message) = message {
+                            stream.push(Literal::string(&message).into());
+                            stream.push(Punct::new(',', Spacing::Alone).into());
+                        }
+
+                        if style && is_compile_time {
+                            push_styled_arg(&mut stream, &crate_ident, args[index].clone());
+                        } else {
+                            stream.extend(args[index].clone());
+                        }
+
+                        if let Some(second_index) = second_index {
+                            stream.push(Punct::new(',', Spacing::Alone).into());
+                            stream.extend(args[second_index].clone());
+                        }
+
+                        if let (Some(sink), Spec::Runtime(_)) = (&sink, spec) {
+                            stream.push(Punct::new(',', Spacing::Alone).into());
+                            stream.push(Punct::new('&', Spacing::Alone).into());
+                            stream.extend(sink.clone());
+                        }
+
                         stream.into_iter().collect()
                     })));
+
+                    if padded_positions.contains(&position) {
+                        let formatter = fmt_args.split_off(start).into_iter().collect();
+                        push_padded_arg(&mut fmt_args, &crate_ident, formatter);
+                    }
                 }
             }
         }
@@ -79,28 +254,59 @@ fn push_macro_call(
     })));
 }
 
+/// Check if the root macro is the `format_args!` macro, whatever the spelling of its path
+fn is_format_args(root_macro: &TokenStream) -> bool {
+    let macro_name = root_macro.clone().into_iter().filter_map(|tt| if let TokenTree::Ident(ident) = tt { Some(ident) } else { None }).last();
+    macro_name.map_or(false, |ident| &ident.to_string() == "format_args")
+}
+
 /// Compute output Rust code
 pub(super) fn compute_output(parsed_input: ParsedInput, new_format_string: &str, processed_pieces: ProcessedPieces) -> TokenStream {
-    let ParsedInput { crate_ident, root_macro, first_arg, arguments, span } = parsed_input;
-    let ProcessedPieces { arg_indices, new_args } = processed_pieces;
+    let ParsedInput { crate_ident, root_macro, first_arg, arguments, span, style, owned, bytes, .. } = parsed_input;
+    let ProcessedPieces { arg_indices, padded_positions, new_args, ambient_args } = processed_pieces;
+
+    let ambient_exprs = ambient_args.into_iter().map(|name| {
+        let mut expr = Vec::new();
+        push_ambient_formatter(&mut expr, &crate_ident, name);
+        TokenTree::from(Group::new(Delimiter::Parenthesis, expr.into_iter().collect()))
+    });
 
-    let arg_exprs: Vec<TokenStream> = arguments
+    let args: Vec<(bool, TokenTree)> = arguments
         .into_iter()
-        .map(|arg| arg.expr.into())
-        .chain(new_args.into_iter().map(|name| Ident::new(name, span).into()))
-        .map(|tt| vec![TokenTree::from(Punct::new('&', Spacing::Alone)), tt].into_iter().collect())
+        .map(|arg| (is_reference(&arg.expr), arg.expr.into()))
+        .chain(new_args.into_iter().map(|name| (false, new_ident(name, span).into())))
+        .chain(ambient_exprs.map(|tt| (false, tt)))
         .collect();
 
-    let arg_idents: Vec<TokenStream> =
-        (0..arg_exprs.len()).map(|index| TokenTree::from(Ident::new(&format!("arg{}", index), Span::call_site())).into()).collect();
+    let arg_idents: Vec<Ident> = (0..args.len()).map(|index| Ident::new(&format!("arg{}", index), Span::call_site())).collect();
 
-    // Don't use a `match` for the `format_args!` macro because it creates temporary values
-    if let Some(TokenTree::Ident(ident)) = root_macro.clone().into_iter().nth(5) {
-        if &ident.to_string() == "format_args" {
-            let mut output = Vec::new();
-            push_macro_call(&mut output, crate_ident, root_macro, first_arg, new_format_string, arg_indices, &arg_exprs);
-            return output.into_iter().collect();
+    // Don't add another reference to arguments which are already references
+    let add_reference = |is_reference: bool, tt: TokenTree| -> TokenStream {
+        if is_reference {
+            tt.into()
+        } else {
+            vec![TokenTree::from(Punct::new('&', Spacing::Alone)), tt].into_iter().collect()
         }
+    };
+
+    // Arguments bound by value are referenced in the macro call instead of in the `match` expression
+    let (arg_exprs, arg_values): (Vec<TokenStream>, Vec<TokenStream>) = args
+        .into_iter()
+        .zip(&arg_idents)
+        .map(|((is_reference, tt), ident)| {
+            if owned {
+                (tt.into(), add_reference(is_reference, ident.clone().into()))
+            } else {
+                (add_reference(is_reference, tt), TokenTree::from(ident.clone()).into())
+            }
+        })
+        .unzip();
+
+    // Don't use a `match` for the `format_args!` macro because it creates temporary values
+    if is_format_args(&root_macro) {
+        let mut output = Vec::new();
+        push_macro_call(&mut output, crate_ident, root_macro, first_arg, new_format_string, arg_indices, &padded_positions, &arg_exprs, style, bytes);
+        return output.into_iter().collect();
     }
 
     let mut output = vec![Ident::new("match", Span::call_site()).into()];
@@ -121,10 +327,10 @@ pub(super) fn compute_output(parsed_input: ParsedInput, new_format_string: &str,
         let mut block = Vec::new();
 
         block.push(TokenTree::from(Group::new(Delimiter::Parenthesis, {
-            let mut arm_pat = Vec::new();
+            let mut arm_pat = Vec::<TokenTree>::new();
 
             for arg_ident in &arg_idents {
-                arm_pat.extend(arg_ident.clone());
+                arm_pat.push(arg_ident.clone().into());
                 arm_pat.push(Punct::new(',', Spacing::Alone).into());
             }
 
@@ -135,7 +341,7 @@ pub(super) fn compute_output(parsed_input: ParsedInput, new_format_string: &str,
         block.push(Punct::new('=', Spacing::Joint).into());
         block.push(Punct::new('>', Spacing::Alone).into());
 
-        push_macro_call(&mut block, crate_ident, root_macro, first_arg, new_format_string, arg_indices, &arg_idents);
+        push_macro_call(&mut block, crate_ident, root_macro, first_arg, new_format_string, arg_indices, &padded_positions, &arg_values, style, bytes);
 
         block.push(Punct::new(',', Spacing::Alone).into());
 
@@ -145,10 +351,67 @@ pub(super) fn compute_output(parsed_input: ParsedInput, new_format_string: &str,
     output.into_iter().collect()
 }
 
+/// Prepend an unused string constant to the output: `{ const _: &str = value; output }`
+fn add_str_constant(output: TokenStream, value: impl IntoIterator<Item = TokenTree>) -> TokenStream {
+    let mut block = vec![
+        TokenTree::from(Ident::new("const", Span::call_site())),
+        TokenTree::from(Ident::new("_", Span::call_site())),
+        TokenTree::from(Punct::new(':', Spacing::Alone)),
+        TokenTree::from(Punct::new('&', Spacing::Alone)),
+        TokenTree::from(Ident::new("str", Span::call_site())),
+        TokenTree::from(Punct::new('=', Spacing::Alone)),
+    ];
+
+    block.extend(value);
+    block.push(Punct::new(';', Spacing::Alone).into());
+    block.extend(output);
+
+    TokenTree::from(Group::new(Delimiter::Brace, block.into_iter().collect())).into()
+}
+
+/// Prepend the original format string to the output as an unused constant: `{ const _: &str = "original"; output }`.
+///
+/// This is only used for debugging the macro expansion, and has no runtime effect.
+pub(super) fn add_format_string_note(output: TokenStream, format_string: &str) -> TokenStream {
+    add_str_constant(output, Some(TokenTree::from(Literal::string(format_string))))
+}
+
+/// Prepend an unused `include_str!` call to the output: `{ const _: &str = ::core::include_str!("path"); output }`.
+///
+/// The file is then tracked by the compiler, and the output is recompiled when the file changes.
+pub(super) fn add_include_str_dependency(output: TokenStream, path: &str) -> TokenStream {
+    let mut value = Vec::new();
+    push_two_colons(&mut value);
+    value.push(Ident::new("core", Span::call_site()).into());
+    push_two_colons(&mut value);
+    value.push(Ident::new("include_str", Span::call_site()).into());
+    value.push(Punct::new('!', Spacing::Alone).into());
+    value.push(Group::new(Delimiter::Parenthesis, TokenTree::from(Literal::string(path)).into()).into());
+
+    add_str_constant(output, value)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    /// Construct the parsed input of a macro call without options
+    fn parsed_input(root_macro: &str, arguments: Vec<Argument>) -> Result<ParsedInput, Box<dyn std::error::Error>> {
+        Ok(ParsedInput {
+            crate_ident: Ident::new("crate", Span::call_site()),
+            root_macro: root_macro.parse()?,
+            first_arg: None,
+            arguments,
+            span: Span::call_site(),
+            only: None,
+            style: false,
+            owned: false,
+            bytes: false,
+            concat: false,
+            included_file: None,
+        })
+    }
+
     #[test]
     fn test_compute_output() -> Result<(), Box<dyn std::error::Error>> {
         let create_argument = |name: Option<&str>, s| {
@@ -162,7 +425,7 @@ mod test {
                 concat!(
                     r#"match (&("0"), &("1"), &("2"), &("3"), &h, &g) { (arg0, arg1, arg2, arg3, arg4, arg5) => "#,
                     r#"::std::println!("{0}, {1}, {2}, {3}, {4}, {5}, {6:.7$}, {8:9$}", arg4, "#,
-                    r#"crate::custom_formatter!("%z", arg4), arg1, arg1, arg3, "#,
+                    r#"crate::checked_custom_formatter!("%z", "type `{Self}` does not support the custom format specifier `%z`", arg4), arg1, arg1, arg3, "#,
                     r#"crate::runtime::CustomFormatter::new("%x", arg2), arg1, arg0, arg3, arg5), }"#
                 ),
             ),
@@ -170,7 +433,7 @@ mod test {
                 "::core::format_args!",
                 concat!(
                     r#"::core::format_args!("{0}, {1}, {2}, {3}, {4}, {5}, {6:.7$}, {8:9$}", &h, "#,
-                    r#"crate::custom_formatter!("%z", &h), &("1"), &("1"), &("3"), "#,
+                    r#"crate::checked_custom_formatter!("%z", "type `{Self}` does not support the custom format specifier `%z`", &h), &("1"), &("1"), &("3"), "#,
                     r#"crate::runtime::CustomFormatter::new("%x", &("2")), "#,
                     r#"&("1"), &("0"), &("3"), &g)"#,
                 ),
@@ -197,17 +460,8 @@ mod test {
 
             let new_args = vec!["h", "g"];
 
-            let output = compute_output(
-                ParsedInput {
-                    crate_ident: Ident::new("crate", Span::call_site()),
-                    root_macro: root_macro.parse()?,
-                    first_arg: None,
-                    arguments,
-                    span: Span::call_site(),
-                },
-                new_format_string,
-                ProcessedPieces { arg_indices, new_args },
-            );
+            let output =
+                compute_output(parsed_input(root_macro, arguments)?, new_format_string, ProcessedPieces { arg_indices, new_args, ..Default::default() });
 
             assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
         }
@@ -216,21 +470,227 @@ mod test {
     }
 
     #[test]
-    fn test_compute_output_with_first_arg() -> Result<(), Box<dyn std::error::Error>> {
+    fn test_is_reference() -> Result<(), Box<dyn std::error::Error>> {
+        let data = [
+            ("&x", true),
+            ("&&x", true),
+            ("&x.y.0", true),
+            ("&f(x)[0]", true),
+            ("&::std::f64::MAX", true),
+            ("&(a + b)", true),
+            ("&", false),
+            ("x", false),
+            ("*&x", false),
+            ("&mut x", false),
+            ("&a == &b", false),
+            ("&a & b", false),
+            ("&x as *const u8", false),
+            ("&f::<u8>()", false),
+            ("&x?", false),
+            ("&a..b", false),
+            ("&a..=b", false),
+            ("&a..", false),
+            ("&..b", false),
+            ("&a. .b", false),
+            ("&x.0.y", true),
+        ];
+
+        for &(expr, result) in &data {
+            assert_eq!(is_reference(&Group::new(Delimiter::Parenthesis, expr.parse()?)), result, "{}", expr);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_output_with_reference() -> Result<(), Box<dyn std::error::Error>> {
+        let arguments = ["&a", "&a == &b", "&mut c"]
+            .iter()
+            .map(|expr| Ok(Argument { ident: None, expr: Group::new(Delimiter::Parenthesis, expr.parse()?) }))
+            .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+
+        let output = compute_output(
+            parsed_input("::std::format!", arguments)?,
+            "{0} {1} {2}",
+            ProcessedPieces { arg_indices: vec![(0, Some(Spec::Runtime("x"))), (1, None), (2, None)], new_args: vec![], ..Default::default() },
+        );
+
+        let result = concat!(
+            r#"match ((&a), &(&a == &b), &(&mut c)) { "#,
+            r#"(arg0, arg1, arg2) => ::std::format!("{0} {1} {2}", crate::runtime::CustomFormatter::new("x", arg0), arg1, arg2), }"#
+        );
+
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_output_with_pair() -> Result<(), Box<dyn std::error::Error>> {
+        let arguments = ["a", "&b"]
+            .iter()
+            .map(|expr| Ok(Argument { ident: None, expr: Group::new(Delimiter::Parenthesis, expr.parse()?) }))
+            .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+
         let output = compute_output(
-            ParsedInput {
-                crate_ident: Ident::new("crate", Span::call_site()),
-                root_macro: "::std::writeln!".parse()?,
-                first_arg: Some("f".parse()?),
-                arguments: vec![],
-                span: Span::call_site(),
+            parsed_input("::std::format!", arguments)?,
+            "{0} {1}",
+            ProcessedPieces {
+                arg_indices: vec![(0, Some(Spec::RuntimePair("ratio", 1))), (1, Some(Spec::RuntimePair("ratio", 0)))],
+                new_args: vec![],
+                ..Default::default()
             },
+        );
+
+        let result = concat!(
+            r#"match (&(a), (&b)) { "#,
+            r#"(arg0, arg1) => ::std::format!("{0} {1}", crate::runtime::CustomFormatter2::new("ratio", arg0, arg1), crate::runtime::CustomFormatter2::new("ratio", arg1, arg0)), }"#
+        );
+
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_format_string_note() -> Result<(), Box<dyn std::error::Error>> {
+        let output = add_format_string_note(r#"::std::format!("{0}", crate::custom_formatter!("%a", &(x)))"#.parse()?, "{x :%a}");
+
+        let result = r#"{ const _: &str = "{x :%a}"; ::std::format!("{0}", crate::custom_formatter!("%a", &(x))) }"#;
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_include_str_dependency() -> Result<(), Box<dyn std::error::Error>> {
+        let output = add_include_str_dependency(r#"::std::format!("{0}", x)"#.parse()?, "/path/to/file.txt");
+
+        let result = r#"{ const _: &str = ::core::include_str!("/path/to/file.txt"); ::std::format!("{0}", x) }"#;
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_format_args() -> Result<(), Box<dyn std::error::Error>> {
+        for root_macro in ["::core::format_args!", "core::format_args!", "::std::format_args!", "std :: format_args !", "format_args!"] {
+            assert!(is_format_args(&root_macro.parse()?), "{}", root_macro);
+        }
+
+        for root_macro in ["::std::format!", "::core::write!", "format_args::format!", "call!", ""] {
+            assert!(!is_format_args(&root_macro.parse()?), "{}", root_macro);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_output_with_format_args_path() -> Result<(), Box<dyn std::error::Error>> {
+        let output = compute_output(
+            parsed_input("core::format_args!", vec![Argument { ident: None, expr: Group::new(Delimiter::Parenthesis, "h".parse()?) }])?,
+            "{0}",
+            ProcessedPieces { arg_indices: vec![(0, Some(Spec::Runtime("x")))], new_args: vec![], ..Default::default() },
+        );
+
+        let result = r#"core::format_args!("{0}", crate::runtime::CustomFormatter::new("x", &(h)))"#;
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_output_with_first_arg() -> Result<(), Box<dyn std::error::Error>> {
+        let output = compute_output(
+            ParsedInput { first_arg: Some("f".parse()?), ..parsed_input("::std::writeln!", vec![])? },
             "string",
-            ProcessedPieces { arg_indices: vec![], new_args: vec![] },
+            ProcessedPieces { arg_indices: vec![], new_args: vec![], ..Default::default() },
         );
 
         assert_eq!(output.to_string(), "match () { () => ::std::writeln!(f, \"string\"), }".parse::<TokenStream>()?.to_string());
 
         Ok(())
     }
+
+    #[test]
+    fn test_compute_output_with_ambient_args() -> Result<(), Box<dyn std::error::Error>> {
+        let output = compute_output(
+            parsed_input("::std::format!", vec![])?,
+            "{0} {1}",
+            ProcessedPieces { arg_indices: vec![(1, None), (0, None)], new_args: vec!["h"], ambient_args: vec!["now"], ..Default::default() },
+        );
+
+        let result =
+            concat!(r#"match (&h, &(crate::runtime::AmbientFormatter::new("now"))) { "#, r#"(arg0, arg1) => ::std::format!("{0} {1}", arg1, arg0), }"#);
+
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_output_with_prefix_and_fallback() -> Result<(), Box<dyn std::error::Error>> {
+        let output = compute_output(
+            parsed_input("::core::format_args!", vec![])?,
+            "{0} {1}",
+            ProcessedPieces {
+                arg_indices: vec![(0, Some(Spec::CompileTimePrefix("%N(6)", "%N", "6"))), (0, Some(Spec::CompileTimeOrRuntime("%Y")))],
+                new_args: vec!["h"],
+                ..Default::default()
+            },
+        );
+
+        let result = concat!(
+            r#"::core::format_args!("{0} {1}", "#,
+            r#"crate::checked_prefix_formatter!("%N(6)", "%N", "6", "type `{Self}` does not support the custom format specifier `%N(6)`", &h), "#,
+            r#"crate::fallback_formatter!("%Y", &h))"#,
+        );
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_impl_message() {
+        assert_eq!(missing_impl_message("%a"), "type `{Self}` does not support the custom format specifier `%a`");
+        assert_eq!(missing_impl_message("{x}"), "type `{Self}` does not support the custom format specifier `{{x}}`");
+    }
+
+    #[test]
+    fn test_compute_output_with_empty_specs() -> Result<(), Box<dyn std::error::Error>> {
+        let output = compute_output(
+            parsed_input("::core::format_args!", vec![])?,
+            "{0} {1}",
+            ProcessedPieces { arg_indices: vec![(0, Some(Spec::CompileTime(""))), (0, Some(Spec::Runtime("")))], new_args: vec!["h"], ..Default::default() },
+        );
+
+        let result = r#"::core::format_args!("{0} {1}", crate::empty_spec_formatter!("", &h), crate::runtime_empty_spec_formatter!("", &h))"#;
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_output_with_padding() -> Result<(), Box<dyn std::error::Error>> {
+        let output = compute_output(
+            parsed_input("::core::format_args!", vec![])?,
+            "{0:>10} {1:<5} {2}",
+            ProcessedPieces {
+                arg_indices: vec![(0, Some(Spec::CompileTime("%Y"))), (0, Some(Spec::Runtime("x"))), (0, Some(Spec::Runtime("x")))],
+                padded_positions: vec![0, 1],
+                new_args: vec!["h"],
+                ..Default::default()
+            },
+        );
+
+        let result = concat!(
+            r#"::core::format_args!("{0:>10} {1:<5} {2}", "#,
+            r#"&crate::Padded(crate::checked_custom_formatter!("%Y", "type `{Self}` does not support the custom format specifier `%Y`", &h)), "#,
+            r#"&crate::Padded(crate::runtime::CustomFormatter::new("x", &h)), "#,
+            r#"crate::runtime::CustomFormatter::new("x", &h))"#,
+        );
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        Ok(())
+    }
 }
@@ -142,6 +142,11 @@ pub(super) fn parse_argument<'a>(cursor: &mut StrCursor<'a>) -> Result<Option<Ar
         }
     };
 
+    // Like the standard macros, a raw identifier argument `r#type` is referenced as `type` in the format string
+    if identifier == "r" && cursor.remaining().starts_with('#') {
+        return Err("invalid argument: raw identifiers are not supported in format strings".into());
+    }
+
     Ok(Some(ArgKind::Named(Id::new(identifier)?)))
 }
 
@@ -221,6 +226,7 @@ mod test {
         }
 
         assert_eq!(&*parse_argument(&mut StrCursor::new("_")).unwrap_err(), "invalid argument: argument name cannot be a single underscore");
+        assert_eq!(&*parse_argument(&mut StrCursor::new("r#type")).unwrap_err(), "invalid argument: raw identifiers are not supported in format strings");
 
         assert_eq!(
             &*parse_argument(&mut StrCursor::new("A\u{30a}")).unwrap_err(),
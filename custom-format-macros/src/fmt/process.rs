@@ -1,6 +1,6 @@
 //! Functions used for processing input.
 
-use super::utils::StrCursor;
+use super::utils::{spec_value, StrCursor};
 use super::*;
 
 use std::collections::hash_map::{Entry, HashMap};
@@ -41,25 +41,41 @@ pub(super) fn parse_tokens(input: TokenStream) -> Result<(String, ParsedInput),
         _ => return Err(compile_error("invalid tokens", Span::call_site())),
     };
 
+    let options = match args_iter.next() {
+        None => Options::default(),
+        Some([TokenTree::Group(group)]) => parse_options(group.stream())?,
+        _ => return Err(compile_error("invalid tokens", Span::call_site())),
+    };
+
     let mut remaining_iter = remaining.split(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ',' ));
 
-    let (format_string, span) = match remaining_iter.next() {
-        Some([TokenTree::Group(group)]) => {
-            let mut stream_iter = group.stream().into_iter();
-            match (stream_iter.next(), stream_iter.next()) {
-                (Some(tt), None) => {
-                    let span = tt.span();
-                    match litrs::StringLit::parse(tt.to_string()) {
-                        Ok(lit) => (lit.into_value().into_owned(), span),
-                        Err(e) => return Err(compile_error(&e.to_string(), span)),
-                    }
+    let (mut format_string, span, included_file) = match remaining_iter.next() {
+        Some([TokenTree::Group(group)]) => match unwrap_groups(group.stream()).as_slice() {
+            [tt] => (parse_string_literal(tt)?, tt.span(), None),
+            // Like `concat!`, the `include_str!` macro is not expanded before the proc-macro, so the file is read here
+            [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Group(group)] if &ident.to_string() == "include_str" && punct.as_char() == '!' => {
+                // The path literal is the only token which always comes from the invoking source file
+                let (path, path_span) = match unwrap_groups(group.stream()).as_slice() {
+                    [tt] => (parse_string_literal(tt)?, tt.span()),
+                    _ => return Err(compile_error("invalid tokens", group.span())),
+                };
+
+                let included_file = resolve_included_file(&path, path_span);
+
+                match std::fs::read_to_string(&included_file) {
+                    Ok(contents) => (contents, path_span, Some(included_file.display().to_string())),
+                    Err(e) => return Err(compile_error(&format!("couldn't read `{}`: {}", included_file.display(), e), path_span)),
                 }
-                _ => return Err(compile_error("invalid tokens", Span::call_site())),
             }
-        }
+            _ => return Err(compile_error("invalid tokens", Span::call_site())),
+        },
         _ => return Err(compile_error("invalid tokens", Span::call_site())),
     };
 
+    if options.newline {
+        format_string.push('\n');
+    }
+
     let arguments = remaining_iter
         .map(|x| match x {
             [TokenTree::Group(group)] => {
@@ -78,7 +94,8 @@ pub(super) fn parse_tokens(input: TokenStream) -> Result<(String, ParsedInput),
                     if let (Some(TokenTree::Ident(_)), None) = (g1_iter.next(), g1_iter.next()) {
                         if let (Some(TokenTree::Punct(punct)), Some(TokenTree::Group(inner_group)), None) = (tt2, tt3, tt4) {
                             if punct.as_char() == '=' && punct.spacing() == Spacing::Alone {
-                                ident = Some(g1_inner);
+                                // Raw identifiers are referenced without their `r#` prefix in the format string
+                                ident = Some(g1_inner.strip_prefix("r#").map(str::to_owned).unwrap_or(g1_inner));
                                 stream = inner_group.stream();
                             }
                         }
@@ -91,12 +108,197 @@ pub(super) fn parse_tokens(input: TokenStream) -> Result<(String, ParsedInput),
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    Ok((format_string, ParsedInput { crate_ident, root_macro, first_arg, arguments, span }))
+    Ok((
+        format_string,
+        ParsedInput {
+            crate_ident,
+            root_macro,
+            first_arg,
+            arguments,
+            span,
+            only: options.only,
+            style: options.style,
+            owned: options.owned,
+            bytes: options.bytes,
+            concat: options.concat,
+            included_file,
+        },
+    ))
+}
+
+/// Unwrap the token trees of nested groups containing a single token tree, like the invisible groups of captured fragments
+pub(super) fn unwrap_groups(stream: TokenStream) -> Vec<TokenTree> {
+    let mut token_trees: Vec<_> = stream.into_iter().collect();
+
+    while let [TokenTree::Group(group)] = token_trees.as_slice() {
+        match group.delimiter() {
+            Delimiter::None | Delimiter::Parenthesis => token_trees = group.stream().into_iter().collect(),
+            _ => break,
+        }
+    }
+
+    token_trees
+}
+
+/// Parse a string literal
+fn parse_string_literal(tt: &TokenTree) -> Result<String, TokenStream> {
+    match litrs::StringLit::parse(tt.to_string()) {
+        Ok(lit) => Ok(lit.into_value().into_owned()),
+        Err(e) => Err(compile_error(&e.to_string(), tt.span())),
+    }
+}
+
+/// Resolve the path of a file included with `include_str!`.
+///
+/// A relative path is resolved from the directory of the invoking source file when available (since Rust 1.88),
+/// or from the `CARGO_MANIFEST_DIR` directory otherwise.
+fn resolve_included_file(path: &str, span: Span) -> std::path::PathBuf {
+    #[cfg(all(not(test), has_span_local_file))]
+    #[allow(clippy::incompatible_msrv)] // Only compiled since Rust 1.88
+    let base_dir = span.local_file().and_then(|file| file.parent().map(|dir| dir.to_owned()));
+    #[cfg(not(all(not(test), has_span_local_file)))]
+    let base_dir = {
+        let _ = span;
+        None
+    };
+
+    let base_dir = base_dir.or_else(|| std::env::var_os("CARGO_MANIFEST_DIR").map(std::path::PathBuf::from)).unwrap_or_default();
+    let included_file = base_dir.join(path);
+
+    // The path of the source file may be relative to the working directory of the compiler
+    match std::env::current_dir() {
+        Ok(current_dir) if included_file.is_relative() => current_dir.join(included_file),
+        _ => included_file,
+    }
+}
+
+/// Macro options
+#[derive(Debug, Default)]
+struct Options {
+    /// Kind of custom format specifiers allowed in the format string, if restricted
+    only: Option<SpecKind>,
+    /// Append a newline to the format string
+    newline: bool,
+    /// Interpret compile-time specifiers as ANSI styles
+    style: bool,
+    /// Bind the arguments by value instead of by reference
+    owned: bool,
+    /// Write runtime specifiers to the byte sink given as the first argument
+    bytes: bool,
+    /// Format the literal arguments at compile-time into a string literal
+    concat: bool,
+}
+
+/// Parse macro options
+fn parse_options(input: TokenStream) -> Result<Options, TokenStream> {
+    let token_trees: Vec<_> = input.into_iter().collect();
+
+    let mut options = Options::default();
+
+    for option in token_trees.split(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ',' )) {
+        match option {
+            [] => (),
+            [TokenTree::Ident(ident), TokenTree::Punct(punct), value] if &ident.to_string() == "only" && punct.as_char() == '=' => {
+                options.only = match litrs::StringLit::parse(value.to_string()).as_ref().map(|lit| lit.value()) {
+                    Ok("compile-time") => Some(SpecKind::CompileTime),
+                    Ok("runtime") => Some(SpecKind::Runtime),
+                    _ => return Err(compile_error(r#"invalid value for the `only` option, expected "compile-time" or "runtime""#, value.span())),
+                }
+            }
+            // Internal option used by the `format_args_nl!` macro
+            [TokenTree::Ident(ident)] if &ident.to_string() == "newline" => options.newline = true,
+            // Internal option used by the `cprint!` and `cprintln!` macros
+            [TokenTree::Ident(ident)] if &ident.to_string() == "style" => options.style = true,
+            // Internal option used by the `owned_format_args!` macro
+            [TokenTree::Ident(ident)] if &ident.to_string() == "owned" => options.owned = true,
+            // Internal option used by the `write_bytes!` macro
+            [TokenTree::Ident(ident)] if &ident.to_string() == "bytes" => options.bytes = true,
+            // Internal option used by the `concat_format!` macro
+            [TokenTree::Ident(ident)] if &ident.to_string() == "concat" => options.concat = true,
+            _ => return Err(compile_error("invalid tokens", Span::call_site())),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Process standard format flags following a `:` char, returning the optional kinds of the width and precision arguments
+fn process_std_flags<'a>(
+    cursor: &mut StrCursor<'a>,
+    arg_kind_position: &mut ArgKind<'a>,
+    has_arg_kind: bool,
+    current_positional_index: &mut usize,
+    new_format_string: &mut String,
+    new_current_index: &mut usize,
+) -> Result<(Option<ArgKind<'a>>, Option<ArgKind<'a>>), Error> {
+    let mut arg_kind_width = None;
+    let mut arg_kind_precision = None;
+
+    new_format_string.push(':');
+    new_format_string.extend(parse::process_align(cursor).iter().flatten());
+    new_format_string.extend(parse::process_sign(cursor));
+    new_format_string.extend(parse::process_alternate(cursor));
+    new_format_string.extend(parse::process_sign_aware_zero_pad(cursor));
+
+    match parse::process_width(cursor)? {
+        None => (),
+        Some(Count::Integer(integer)) => *new_format_string += integer,
+        Some(Count::Argument(arg_kind_for_width)) => {
+            arg_kind_width = Some(arg_kind_for_width);
+            write!(new_format_string, "{}$", *new_current_index).unwrap();
+            *new_current_index += 1;
+        }
+    }
+
+    match parse::process_precision(cursor)? {
+        None => (),
+        Some(Precision::Asterisk) => {
+            let new_arg_kind = ArgKind::Positional(*current_positional_index);
+            *current_positional_index += 1;
+
+            if has_arg_kind {
+                arg_kind_precision = Some(new_arg_kind);
+            } else {
+                arg_kind_precision = Some(std::mem::replace(arg_kind_position, new_arg_kind));
+            }
+
+            write!(new_format_string, ".{}$", *new_current_index).unwrap();
+            *new_current_index += 1;
+        }
+        Some(Precision::WithCount(Count::Integer(integer))) => write!(new_format_string, ".{}", integer).unwrap(),
+        Some(Precision::WithCount(Count::Argument(arg_kind_for_precision))) => {
+            arg_kind_precision = Some(arg_kind_for_precision);
+            write!(new_format_string, ".{}$", *new_current_index).unwrap();
+            *new_current_index += 1;
+        }
+    };
+
+    Ok((arg_kind_width, arg_kind_precision))
+}
+
+/// Check if a format argument is an ambient specifier, like `:<now>`.
+///
+/// This is not a valid standard format specifier, since the alignment cannot be followed by an identifier and a `>` char.
+fn is_ambient_fmt(inner: &str) -> bool {
+    match inner.strip_prefix(":<").and_then(|x| x.strip_suffix('>')) {
+        Some(name) => !name.is_empty() && name.chars().all(unicode_ident::is_xid_continue),
+        None => false,
+    }
+}
+
+/// Check if standard flags are made of padding flags only, like `>10`, `*^w$` or an empty string.
+///
+/// Only the fill, alignment and width flags are allowed.
+fn is_padding(flags: &str) -> bool {
+    let mut cursor = StrCursor::new(flags);
+    parse::process_align(&mut cursor);
+    parse::process_width(&mut cursor).is_ok() && cursor.remaining().is_empty()
 }
 
 /// Process formatting argument
 fn process_fmt<'a>(
     fmt: &'a str,
+    only: Option<SpecKind>,
     current_positional_index: &mut usize,
     new_format_string: &mut String,
     new_current_index: &mut usize,
@@ -116,23 +318,138 @@ fn process_fmt<'a>(
 
             let mut spec_chars = specifier.chars();
             let spec = match (spec_chars.next(), spec_chars.next_back()) {
-                (Some('<'), Some('>')) => Spec::Runtime(spec_chars.as_str()),
+                // A runtime specifier ending with `?` quotes the custom output like a `Debug` string, except for the `<?>` specifier
+                (Some('<'), Some('>')) => match spec_chars.as_str() {
+                    spec if spec.len() > 1 && spec.ends_with('?') => Spec::RuntimeQuoted(&spec[..spec.len() - 1]),
+                    spec => Spec::Runtime(spec),
+                },
+                // A runtime specifier followed by `|?` falls back to the `Debug` trait if the custom formatting fails
+                (Some('<'), Some('?')) if specifier.ends_with(">|?") => Spec::RuntimeOrDebug(&specifier[1..specifier.len() - 3]),
+                // A compile-time specifier followed by `|<>` falls back to a runtime specifier if it is not implemented
+                (Some(_), Some('>')) if specifier.len() > 3 && specifier.ends_with("|<>") => Spec::CompileTimeOrRuntime(&specifier[..specifier.len() - 3]),
+                // A compile-time specifier written as `prefix(rest)` is mapped to the implementation for its prefix
+                (Some(_), Some(')')) => match specifier.find('(') {
+                    Some(position) if position > 0 => Spec::CompileTimePrefix(specifier, &specifier[..position], &specifier[position + 1..specifier.len() - 1]),
+                    _ => Spec::CompileTime(specifier),
+                },
                 _ => Spec::CompileTime(specifier),
             };
 
-            let mut cursor = StrCursor::new(&inner[..position]);
+            // A custom specifier can reference a pair of arguments, like `{a, b :<ratio>}`.
+            // Only a comma before the standard flags is a separator, since it can also be a fill char.
+            let argument = &inner[..position];
+            let (first_argument, argument) = match argument.find(':').map_or(argument, |colon| &argument[..colon]).find(',') {
+                Some(comma) => (Some(&argument[..comma]), argument[comma + 1..].trim_start_matches(' ')),
+                None => (None, argument),
+            };
 
-            let arg_kind = parse::parse_argument(&mut cursor)?.unwrap_or_else(|| {
+            match (spec, only) {
+                (Spec::CompileTime(_) | Spec::CompileTimePrefix(..) | Spec::CompileTimeOrRuntime(_), Some(SpecKind::Runtime)) => {
+                    return Err("compile-time specifier used but only runtime specifiers are allowed".into())
+                }
+                (Spec::Runtime(_) | Spec::RuntimeOrDebug(_) | Spec::RuntimeQuoted(_) | Spec::CompileTimeOrRuntime(_), Some(SpecKind::CompileTime)) => {
+                    return Err("runtime specifier used but only compile-time specifiers are allowed".into())
+                }
+                _ => (),
+            }
+
+            match spec {
+                Spec::CompileTimeOrRuntime(_) if !cfg!(all(feature = "compile-time", feature = "runtime")) => {
+                    return Err("compile-time specifier with a runtime fallback used but `compile-time` or `runtime` feature is disabled".into())
+                }
+                Spec::CompileTime(_) | Spec::CompileTimePrefix(..) if !cfg!(feature = "compile-time") => {
+                    return Err("compile-time specifier used but `compile-time` feature is disabled".into())
+                }
+                Spec::Runtime(_) | Spec::RuntimeOrDebug(_) | Spec::RuntimeQuoted(_) if !cfg!(feature = "runtime") => {
+                    return Err("runtime specifier used but `runtime` feature is disabled".into())
+                }
+                _ => (),
+            }
+
+            if first_argument.is_some() && !matches!(spec, Spec::Runtime(_)) {
+                return Err("custom specifiers referencing two arguments must be runtime specifiers".into());
+            }
+
+            let first_arg_kind = match first_argument {
+                None => None,
+                Some(first_argument) => {
+                    let mut cursor = StrCursor::new(first_argument);
+
+                    let arg_kind = parse::parse_argument(&mut cursor)?.unwrap_or_else(|| {
+                        let arg_kind = ArgKind::Positional(*current_positional_index);
+                        *current_positional_index += 1;
+                        arg_kind
+                    });
+
+                    if !cursor.remaining().is_empty() {
+                        return Err(format!("invalid argument reference before custom specifier: `{}`", first_argument).into());
+                    }
+
+                    Some(arg_kind)
+                }
+            };
+
+            let mut cursor = StrCursor::new(argument);
+
+            let mut has_arg_kind = true;
+            let mut arg_kind = parse::parse_argument(&mut cursor)?.unwrap_or_else(|| {
                 let arg_kind = ArgKind::Positional(*current_positional_index);
                 *current_positional_index += 1;
+                has_arg_kind = false;
                 arg_kind
             });
 
-            if !cursor.remaining().is_empty() {
-                return Err("invalid format string".into());
+            let mut arg_kind_width = None;
+            let mut arg_kind_precision = None;
+            let mut padded = false;
+
+            match cursor.next() {
+                Some(':') => {
+                    // Standard flags followed by `!` pad the whole custom output with a wrapper, like in `{x:>10! :%Y}`
+                    if let Some(flags) = cursor.remaining().strip_suffix('!') {
+                        if !is_padding(flags) {
+                            return Err(format!("only fill, alignment and width flags can pad the custom output: `{}`", argument).into());
+                        }
+                        padded = true;
+                    }
+
+                    let (width, precision) =
+                        process_std_flags(&mut cursor, &mut arg_kind, has_arg_kind, current_positional_index, new_format_string, new_current_index)?;
+
+                    if cursor.remaining() != if padded { "!" } else { "" } {
+                        return Err(format!("invalid standard flags before custom specifier: `{}`", argument).into());
+                    }
+
+                    arg_kind_width = width;
+                    arg_kind_precision = precision;
+                }
+                None => (),
+                _ => return Err(format!("invalid argument reference before custom specifier: `{}`", argument).into()),
             }
 
-            Piece::CustomFmt { arg_kind, spec }
+            let piece = match (first_arg_kind, spec) {
+                (Some(first_arg_kind), Spec::Runtime(spec)) => {
+                    Piece::CustomFmtPair { arg_kinds: [first_arg_kind, arg_kind], arg_kind_width, arg_kind_precision, spec }
+                }
+                _ => Piece::CustomFmt { arg_kind, arg_kind_width, arg_kind_precision, spec },
+            };
+
+            if padded {
+                Piece::Padded(Box::new(piece))
+            } else {
+                piece
+            }
+        }
+        None if is_ambient_fmt(inner) => {
+            if only == Some(SpecKind::CompileTime) {
+                return Err("ambient specifier used but only compile-time specifiers are allowed".into());
+            }
+
+            if !cfg!(feature = "std") {
+                return Err("ambient specifier used but `std` feature is disabled".into());
+            }
+
+            Piece::Ambient { name: &inner[2..inner.len() - 1] }
         }
         None => {
             let mut cursor = StrCursor::new(inner);
@@ -150,45 +467,11 @@ fn process_fmt<'a>(
 
             match cursor.next() {
                 Some(':') => {
-                    new_format_string.push(':');
-                    new_format_string.extend(parse::process_align(&mut cursor).iter().flatten());
-                    new_format_string.extend(parse::process_sign(&mut cursor));
-                    new_format_string.extend(parse::process_alternate(&mut cursor));
-                    new_format_string.extend(parse::process_sign_aware_zero_pad(&mut cursor));
-
-                    match parse::process_width(&mut cursor)? {
-                        None => (),
-                        Some(Count::Integer(integer)) => *new_format_string += integer,
-                        Some(Count::Argument(arg_kind_for_width)) => {
-                            arg_kind_width = Some(arg_kind_for_width);
-                            write!(new_format_string, "{}$", *new_current_index).unwrap();
-                            *new_current_index += 1;
-                        }
-                    }
+                    let (width, precision) =
+                        process_std_flags(&mut cursor, &mut arg_kind_position, has_arg_kind, current_positional_index, new_format_string, new_current_index)?;
 
-                    match parse::process_precision(&mut cursor)? {
-                        None => (),
-                        Some(Precision::Asterisk) => {
-                            let new_arg_kind = ArgKind::Positional(*current_positional_index);
-                            *current_positional_index += 1;
-
-                            if has_arg_kind {
-                                arg_kind_precision = Some(new_arg_kind);
-                            } else {
-                                arg_kind_precision = Some(arg_kind_position);
-                                arg_kind_position = new_arg_kind;
-                            }
-
-                            write!(new_format_string, ".{}$", *new_current_index).unwrap();
-                            *new_current_index += 1;
-                        }
-                        Some(Precision::WithCount(Count::Integer(integer))) => write!(new_format_string, ".{}", integer).unwrap(),
-                        Some(Precision::WithCount(Count::Argument(arg_kind_for_precision))) => {
-                            arg_kind_precision = Some(arg_kind_for_precision);
-                            write!(new_format_string, ".{}$", *new_current_index).unwrap();
-                            *new_current_index += 1;
-                        }
-                    };
+                    arg_kind_width = width;
+                    arg_kind_precision = precision;
 
                     *new_format_string += cursor.remaining();
                 }
@@ -206,7 +489,7 @@ fn process_fmt<'a>(
 }
 
 /// Parse format string
-pub(super) fn parse_format_string(format_string: &str) -> Result<(String, Vec<Piece>), Error> {
+pub(super) fn parse_format_string(format_string: &str, only: Option<SpecKind>) -> Result<(String, Vec<Piece<'_>>), Error> {
     let mut cursor = StrCursor::new(format_string);
     let mut current_positional_index = 0;
 
@@ -229,7 +512,7 @@ pub(super) fn parse_format_string(format_string: &str) -> Result<(String, Vec<Pi
         }
 
         let fmt = cursor.read_until_included(|c| c == '}');
-        pieces.push(process_fmt(fmt, &mut current_positional_index, &mut new_format_string, &mut new_current_index)?);
+        pieces.push(process_fmt(fmt, only, &mut current_positional_index, &mut new_format_string, &mut new_current_index)?);
     }
 
     Ok((new_format_string, pieces))
@@ -255,28 +538,24 @@ pub(super) fn process_pieces<'a>(pieces: Vec<Piece<'a>>, arguments: &[Argument])
 
     let mut arg_indices = Vec::new();
     let mut new_args = Vec::new();
+    let mut ambient_args = Vec::new();
+    let mut ambient_positions = Vec::new();
     let mut used_args = vec![false; arguments.len()];
 
-    let mut process_arg_kind = |arg_kind: &_, spec| {
+    let mut process_arg_kind = |arg_kind: &_| {
         let index = match *arg_kind {
             ArgKind::Positional(index) => {
                 if index >= arguments.len() {
                     return Err(format!("invalid positional argument index: {}", index));
                 }
 
-                arg_indices.push((index, spec));
                 index
             }
             ArgKind::Named(ref ident) => match named_args_positions.entry(ident.name().to_owned()) {
-                Entry::Occupied(entry) => {
-                    let index = *entry.get();
-                    arg_indices.push((index, spec));
-                    index
-                }
+                Entry::Occupied(entry) => *entry.get(),
                 Entry::Vacant(entry) => {
                     let new_index = arguments.len() + new_args.len();
                     entry.insert(new_index);
-                    arg_indices.push((new_index, spec));
                     new_args.push(ident.name());
                     new_index
                 }
@@ -287,17 +566,49 @@ pub(super) fn process_pieces<'a>(pieces: Vec<Piece<'a>>, arguments: &[Argument])
             *used = true;
         }
 
-        Ok(())
+        Ok(index)
     };
 
+    let mut padded_positions = Vec::new();
+
     for piece in pieces {
+        let piece = match piece {
+            Piece::Padded(piece) => {
+                padded_positions.push(arg_indices.len());
+                *piece
+            }
+            piece => piece,
+        };
+
         match piece {
             Piece::StdFmt { arg_kind_position, arg_kind_width, arg_kind_precision } => {
                 for arg_kind in [Some(arg_kind_position), arg_kind_width, arg_kind_precision].iter().flatten() {
-                    process_arg_kind(arg_kind, None)?;
+                    arg_indices.push((process_arg_kind(arg_kind)?, None));
                 }
             }
-            Piece::CustomFmt { arg_kind, spec } => process_arg_kind(&arg_kind, Some(spec))?,
+            Piece::CustomFmt { arg_kind, arg_kind_width, arg_kind_precision, spec } => {
+                arg_indices.push((process_arg_kind(&arg_kind)?, Some(spec)));
+
+                for arg_kind in [arg_kind_width, arg_kind_precision].iter().flatten() {
+                    arg_indices.push((process_arg_kind(arg_kind)?, None));
+                }
+            }
+            Piece::CustomFmtPair { arg_kinds: [first_arg_kind, second_arg_kind], arg_kind_width, arg_kind_precision, spec } => {
+                let first_index = process_arg_kind(&first_arg_kind)?;
+                let second_index = process_arg_kind(&second_arg_kind)?;
+                arg_indices.push((first_index, Some(Spec::RuntimePair(spec, second_index))));
+
+                for arg_kind in [arg_kind_width, arg_kind_precision].iter().flatten() {
+                    arg_indices.push((process_arg_kind(arg_kind)?, None));
+                }
+            }
+            Piece::Ambient { name } => {
+                // The argument index is only known after all captured identifiers are processed
+                ambient_positions.push(arg_indices.len());
+                arg_indices.push((ambient_args.len(), None));
+                ambient_args.push(name);
+            }
+            Piece::Padded(_) => unreachable!("padded pieces are not nested"),
         }
     }
 
@@ -308,7 +619,32 @@ pub(super) fn process_pieces<'a>(pieces: Vec<Piece<'a>>, arguments: &[Argument])
         };
     }
 
-    Ok(ProcessedPieces { arg_indices, new_args })
+    for position in ambient_positions {
+        arg_indices[position].0 += arguments.len() + new_args.len();
+    }
+
+    let mut spec_values = HashMap::new();
+    for (_, spec) in &arg_indices {
+        let specs = match *spec {
+            Some(Spec::CompileTime(spec) | Spec::CompileTimeOrRuntime(spec)) => [Some(spec), None],
+            Some(Spec::CompileTimePrefix(spec, prefix, _)) => [Some(spec), Some(prefix)],
+            _ => [None, None],
+        };
+
+        for spec in specs.iter().flatten().copied() {
+            match spec_values.entry(spec_value(spec)) {
+                Entry::Occupied(entry) if *entry.get() != spec => {
+                    return Err(format!("format specifiers `{}` and `{}` have the same compile-time value, rename one of them", entry.get(), spec).into());
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(entry) => {
+                    entry.insert(spec);
+                }
+            }
+        }
+    }
+
+    Ok(ProcessedPieces { arg_indices, padded_positions, new_args, ambient_args })
 }
 
 #[cfg(test)]
@@ -374,28 +710,85 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_tokens_include_str() -> Result<(), Box<dyn std::error::Error>> {
+        let s = r#"crate, [::std::format!], [], [(include_str!("../custom-format-tests/tests/fixtures/hex.txt")), ((name) = (1))]"#;
+        let (format_string, parsed_input) = parse_tokens(s.parse()?).unwrap();
+
+        assert_eq!(format_string, "{name}: {value :x} ({value :<X>})\n");
+        assert_eq!(parsed_input.arguments.len(), 1);
+
+        let included_file = std::path::PathBuf::from(parsed_input.included_file.unwrap());
+        assert!(included_file.is_absolute());
+        assert!(included_file.ends_with("custom-format-tests/tests/fixtures/hex.txt"));
+
+        let err = parse_tokens(r#"crate, [::std::format!], [], [(include_str!("missing.txt"))]"#.parse()?).unwrap_err();
+        assert!(err.to_string().starts_with("compile_error"));
+        assert!(err.into_iter().last().unwrap().to_string().contains("couldn't read"));
+
+        let err = parse_tokens(r#"crate, [::std::format!], [], [(include_str!(42))]"#.parse()?).unwrap_err();
+        assert!(err.to_string().starts_with("compile_error"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_process_fmt() -> Result<(), Error> {
         #[rustfmt::skip]
         let data = [
-            ("{ :}",            "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::CompileTime("") }),
-            ("{ : \t\r\n }",    "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::CompileTime("") }),
-            ("{ :\u{2000} }",   "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::CompileTime("") }),
-            ("{ : : : }",       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::CompileTime(" : :") }),
-            ("{ : <: :> }",     "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::CompileTime(" <: :>") }),
-            ("{ : éà }" ,       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::CompileTime(" éà") }),
-            ("{ : <éà> }" ,     "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::CompileTime(" <éà>") }),
-            ("{3 :%a }",        "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(3),         spec: Spec::CompileTime("%a") }),
-            ("{éà :%a}",        "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("éà")?), spec: Spec::CompileTime("%a") }),
-            ("{éà :<<<>>%a><}", "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("éà")?), spec: Spec::CompileTime("<<<>>%a><") }),
-            ("{ :<>}",          "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::Runtime("") }),
-            ("{ :<> \t\r\n }",  "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::Runtime("") }),
-            ("{ :<>\u{2000} }", "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::Runtime("") }),
-            ("{ :< : :> }",     "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::Runtime(" : :") }),
-            ("{ :<%a> }",       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         spec: Spec::Runtime("%a") }),
-            ("{3 :<%a> }",      "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(3),         spec: Spec::Runtime("%a") }),
-            ("{éà :<%a>}",      "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("éà")?), spec: Spec::Runtime("%a") }),
-            ("{éà :<<<>>%a>}",  "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("éà")?), spec: Spec::Runtime("<<>>%a") }),
+            ("{ :}",            "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("") }),
+            ("{ : \t\r\n }",    "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("") }),
+            ("{ :\u{2000} }",   "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("") }),
+            ("{ : : : }",       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime(" : :") }),
+            ("{ : <: :> }",     "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime(" <: :>") }),
+            ("{ : éà }" ,       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime(" éà") }),
+            ("{ : <éà> }" ,     "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime(" <éà>") }),
+            ("{3 :%a }",        "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(3),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%a") }),
+            ("{éà :%a}",        "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("éà")?), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%a") }),
+            ("{éà :<<<>>%a><}", "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("éà")?), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("<<<>>%a><") }),
+            ("{ :<>}",          "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("") }),
+            ("{ :<> \t\r\n }",  "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("") }),
+            ("{ :<>\u{2000} }", "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("") }),
+            ("{ :< : :> }",     "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime(" : :") }),
+            ("{ :<%a> }",       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("%a") }),
+            ("{ :<e\u{301}>}", "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("e\u{301}") }),
+            ("{3 :<%a> }",      "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(3),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("%a") }),
+            ("{éà :<%a>}",      "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("éà")?), arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("%a") }),
+            ("{éà :<<<>>%a>}",  "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("éà")?), arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("<<>>%a") }),
+            ("{ :<%a>|?}",      "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::RuntimeOrDebug("%a") }),
+            ("{x :<>|? }",      "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?),  arg_kind_width: None, arg_kind_precision: None, spec: Spec::RuntimeOrDebug("") }),
+            ("{ :<%a?>}",       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::RuntimeQuoted("%a") }),
+            ("{x:>8 :<??> }",   "{0:>8}",          0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?),  arg_kind_width: None, arg_kind_precision: None, spec: Spec::RuntimeQuoted("?") }),
+            ("{ :<?>}",         "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("?") }),
+            ("{ :%a?}",         "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%a?") }),
+            ("{ :<%a>|x}",      "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("<%a>|x") }),
+            ("{ :%a|?}",        "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%a|?") }),
+            ("{ :%N(6)}",       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTimePrefix("%N(6)", "%N", "6") }),
+            ("{ :a(b(c))}",     "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTimePrefix("a(b(c))", "a", "b(c)") }),
+            ("{ :(x)}",         "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("(x)") }),
+            ("{ :%a)}",         "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%a)") }),
+            ("{ :%Y|<>}",       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTimeOrRuntime("%Y") }),
+            ("{ :|<>}",         "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("|<>") }),
+            ("{x:★>10 :%Y}",    "{0:★>10}",        0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?),  arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::CompileTime("%Y") }),
+            ("{:+#08.3 :<%a>}", "{0:+#08.3}",      1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::Runtime("%a") }),
+            ("{2:^w$ :%a}",     "{0:^1$}",         0, 2, Piece::CustomFmt { arg_kind: ArgKind::Positional(2),         arg_kind_width: Some(ArgKind::Named(Id::new("w")?)), arg_kind_precision: None,                        spec: Spec::CompileTime("%a") }),
+            ("{:>w$.* :<%a>}",  "{0:>1$.2$}",      2, 3, Piece::CustomFmt { arg_kind: ArgKind::Positional(1),         arg_kind_width: Some(ArgKind::Named(Id::new("w")?)), arg_kind_precision: Some(ArgKind::Positional(0)), spec: Spec::Runtime("%a") }),
+            ("{a:.* :%a}",      "{0:.1$}",         1, 2, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("a")?),  arg_kind_width: None,                                arg_kind_precision: Some(ArgKind::Positional(0)), spec: Spec::CompileTime("%a") }),
+            ("{a, b :<%r>}",    "{0}",             0, 1, Piece::CustomFmtPair { arg_kinds: [ArgKind::Named(Id::new("a")?), ArgKind::Named(Id::new("b")?)], arg_kind_width: None, arg_kind_precision: None, spec: "%r" }),
+            ("{,:>4 :<%r>}",    "{0:>4}",          2, 1, Piece::CustomFmtPair { arg_kinds: [ArgKind::Positional(0), ArgKind::Positional(1)],                 arg_kind_width: None, arg_kind_precision: None, spec: "%r" }),
+            ("{1,0:.* :<%r>}",  "{0:.1$}",         1, 2, Piece::CustomFmtPair { arg_kinds: [ArgKind::Positional(1), ArgKind::Positional(0)],                 arg_kind_width: None, arg_kind_precision: Some(ArgKind::Positional(0)), spec: "%r" }),
+            ("{x:,>5 :%a}",     "{0:,>5}",         0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?),  arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::CompileTime("%a") }),
+            ("{x :>10:%Y}",     "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?),  arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::CompileTime(">10:%Y") }),
+            ("{ :12:%a}",       "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::CompileTime("12:%a") }),
+            ("{ :12:%a:b}",     "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::CompileTime("12:%a:b") }),
+            ("{x :<5:foo>}",    "{0}",             0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?),  arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::Runtime("5:foo") }),
+            ("{ :*^8:<%a>}",    "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::CompileTime("*^8:<%a>") }),
+            ("{x:>4 :>10:%a}",  "{0:>4}",          0, 1, Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?),  arg_kind_width: None,                                arg_kind_precision: None,                        spec: Spec::CompileTime(">10:%a") }),
+            ("{x:>10! :%Y}",    "{0:>10}",         0, 1, Piece::Padded(Box::new(Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%Y") }))),
+            ("{:*^w$! :<%a>}",  "{0:*^1$}",        1, 2, Piece::Padded(Box::new(Piece::CustomFmt { arg_kind: ArgKind::Positional(0), arg_kind_width: Some(ArgKind::Named(Id::new("w")?)), arg_kind_precision: None, spec: Spec::Runtime("%a") }))),
+            ("{x:!<5! :%a}",    "{0:!<5}",         0, 1, Piece::Padded(Box::new(Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("x")?), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%a") }))),
+            ("{a,b:<8! :<%r>}", "{0:<8}",          0, 1, Piece::Padded(Box::new(Piece::CustomFmtPair { arg_kinds: [ArgKind::Named(Id::new("a")?), ArgKind::Named(Id::new("b")?)], arg_kind_width: None, arg_kind_precision: None, spec: "%r" }))),
+            ("{ :x:%a}",        "{0}",             1, 1, Piece::CustomFmt { arg_kind: ArgKind::Positional(0),         arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("x:%a") }),
             ("{}",              "{0}",             1, 1, Piece::StdFmt { arg_kind_position: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: None }),
             ("{:?}",            "{0:?}",           1, 1, Piece::StdFmt { arg_kind_position: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: None }),
             ("{3:? }",          "{0:?}",           0, 1, Piece::StdFmt { arg_kind_position: ArgKind::Positional(3),         arg_kind_width: None,                                arg_kind_precision: None }),
@@ -410,6 +803,10 @@ mod test {
             ("{:.2$? }",        "{0:.1$?}",        1, 2, Piece::StdFmt { arg_kind_position: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: Some(ArgKind::Positional(2)) }),
             ("{:.*? }",         "{0:.1$?}",        2, 2, Piece::StdFmt { arg_kind_position: ArgKind::Positional(1),         arg_kind_width: None,                                arg_kind_precision: Some(ArgKind::Positional(0)) }),
             ("{a:.*? }",        "{0:.1$?}",        1, 2, Piece::StdFmt { arg_kind_position: ArgKind::Named(Id::new("a")?),  arg_kind_width: None,                                arg_kind_precision: Some(ArgKind::Positional(0)) }),
+            ("{:<x}",           "{0:<x}",          1, 1, Piece::StdFmt { arg_kind_position: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: None }),
+            ("{:<>}",           "{0:<>}",          1, 1, Piece::StdFmt { arg_kind_position: ArgKind::Positional(0),         arg_kind_width: None,                                arg_kind_precision: None }),
+            ("{:<now>}",        "{0}",             0, 1, Piece::Ambient { name: "now" }),
+            ("{:<é_1> }",       "{0}",             0, 1, Piece::Ambient { name: "é_1" }),
         ];
 
         for &(fmt, result_new_format_string, result_current_positional_index, result_new_current_index, ref result_piece) in &data {
@@ -417,7 +814,7 @@ mod test {
             let mut current_positional_index = 0;
             let mut new_current_index = 0;
 
-            let piece = process_fmt(fmt, &mut current_positional_index, &mut new_format_string, &mut new_current_index)?;
+            let piece = process_fmt(fmt, None, &mut current_positional_index, &mut new_format_string, &mut new_current_index)?;
 
             assert_eq!(new_format_string, result_new_format_string);
             assert_eq!(current_positional_index, result_current_positional_index);
@@ -425,11 +822,94 @@ mod test {
             assert_eq!(piece, *result_piece);
         }
 
-        assert_eq!(process_fmt("{: ", &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid format string");
-        assert_eq!(process_fmt("{0éà0 :%a}", &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid format string");
-        assert_eq!(process_fmt("{0éà0}", &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid format string");
-        assert_eq!(process_fmt("{0:.}", &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid count in format string");
-        assert_eq!(process_fmt("{_:?}", &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid argument: argument name cannot be a single underscore");
+        assert_eq!(process_fmt("{: ", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid format string");
+        assert_eq!(
+            process_fmt("{0éà0 :%a}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "invalid argument reference before custom specifier: `0éà0`"
+        );
+        assert_eq!(
+            process_fmt("{a-b :%a}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "invalid argument reference before custom specifier: `a-b`"
+        );
+        assert_eq!(
+            process_fmt("{a b :%a}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "invalid argument reference before custom specifier: `a b`"
+        );
+        assert_eq!(process_fmt("{@ :<%a>}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid argument reference before custom specifier: `@`");
+        assert_eq!(process_fmt("{x:? :%a}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid standard flags before custom specifier: `x:?`");
+        assert_eq!(
+            process_fmt("{x:>5x :%a}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "invalid standard flags before custom specifier: `x:>5x`"
+        );
+        assert_eq!(
+            process_fmt("{x:>10.2! :%a}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "only fill, alignment and width flags can pad the custom output: `x:>10.2!`"
+        );
+        assert_eq!(
+            process_fmt("{x:+! :%a}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "only fill, alignment and width flags can pad the custom output: `x:+!`"
+        );
+        assert_eq!(
+            process_fmt("{x:!! :%a}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "only fill, alignment and width flags can pad the custom output: `x:!!`"
+        );
+        assert_eq!(process_fmt("{0éà0}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid format string");
+        assert_eq!(process_fmt("{0:.}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(), "invalid count in format string");
+        assert_eq!(
+            process_fmt("{_:?}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "invalid argument: argument name cannot be a single underscore"
+        );
+
+        assert_eq!(
+            process_fmt("{a, b :%r}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "custom specifiers referencing two arguments must be runtime specifiers"
+        );
+        assert_eq!(
+            process_fmt("{a, b :<%r>|?}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "custom specifiers referencing two arguments must be runtime specifiers"
+        );
+        assert_eq!(
+            process_fmt("{a, b :<%r?>}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "custom specifiers referencing two arguments must be runtime specifiers"
+        );
+        assert_eq!(
+            process_fmt("{a b, c :<%r>}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "invalid argument reference before custom specifier: `a b`"
+        );
+        assert_eq!(
+            process_fmt("{a, b, c :<%r>}", None, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "invalid argument reference before custom specifier: `b, c`"
+        );
+
+        let only_compile_time = Some(SpecKind::CompileTime);
+        let only_runtime = Some(SpecKind::Runtime);
+        assert!(process_fmt("{ :%a}", only_compile_time, &mut 0, &mut String::new(), &mut 0).is_ok());
+        assert!(process_fmt("{ :<%a>}", only_runtime, &mut 0, &mut String::new(), &mut 0).is_ok());
+        assert!(process_fmt("{:?}", only_runtime, &mut 0, &mut String::new(), &mut 0).is_ok());
+        assert_eq!(
+            process_fmt("{ :<%a>}", only_compile_time, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "runtime specifier used but only compile-time specifiers are allowed"
+        );
+        assert_eq!(
+            process_fmt("{ :<%a?>}", only_compile_time, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "runtime specifier used but only compile-time specifiers are allowed"
+        );
+        assert_eq!(
+            process_fmt("{ :%a}", only_runtime, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "compile-time specifier used but only runtime specifiers are allowed"
+        );
+        assert_eq!(
+            process_fmt("{:<now>}", only_compile_time, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "ambient specifier used but only compile-time specifiers are allowed"
+        );
+        assert_eq!(
+            process_fmt("{ :%a|<>}", only_compile_time, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "runtime specifier used but only compile-time specifiers are allowed"
+        );
+        assert_eq!(
+            process_fmt("{ :%a|<>}", only_runtime, &mut 0, &mut String::new(), &mut 0).unwrap_err(),
+            "compile-time specifier used but only runtime specifiers are allowed"
+        );
 
         Ok(())
     }
@@ -443,17 +923,89 @@ mod test {
         let result_pieces = [
             Piece::StdFmt { arg_kind_position: ArgKind::Positional(0), arg_kind_width: None, arg_kind_precision: None },
             Piece::StdFmt { arg_kind_position: ArgKind::Positional(2), arg_kind_width: None, arg_kind_precision: Some(ArgKind::Positional(1)) },
-            Piece::CustomFmt { arg_kind: ArgKind::Named(Id("h")), spec: Spec::Runtime("z") },
-            Piece::CustomFmt { arg_kind: ArgKind::Named(Id("e")), spec: Spec::CompileTime("3xxxGxxxxxxx") },
+            Piece::CustomFmt { arg_kind: ArgKind::Named(Id("h")), arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("z") },
+            Piece::CustomFmt { arg_kind: ArgKind::Named(Id("e")), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("3xxxGxxxxxxx") },
             Piece::StdFmt { arg_kind_position: ArgKind::Positional(3), arg_kind_width: None, arg_kind_precision: None },
-            Piece::CustomFmt { arg_kind: ArgKind::Positional(4), spec: Spec::CompileTime("") },
+            Piece::CustomFmt { arg_kind: ArgKind::Positional(4), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("") },
             Piece::StdFmt { arg_kind_position: ArgKind::Positional(5), arg_kind_width: None, arg_kind_precision: None },
             Piece::StdFmt { arg_kind_position: ArgKind::Positional(6), arg_kind_width: None, arg_kind_precision: None },
             Piece::StdFmt { arg_kind_position: ArgKind::Positional(7), arg_kind_width: None, arg_kind_precision: None },
-            Piece::CustomFmt { arg_kind: ArgKind::Positional(8), spec: Spec::Runtime("") },
+            Piece::CustomFmt { arg_kind: ArgKind::Positional(8), arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("") },
+        ];
+
+        let (new_format_string, pieces) = parse_format_string(format_string, None)?;
+
+        assert_eq!(new_format_string, result_new_format_string);
+        assert_eq!(pieces, result_pieces);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_format_string_literal_only() -> Result<(), Error> {
+        // Literal-only format strings are kept exactly, without any index rewriting
+        for &format_string in &["", " ", "   ", " \t\n ", "{{}}", "{{ }} {{{{}}}}", "}}{{", "a :b", "{{x :%a}}"] {
+            let (new_format_string, pieces) = parse_format_string(format_string, None)?;
+            assert_eq!(new_format_string, format_string);
+            assert_eq!(pieces, []);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_format_string_crlf() -> Result<(), Error> {
+        let format_string = "line 1\r\n{a :%a\r\n}\r\n{b :<%b>\r\n\t}\u{2028}{c:>4\r\n} {:<now>\r\n}\r\n\r\n";
+
+        let result_new_format_string = "line 1\r\n{0}\r\n{1}\u{2028}{2:>4} {3}\r\n\r\n";
+
+        let result_pieces = [
+            Piece::CustomFmt { arg_kind: ArgKind::Named(Id("a")), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%a") },
+            Piece::CustomFmt { arg_kind: ArgKind::Named(Id("b")), arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("%b") },
+            Piece::StdFmt { arg_kind_position: ArgKind::Named(Id("c")), arg_kind_width: None, arg_kind_precision: None },
+            Piece::Ambient { name: "now" },
         ];
 
-        let (new_format_string, pieces) = parse_format_string(format_string)?;
+        let (new_format_string, pieces) = parse_format_string(format_string, None)?;
+
+        assert_eq!(new_format_string, result_new_format_string);
+        assert_eq!(pieces, result_pieces);
+
+        // Whitespace inside a runtime specifier is passed unchanged
+        let (_, pieces) = parse_format_string("{a :<%a\r\n>}", None)?;
+        assert_eq!(
+            pieces,
+            [Piece::CustomFmt { arg_kind: ArgKind::Named(Id("a")), arg_kind_width: None, arg_kind_precision: None, spec: Spec::Runtime("%a\r\n") }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_format_string_named_asterisk() -> Result<(), Error> {
+        let format_string = "{a:.*} {} {b:.* :<%f>} {:.* :%a} {}";
+
+        let result_new_format_string = "{0:.1$} {2} {3:.4$} {5:.6$} {7}";
+
+        let result_pieces = [
+            Piece::StdFmt { arg_kind_position: ArgKind::Named(Id("a")), arg_kind_width: None, arg_kind_precision: Some(ArgKind::Positional(0)) },
+            Piece::StdFmt { arg_kind_position: ArgKind::Positional(1), arg_kind_width: None, arg_kind_precision: None },
+            Piece::CustomFmt {
+                arg_kind: ArgKind::Named(Id("b")),
+                arg_kind_width: None,
+                arg_kind_precision: Some(ArgKind::Positional(2)),
+                spec: Spec::Runtime("%f"),
+            },
+            Piece::CustomFmt {
+                arg_kind: ArgKind::Positional(4),
+                arg_kind_width: None,
+                arg_kind_precision: Some(ArgKind::Positional(3)),
+                spec: Spec::CompileTime("%a"),
+            },
+            Piece::StdFmt { arg_kind_position: ArgKind::Positional(5), arg_kind_width: None, arg_kind_precision: None },
+        ];
+
+        let (new_format_string, pieces) = parse_format_string(format_string, None)?;
 
         assert_eq!(new_format_string, result_new_format_string);
         assert_eq!(pieces, result_pieces);
@@ -470,7 +1022,7 @@ mod test {
 
         let pieces = vec![
             Piece::StdFmt { arg_kind_position: ArgKind::Named(Id::new("h")?), arg_kind_width: None, arg_kind_precision: None },
-            Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("h")?), spec: Spec::CompileTime("%z") },
+            Piece::CustomFmt { arg_kind: ArgKind::Named(Id::new("h")?), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("%z") },
             Piece::StdFmt { arg_kind_position: ArgKind::Positional(1), arg_kind_width: None, arg_kind_precision: None },
             Piece::StdFmt { arg_kind_position: ArgKind::Named(Id::new("a")?), arg_kind_width: None, arg_kind_precision: None },
             Piece::StdFmt { arg_kind_position: ArgKind::Positional(3), arg_kind_width: None, arg_kind_precision: None },
@@ -490,6 +1042,62 @@ mod test {
         assert_eq!(processed_pieces.arg_indices, result_arg_indices);
         assert_eq!(processed_pieces.new_args, result_new_args);
 
+        let pieces = vec![Piece::CustomFmt {
+            arg_kind: ArgKind::Named(Id::new("v")?),
+            arg_kind_width: Some(ArgKind::Named(Id::new("w")?)),
+            arg_kind_precision: Some(ArgKind::Named(Id::new("prec")?)),
+            spec: Spec::CompileTime("%f"),
+        }];
+
+        let processed_pieces = process_pieces(pieces, &[create_argument(Some("v")), create_argument(Some("w"))])?;
+        assert_eq!(processed_pieces.arg_indices, [(0, Some(Spec::CompileTime("%f"))), (1, None), (2, None)]);
+        assert_eq!(processed_pieces.new_args, ["prec"]);
+
+        let pieces = vec![
+            Piece::CustomFmtPair {
+                arg_kinds: [ArgKind::Named(Id::new("b")?), ArgKind::Positional(0)],
+                arg_kind_width: Some(ArgKind::Named(Id::new("w")?)),
+                arg_kind_precision: None,
+                spec: "%r",
+            },
+            Piece::CustomFmtPair {
+                arg_kinds: [ArgKind::Positional(0), ArgKind::Named(Id::new("a")?)],
+                arg_kind_width: None,
+                arg_kind_precision: None,
+                spec: "%q",
+            },
+        ];
+
+        let processed_pieces = process_pieces(pieces, &[create_argument(None), create_argument(Some("a"))])?;
+        assert_eq!(processed_pieces.arg_indices, [(2, Some(Spec::RuntimePair("%r", 0))), (3, None), (0, Some(Spec::RuntimePair("%q", 1)))]);
+        assert_eq!(processed_pieces.new_args, ["b", "w"]);
+
+        let pieces = vec![
+            Piece::Ambient { name: "now" },
+            Piece::StdFmt { arg_kind_position: ArgKind::Named(Id::new("h")?), arg_kind_width: None, arg_kind_precision: None },
+            Piece::StdFmt { arg_kind_position: ArgKind::Positional(0), arg_kind_width: None, arg_kind_precision: None },
+            Piece::Ambient { name: "now" },
+        ];
+
+        let processed_pieces = process_pieces(pieces, &[create_argument(None)])?;
+        assert_eq!(processed_pieces.arg_indices, [(2, None), (1, None), (0, None), (3, None)]);
+        assert_eq!(processed_pieces.new_args, ["h"]);
+        assert_eq!(processed_pieces.ambient_args, ["now", "now"]);
+
+        let pieces = vec![
+            Piece::StdFmt { arg_kind_position: ArgKind::Positional(0), arg_kind_width: None, arg_kind_precision: None },
+            Piece::Padded(Box::new(Piece::CustomFmt {
+                arg_kind: ArgKind::Positional(0),
+                arg_kind_width: Some(ArgKind::Named(Id::new("w")?)),
+                arg_kind_precision: None,
+                spec: Spec::Runtime("%a"),
+            })),
+        ];
+
+        let processed_pieces = process_pieces(pieces, &[create_argument(None)])?;
+        assert_eq!(processed_pieces.arg_indices, [(0, None), (0, Some(Spec::Runtime("%a"))), (1, None)]);
+        assert_eq!(processed_pieces.padded_positions, [1]);
+
         assert_eq!(process_pieces(vec![], &[create_argument(Some("a")), create_argument(Some("a"))]).unwrap_err(), "duplicate argument named `a`");
         assert_eq!(process_pieces(vec![], &[create_argument(None)]).unwrap_err(), "positional argument 0 not used");
         assert_eq!(process_pieces(vec![], &[create_argument(Some("a"))]).unwrap_err(), "named argument `a` not used");
@@ -500,10 +1108,29 @@ mod test {
         );
 
         assert_eq!(
-            process_pieces(vec![Piece::CustomFmt { arg_kind: ArgKind::Positional(0), spec: Spec::CompileTime("") }], &[]).unwrap_err(),
+            process_pieces(
+                vec![Piece::CustomFmt { arg_kind: ArgKind::Positional(0), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("") }],
+                &[]
+            )
+            .unwrap_err(),
             "invalid positional argument index: 0"
         );
 
+        let colliding_pieces = vec![
+            Piece::CustomFmt { arg_kind: ArgKind::Positional(0), arg_kind_width: None, arg_kind_precision: None, spec: Spec::CompileTime("a") },
+            Piece::CustomFmt {
+                arg_kind: ArgKind::Positional(0),
+                arg_kind_width: None,
+                arg_kind_precision: None,
+                spec: Spec::CompileTimePrefix("a\0(1)", "a\0", "1"),
+            },
+        ];
+
+        assert_eq!(
+            process_pieces(colliding_pieces, &[create_argument(None)]).unwrap_err(),
+            "format specifiers `a` and `a\0` have the same compile-time value, rename one of them"
+        );
+
         Ok(())
     }
 }
@@ -57,6 +57,25 @@ impl<'a> StrCursor<'a> {
     }
 }
 
+/// Convert a compile-time format specifier to its `u128` value, like the `custom_format::compile_time::spec` function.
+///
+/// Format specifiers of at most 16 bytes are stored directly, and longer format specifiers are hashed with the 128-bit FNV-1a algorithm.
+pub fn spec_value(spec: &str) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    let bytes = spec.as_bytes();
+
+    if bytes.len() > 16 {
+        let hash = bytes.iter().fold(OFFSET_BASIS, |hash, &byte| (hash ^ u128::from(byte)).wrapping_mul(PRIME));
+        return hash | (0xFF << 120);
+    }
+
+    let mut result = [0u8; 16];
+    result[..bytes.len()].copy_from_slice(bytes);
+    u128::from_le_bytes(result)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -91,4 +110,12 @@ mod test {
         assert_eq!(cursor.read_until_included(|c| c == 'ç'), "éèç");
         assert_eq!(cursor.read_until_included(|c| c == ' '), "0€");
     }
+
+    #[test]
+    fn test_spec_value() {
+        assert_eq!(spec_value(""), 0);
+        assert_eq!(spec_value("%z"), 0x7a25);
+        assert_eq!(spec_value("0123456789abcdef"), u128::from_le_bytes(*b"0123456789abcdef"));
+        assert_eq!(spec_value("%Y-%m-%dT%H:%M:%S%.3fZ"), 0xff99034ceca454be14a8dcfdfee7bbe3);
+    }
 }
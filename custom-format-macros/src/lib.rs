@@ -5,6 +5,12 @@
 
 mod fmt;
 
+#[cfg(feature = "compile-time")]
+mod derive;
+
+#[cfg(feature = "compile-time")]
+mod specs;
+
 use proc_macro::TokenStream;
 
 /// Parse custom format specifiers in format string and write output tokens.
@@ -15,3 +21,23 @@ use proc_macro::TokenStream;
 pub fn fmt(input: TokenStream) -> TokenStream {
     fmt::fmt(input.into()).into()
 }
+
+/// Generate compile-time custom format implementations from the methods of an impl block.
+///
+/// See the documentation of the `custom-format` crate.
+#[cfg(feature = "compile-time")]
+#[proc_macro_attribute]
+#[allow(clippy::useless_conversion)]
+pub fn specs(attr: TokenStream, item: TokenStream) -> TokenStream {
+    specs::specs(attr.into(), item.into()).into()
+}
+
+/// Derive compile-time custom format implementations from `#[custom_format(...)]` attributes.
+///
+/// See the documentation of the `custom-format` crate.
+#[cfg(feature = "compile-time")]
+#[proc_macro_derive(CustomFormat, attributes(custom_format))]
+#[allow(clippy::useless_conversion)]
+pub fn derive_custom_format(input: TokenStream) -> TokenStream {
+    derive::derive_custom_format(input.into()).into()
+}
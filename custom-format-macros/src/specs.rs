@@ -0,0 +1,344 @@
+//! Module containing the `specs` attribute macro code.
+
+use crate::fmt::compile_error;
+
+#[cfg(not(test))]
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+#[cfg(test)]
+use proc_macro2::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Prefix of the method names mapped to a compile-time format specifier by naming convention
+const SPEC_PREFIX: &str = "spec_";
+
+/// Error type for the attribute macro, with the span of the error
+type Error = (&'static str, Span);
+
+/// Method mapped to a compile-time format specifier
+#[derive(Debug)]
+struct SpecMethod {
+    /// Method name
+    name: String,
+    /// Compile-time format specifier
+    spec: String,
+}
+
+/// Parsed `impl` block header
+#[derive(Debug)]
+struct ImplHeader {
+    /// Generic parameters, including the `<>` chars
+    generics: Vec<TokenTree>,
+    /// Self type
+    self_ty: Vec<TokenTree>,
+    /// Where clause, including the `where` keyword
+    where_clause: Vec<TokenTree>,
+}
+
+/// Parse a string containing valid Rust tokens
+fn tokens(s: &str) -> TokenStream {
+    s.parse().unwrap()
+}
+
+/// Parse the attribute arguments, returning the path of the `custom-format` crate
+fn parse_attr(attr: TokenStream) -> Result<TokenStream, Error> {
+    let mut attr_iter = attr.into_iter();
+
+    match (attr_iter.next(), attr_iter.next()) {
+        (None, _) => Ok(tokens("::custom_format")),
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Punct(punct))) if &ident.to_string() == "crate" && punct.as_char() == '=' => {
+            let path: TokenStream = attr_iter.collect();
+            if path.is_empty() {
+                Err(("expected crate path after `crate =`", punct.span()))
+            } else {
+                Ok(path)
+            }
+        }
+        (Some(tt), _) => Err(("invalid attribute arguments, expected `crate = path`", tt.span())),
+    }
+}
+
+/// Parse the `impl` block header, between the `impl` keyword and the block body
+fn parse_header(header: &[TokenTree]) -> Result<ImplHeader, Error> {
+    let mut generics_len = 0;
+
+    if matches!(header.first(), Some(TokenTree::Punct(punct)) if punct.as_char() == '<') {
+        let mut depth = 0;
+        let mut previous_is_dash = false;
+
+        for (index, tt) in header.iter().enumerate() {
+            if let TokenTree::Punct(punct) = tt {
+                match punct.as_char() {
+                    '<' => depth += 1,
+                    // Skip the `>` char of the `->` token
+                    '>' if !previous_is_dash => depth -= 1,
+                    _ => (),
+                }
+                previous_is_dash = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+            } else {
+                previous_is_dash = false;
+            }
+
+            if depth == 0 {
+                generics_len = index + 1;
+                break;
+            }
+        }
+    }
+
+    let (generics, remaining) = header.split_at(generics_len);
+
+    let where_position = remaining.iter().position(|tt| matches!(tt, TokenTree::Ident(ident) if &ident.to_string() == "where"));
+    let (self_ty, where_clause) = remaining.split_at(where_position.unwrap_or(remaining.len()));
+
+    if let Some(tt) = self_ty.iter().find(|tt| matches!(tt, TokenTree::Ident(ident) if &ident.to_string() == "for")) {
+        return Err(("`specs` attribute requires an inherent impl block", tt.span()));
+    }
+
+    if self_ty.is_empty() {
+        return Err(("expected self type in impl block", Span::call_site()));
+    }
+
+    Ok(ImplHeader { generics: generics.to_vec(), self_ty: self_ty.to_vec(), where_clause: where_clause.to_vec() })
+}
+
+/// Parse the argument of a `#[spec("...")]` attribute, returning `None` for other attributes
+fn parse_spec_attr(group: &Group) -> Result<Option<String>, Error> {
+    let inner: Vec<_> = group.stream().into_iter().collect();
+
+    match inner.as_slice() {
+        [TokenTree::Ident(ident), TokenTree::Group(args)] if &ident.to_string() == "spec" && args.delimiter() == Delimiter::Parenthesis => {
+            let mut args_iter = args.stream().into_iter();
+            match (args_iter.next(), args_iter.next()) {
+                (Some(tt), None) => match litrs::StringLit::parse(tt.to_string()) {
+                    Ok(lit) => Ok(Some(lit.into_value().into_owned())),
+                    Err(_) => Err(("invalid `spec` attribute, expected a string literal", tt.span())),
+                },
+                _ => Err(("invalid `spec` attribute, expected a string literal", args.span())),
+            }
+        }
+        [TokenTree::Ident(ident), ..] if &ident.to_string() == "spec" => Err(("invalid `spec` attribute, expected `#[spec(\"...\")]`", ident.span())),
+        _ => Ok(None),
+    }
+}
+
+/// Process the `impl` block body, removing the `#[spec]` attributes and returning the list of methods mapped to a format specifier
+fn process_body(body: TokenStream) -> Result<(TokenStream, Vec<SpecMethod>), Error> {
+    let token_trees: Vec<_> = body.into_iter().collect();
+
+    let mut new_body = Vec::new();
+    let mut methods = Vec::new();
+
+    let mut item_start = 0;
+    let mut explicit_spec = None;
+    let mut is_fn = false;
+    let mut name = None;
+
+    let mut index = 0;
+    while index < token_trees.len() {
+        let tt = &token_trees[index];
+        index += 1;
+
+        match tt {
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                if let Some(TokenTree::Group(group)) = token_trees.get(index) {
+                    if let Some(spec) = parse_spec_attr(group)? {
+                        if explicit_spec.replace(spec).is_some() {
+                            return Err(("duplicate `spec` attribute", group.span()));
+                        }
+                        index += 1;
+                        continue;
+                    }
+                }
+            }
+            TokenTree::Ident(ident) if &ident.to_string() == "fn" => {
+                is_fn = true;
+                if let Some(TokenTree::Ident(ident)) = token_trees.get(index) {
+                    name = Some(ident.to_string());
+                }
+            }
+            _ => (),
+        }
+
+        new_body.push(tt.clone());
+
+        let is_item_end = match tt {
+            TokenTree::Punct(punct) => punct.as_char() == ';',
+            TokenTree::Group(group) => is_fn && group.delimiter() == Delimiter::Brace,
+            _ => false,
+        };
+
+        if is_item_end {
+            let method_name = name.take();
+
+            let spec = match (explicit_spec.take(), &method_name) {
+                (Some(spec), _) => Some(spec),
+                (None, Some(method_name)) => method_name.strip_prefix(SPEC_PREFIX).map(|suffix| format!("%{}", suffix)),
+                (None, None) => None,
+            };
+
+            if let Some(spec) = spec {
+                match method_name {
+                    Some(name) => {
+                        // Method names using the naming convention are not snake case
+                        new_body.splice(item_start..item_start, tokens("#[allow(non_snake_case)]"));
+                        methods.push(SpecMethod { name, spec });
+                    }
+                    None => return Err(("`spec` attribute can only be used on methods", tt.span())),
+                }
+            }
+
+            item_start = new_body.len();
+            is_fn = false;
+        }
+    }
+
+    if explicit_spec.is_some() {
+        return Err(("`spec` attribute can only be used on methods", Span::call_site()));
+    }
+
+    Ok((new_body.into_iter().collect(), methods))
+}
+
+/// Compute the `CustomFormat` impl block for a method
+fn compute_impl(crate_path: &TokenStream, header: &ImplHeader, method: &SpecMethod) -> TokenStream {
+    let mut output = Vec::<TokenTree>::new();
+
+    output.extend(tokens("impl"));
+    output.extend(header.generics.iter().cloned());
+    output.extend(crate_path.clone());
+    output.extend(tokens("::compile_time::CustomFormat<"));
+
+    output.push(TokenTree::from(Group::new(Delimiter::Brace, {
+        let mut spec = crate_path.clone().into_iter().collect::<Vec<_>>();
+        spec.extend(tokens("::compile_time::spec"));
+        spec.push(Group::new(Delimiter::Parenthesis, TokenTree::from(Literal::string(&method.spec)).into()).into());
+        spec.into_iter().collect()
+    })));
+
+    output.push(Punct::new('>', Spacing::Alone).into());
+    output.push(Ident::new("for", Span::call_site()).into());
+    output.extend(header.self_ty.iter().cloned());
+    output.extend(header.where_clause.iter().cloned());
+
+    output.push(TokenTree::from(Group::new(
+        Delimiter::Brace,
+        tokens(&format!("fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {{ Self::{}(self, f) }}", method.name)),
+    )));
+
+    output.into_iter().collect()
+}
+
+/// Process the attribute arguments and the annotated `impl` block
+fn process(attr: TokenStream, item: TokenStream) -> Result<TokenStream, Error> {
+    let crate_path = parse_attr(attr)?;
+
+    let mut token_trees: Vec<_> = item.into_iter().collect();
+
+    let body = match token_trees.pop() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group,
+        _ => return Err(("`specs` attribute can only be used on an impl block", Span::call_site())),
+    };
+
+    let impl_position = token_trees
+        .iter()
+        .position(|tt| matches!(tt, TokenTree::Ident(ident) if &ident.to_string() == "impl"))
+        .ok_or(("`specs` attribute can only be used on an impl block", Span::call_site()))?;
+
+    let header = parse_header(&token_trees[impl_position + 1..])?;
+    let (new_body, methods) = process_body(body.stream())?;
+
+    let mut new_body = Group::new(Delimiter::Brace, new_body);
+    new_body.set_span(body.span());
+
+    let mut output: TokenStream = token_trees.into_iter().collect();
+    output.extend(Some(TokenTree::from(new_body)));
+
+    for method in &methods {
+        output.extend(compute_impl(&crate_path, &header, method));
+    }
+
+    Ok(output)
+}
+
+/// Main function, working with both [`proc_macro::TokenStream`] and `proc_macro2::TokenStream`
+pub(crate) fn specs(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match process(attr, item) {
+        Ok(output) => output,
+        Err((msg, span)) => compile_error(msg, span),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_specs() -> Result<(), Box<dyn std::error::Error>> {
+        let item = r#"
+            impl<T: Into<u8> + Copy> Foo<T> where T: Clone {
+                const X: u8 = { 0 };
+
+                fn spec_Y(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", 1) }
+
+                #[inline]
+                #[spec("<%m>")]
+                pub fn month(&self, f: &mut fmt::Formatter) -> fmt::Result { Ok(()) }
+
+                fn other(&self) -> impl Fn() -> u8 { || 0 }
+            }
+        "#;
+
+        let result = r#"
+            impl<T: Into<u8> + Copy> Foo<T> where T: Clone {
+                const X: u8 = { 0 };
+
+                #[allow(non_snake_case)]
+                fn spec_Y(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", 1) }
+
+                #[allow(non_snake_case)]
+                #[inline]
+                pub fn month(&self, f: &mut fmt::Formatter) -> fmt::Result { Ok(()) }
+
+                fn other(&self) -> impl Fn() -> u8 { || 0 }
+            }
+
+            impl<T: Into<u8> + Copy> cfmt::compile_time::CustomFormat<{ cfmt::compile_time::spec("%Y") }> for Foo<T> where T: Clone {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result { Self::spec_Y(self, f) }
+            }
+
+            impl<T: Into<u8> + Copy> cfmt::compile_time::CustomFormat<{ cfmt::compile_time::spec("<%m>") }> for Foo<T> where T: Clone {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result { Self::month(self, f) }
+            }
+        "#;
+
+        let output = specs("crate = cfmt".parse()?, item.parse()?);
+        assert_eq!(output.to_string(), result.parse::<TokenStream>()?.to_string());
+
+        let output = specs(TokenStream::new(), "impl Foo { fn spec_a(&self, f: &mut Formatter) -> Result { Ok(()) } }".parse()?);
+        assert!(output
+            .to_string()
+            .contains(&r#"impl :: custom_format :: compile_time :: CustomFormat < { :: custom_format :: compile_time :: spec ("%a") } > for Foo"#.to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_specs_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let data = [
+            ("", "struct Foo;", "`specs` attribute can only be used on an impl block"),
+            ("", "impl fmt::Display for Foo {}", "`specs` attribute requires an inherent impl block"),
+            ("crate", "impl Foo {}", "invalid attribute arguments, expected `crate = path`"),
+            ("crate =", "impl Foo {}", "expected crate path after `crate =`"),
+            ("", "impl Foo { #[spec(1)] fn a(&self) {} }", "invalid `spec` attribute, expected a string literal"),
+            ("", "impl Foo { #[spec] fn a(&self) {} }", "invalid `spec` attribute, expected `#[spec(\"...\")]`"),
+            ("", r#"impl Foo { #[spec("a")] #[spec("b")] fn a(&self) {} }"#, "duplicate `spec` attribute"),
+            ("", r#"impl Foo { #[spec("a")] const A: u8 = 0; }"#, "`spec` attribute can only be used on methods"),
+        ];
+
+        for &(attr, item, msg) in &data {
+            let output = specs(attr.parse()?, item.parse()?);
+            assert_eq!(output.into_iter().last().unwrap().to_string(), format!("({:?})", msg));
+        }
+
+        Ok(())
+    }
+}
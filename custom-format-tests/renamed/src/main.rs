@@ -0,0 +1,8 @@
+//! Tests using the crate through a renamed dependency, so that `::custom_format` doesn't resolve.
+
+#[cfg(test)]
+mod tests;
+
+fn main() {
+    println!("Test crate.")
+}
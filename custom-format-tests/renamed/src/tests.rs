@@ -0,0 +1,65 @@
+use core::fmt::{self, Write};
+
+struct Hex(u8);
+
+impl cf::compile_time::CustomFormat<{ cf::compile_time::spec("x") }> for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#04x}", self.0)
+    }
+}
+
+impl cf::runtime::CustomFormat for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        match spec {
+            "X" => write!(f, "{:#04X}", self.0),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+#[test]
+fn test_renamed_crate() {
+    let h = Hex(0xab);
+
+    assert_eq!(cf::format!("{h :x}, {h :<X>}"), "0xab, 0xAB");
+    assert_eq!(format!("{}", cf::format_args!("{0 :x}", h)), "0xab");
+    assert_eq!(cf::custom_formatter!("x", &h).to_string(), "0xab");
+
+    let mut s = String::new();
+    cf::write!(s, "{h :x}").unwrap();
+    cf::writeln!(s, "{h :<X>}").unwrap();
+    assert_eq!(s, "0xab0xAB\n");
+
+    cf::println!("{h :x}, {h :<X>}");
+    cf::eprintln!("{h :x}, {h :<X>}");
+}
+
+#[test]
+fn test_renamed_crate_ambient() {
+    cf::runtime::set_provider("hex", |f| f.write_str("0x2a"));
+    assert_eq!(cf::format!("{:<hex>}"), "0x2a");
+    assert!(cf::runtime::remove_provider("hex"));
+}
+
+#[test]
+fn test_renamed_crate_specs_attribute() {
+    struct Byte(u8);
+
+    #[cf::specs(crate = cf)]
+    impl Byte {
+        fn spec_b(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:08b}", self.0)
+        }
+    }
+
+    assert_eq!(cf::format!("{0 :%b}", Byte(5)), "00000101");
+}
+
+#[test]
+fn test_renamed_crate_derive_custom_format() {
+    #[derive(cf::compile_time::CustomFormat)]
+    #[custom_format(crate = cf)]
+    struct Byte(#[custom_format(spec = "%b")] u8);
+
+    assert_eq!(cf::format!("{0 :%b}", Byte(5)), "5");
+}
@@ -0,0 +1,579 @@
+use custom_format as cfmt;
+
+use cfmt::builtins::*;
+
+#[test]
+fn test_digits() {
+    let number = Digits("+1 (555) 123-4567");
+    assert_eq!(cfmt::format!("{0 :<###-###-####>}", Digits("5551234567")), "555-123-4567");
+    assert_eq!(cfmt::format!("{0 :<(###) ###-####>}", Digits("555.123.4567")), "(555) 123-4567");
+    assert_eq!(cfmt::format!("{0 :<###-###-####>}, {0 :<###-###-####,trunc>}", number), "155-512-34567, 155-512-3456");
+    assert_eq!(cfmt::format!("{0 :<#### #### #### ####>}", Digits("4111 1111")), "4111 1111");
+    assert_eq!(cfmt::format!("{0 :<#### #### #### ####,pad=*>}", Digits("4111 1111")), "4111 1111 **** ****");
+    assert_eq!(cfmt::format!("{0 :<[##]>}, {0 :<[####]>}", Digits("12")), "[12], [12");
+    assert_eq!(cfmt::format!("{0 :<##;##,pad=0,trunc>}", Digits("1234567")), "12;34");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_digits_invalid_flag() {
+    cfmt::format!("{0 :<###,pad=>}", Digits("1"));
+}
+
+#[test]
+fn test_wrap() {
+    use core::num::Wrapping;
+
+    let value = Wrap(Wrapping(u8::MAX) + Wrapping(0x10));
+    assert_eq!(cfmt::format!("{0 :<x>}, {0 :<#x>}, {0 :<X>}, {0 :<#X>}", Wrap(Wrapping(0xABu8))), "ab, 0xab, AB, 0xAB");
+    assert_eq!(cfmt::format!("{0 :<>}, {0 :<?>}, {0 :<#x>}, {0 :<#b>}", value), "15, 15, 0xf, 0b1111");
+    assert_eq!(cfmt::format!("{0 :<o>}, {0 :<e>}", Wrap(Wrapping(-8i32))), "37777777770, -8e0");
+}
+
+#[test]
+fn test_rate() {
+    use core::time::Duration;
+
+    let rate = Rate(12.3);
+    assert_eq!(cfmt::format!("{0 :<>}, {0 :<per=s>}, {0 :<hz>}", rate), "12.3/s, 12.3/s, 12.3 Hz");
+    assert_eq!(cfmt::format!("{0 :<per=min>}, {0 :<per=h>}", Rate::new(3.0, Duration::from_millis(1500))), "120/min, 7200/h");
+    assert_eq!(cfmt::format!("{0 :<>}, {0 :<per=min>}", Rate(0.0)), "0/s, 0/min");
+    assert_eq!(format!("{:.2}", cfmt::runtime::CustomFormatter::new("hz", &Rate(1.0 / 3.0))), "0.33 Hz");
+}
+
+#[test]
+fn test_bases() {
+    assert_eq!(cfmt::format!("{0 :<all>}", Bases(u8::MAX)), "dec=255 hex=0xff oct=0o377 bin=0b11111111");
+    assert_eq!(cfmt::format!("{0 :<hex,bin>}", Bases(u8::MAX)), "hex=0xff bin=0b11111111");
+    assert_eq!(cfmt::format!("{0 :<oct>}, {0 :<dec,dec>}", Bases(8u64)), "oct=0o10, dec=8 dec=8");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_bases_invalid_spec() {
+    cfmt::format!("{0 :<hex,>}", Bases(1));
+}
+
+#[test]
+fn test_bar() {
+    assert_eq!(cfmt::format!("{0 :<width=10>}", Bar(0.0)), "[----------]");
+    assert_eq!(cfmt::format!("{0 :<width=10,pct>}", Bar(0.5)), "[#####-----] 50%");
+    assert_eq!(cfmt::format!("{0 :<width=10,pct>}", Bar(1.0)), "[##########] 100%");
+    assert_eq!(cfmt::format!("{0 :<width=4,pct>}, {1 :<width=4,pct>}", Bar(1.7), Bar(-0.2)), "[####] 100%, [----] 0%");
+    assert_eq!(cfmt::format!("{0 :<>}", Bar(0.333)), "[#######-------------]");
+    assert_eq!(cfmt::format!("{0 :<fill==,empty= ,width=6>}", Bar(0.5)), "[===   ]");
+}
+
+#[test]
+fn test_diff() {
+    assert_eq!(cfmt::format!("{0 :<>}", Diff(&42, &42)), "42");
+    assert_eq!(cfmt::format!("{0 :<>}", Diff(&42, &43)), "42 -> 43");
+    assert_eq!(cfmt::format!("{0 :<unified>}", Diff(&"same", &"same")), "  same");
+    assert_eq!(cfmt::format!("{0 :<unified>}", Diff(&1.5, &-2.0)), "- 1.5\n+ -2");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_diff_invalid_spec() {
+    cfmt::format!("{0 :<side>}", Diff(&1, &2));
+}
+
+#[test]
+fn test_date_parts() {
+    let dt = DateParts { year: 1836, month: 5, day: 18, hour: 23, minute: 45, second: 54, nanoseconds: 123456789 };
+
+    assert_eq!(
+        cfmt::format!("The {ty:?} is: {dt :<%Y>}-{dt :<%m>}-{dt :<%d>} {dt :<%H>}:{dt :<%M>}:{dt :<%S>}.{dt :<%6N>}", ty = "DateParts"),
+        "The \"DateParts\" is: 1836-05-18 23:45:54.123456"
+    );
+
+    assert_eq!(cfmt::format!("{dt :<%F %T>}"), "1836-05-18 23:45:54");
+    assert_eq!(cfmt::format!("{dt :<%D, %y>}"), "05/18/36, 36");
+    assert_eq!(cfmt::format!("{dt :<%1N|%9N|%12N>}"), "1|123456789|123456789000");
+    assert_eq!(cfmt::format!("{dt :<100%% à %Hh>}"), "100% à 23h");
+    assert_eq!(cfmt::format!("{0 :<%Y>}", DateParts { year: -5, ..dt }), "-005");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_date_parts_invalid_spec() {
+    cfmt::format!("{0 :<%Y-%q>}", DateParts::default());
+}
+
+#[test]
+fn test_backoff() {
+    use core::time::Duration;
+
+    let backoff = Backoff(Duration::from_millis(100), 4);
+    assert_eq!(cfmt::format!("{0 :<>}", backoff), "100ms, 200ms, 400ms, 800ms");
+    assert_eq!(cfmt::format!("{0 :<exp>}", backoff), "100ms, 200ms, 400ms, 800ms");
+    assert_eq!(cfmt::format!("{0 :<lin>}", backoff), "100ms, 200ms, 300ms, 400ms");
+    assert_eq!(cfmt::format!("{0 :<lin,cap=250ms>}", backoff), "100ms, 200ms, 250ms, 250ms");
+    assert_eq!(cfmt::format!("{0 :<cap=1s,exp>}", Backoff(Duration::from_millis(300), 3)), "300ms, 600ms, 1s");
+
+    assert_eq!(cfmt::format!("{0 :<>}", Backoff(Duration::from_secs(1), 0)), "");
+    assert_eq!(cfmt::format!("{0 :<cap=5s>}", Backoff(Duration::from_secs(1), 40)).rsplit(", ").next(), Some("5s"));
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_backoff_invalid_spec() {
+    cfmt::format!("{0 :<cap=2min>}", Backoff(core::time::Duration::from_secs(1), 2));
+}
+
+#[test]
+fn test_bit_flags() {
+    const TABLE: &[(&str, u64)] = &[("READ", 0b001), ("WRITE", 0b010), ("EXEC", 0b100), ("RW", 0b011), ("NONE", 0)];
+
+    assert_eq!(cfmt::format!("{0 :<>}", BitFlags(0b001, TABLE)), "READ");
+    assert_eq!(cfmt::format!("{0 :<>}", BitFlags(0b111, TABLE)), "READ | WRITE | EXEC | RW");
+    assert_eq!(cfmt::format!("{0 :<>}", BitFlags(0b1_0101, TABLE)), "READ | EXEC | 0x10");
+    assert_eq!(cfmt::format!("{0 :<>}", BitFlags(0x8000_0000_0000_0000, TABLE)), "0x8000000000000000");
+    assert_eq!(cfmt::format!("{0 :<>}", BitFlags(0, TABLE)), "0x0");
+    assert_eq!(cfmt::format!("{0 :<>}", BitFlags(0b110, &[])), "0x6");
+    assert_eq!(cfmt::format!("{0 :<sep=|>}", BitFlags(0b1010, TABLE)), "WRITE|0x8");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_bit_flags_invalid_spec() {
+    cfmt::format!("{0 :<hex>}", BitFlags(1, &[]));
+}
+
+#[test]
+fn test_boxed() {
+    assert_eq!(cfmt::format!("{0 :<>}", Boxed(&"hello")), "┌───────┐\n│ hello │\n└───────┘");
+    assert_eq!(cfmt::format!("{0 :<title=Summary>}", Boxed(&"ok")), "┌─ Summary ─┐\n│ ok        │\n└───────────┘");
+    assert_eq!(cfmt::format!("{0:8 :<ascii>}", Boxed(&1.5)), "+----------+\n| 1.5      |\n+----------+");
+
+    let content = format_args!("first line\n{}\n\nlast", 2);
+    assert_eq!(
+        cfmt::format!("{0 :<title=Multi,ascii>}", Boxed(&content)),
+        "+- Multi ----+\n| first line |\n| 2          |\n|            |\n| last       |\n+------------+"
+    );
+    assert_eq!(cfmt::format!("{0 :<>}", Boxed(&"é\nà")), "┌───┐\n│ é │\n│ à │\n└───┘");
+    assert_eq!(cfmt::format!("{0 :<>}", Boxed(&"")), "┌──┐\n│  │\n└──┘");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_boxed_invalid_spec() {
+    cfmt::format!("{0 :<Summary>}", Boxed(&1));
+}
+
+#[test]
+fn test_cell() {
+    let none = Cell::<&str>(&None);
+    assert_eq!(cfmt::format!("[{none :<>}], [{none :<none=NA>}], [{none :<q,none=\\N>}]"), "[], [NA], [\\N]");
+
+    let plain = Cell(&Some(1.5));
+    assert_eq!(cfmt::format!("{plain :<>}, {plain :<q>}"), "1.5, 1.5");
+
+    assert_eq!(cfmt::format!("{0 :<q>}", Cell(&Some("a,b"))), "\"a,b\"");
+    assert_eq!(cfmt::format!("{0 :<q>}", Cell(&Some("say \"hi\""))), "\"say \"\"hi\"\"\"");
+    assert_eq!(cfmt::format!("{0 :<q>}", Cell(&Some("a\nb"))), "\"a\nb\"");
+    assert_eq!(cfmt::format!("{0 :<>}", Cell(&Some("a,b"))), "a,b");
+    assert_eq!(cfmt::format!("{0 :<q,delim=\t>}|{1 :<q,delim=\t>}", Cell(&Some("a,b")), Cell(&Some("a\tb"))), "a,b|\"a\tb\"");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_cell_invalid_spec() {
+    cfmt::format!("{0 :<delim=ab>}", Cell(&Some(1)));
+}
+
+#[test]
+fn test_sci_aligned() {
+    let values = [12345.0, -0.000123, 0.0, -0.0, 1.0, 9.999, -6.02214076e23, 1.0e-300, f64::MAX, f64::NAN, f64::INFINITY, f64::NEG_INFINITY];
+
+    let formatted: Vec<_> = values.iter().map(|&x| cfmt::format!("{0 :<2>}", SciAligned(x))).collect();
+
+    assert_eq!(
+        formatted,
+        [
+            "+1.23e+04",
+            "-1.23e-04",
+            "+0.00e+00",
+            "-0.00e+00",
+            "+1.00e+00",
+            "+1.00e+01",
+            "-6.02e+23",
+            "+1.00e-300",
+            "+1.80e+308",
+            "      NaN",
+            "     +inf",
+            "     -inf",
+        ]
+    );
+
+    // Only exponents with three digits change the width
+    assert!(formatted.iter().filter(|s| !s.contains("e+3") && !s.contains("e-3")).all(|s| s.len() == 9));
+
+    assert_eq!(cfmt::format!("{0 :<0>}|{0 :<5>}", SciAligned(-42.0)), "-4e+01|-4.20000e+01");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_sci_aligned_invalid_spec() {
+    cfmt::format!("{0 :<x>}", SciAligned(1.0));
+}
+
+#[test]
+fn test_checksum() {
+    let check = Checksum(b"123456789");
+    assert_eq!(cfmt::format!("{check :<crc32>}, {check :<adler32>}, {check :<fnv>}"), "cbf43926, 091e01de, bb86b11c");
+
+    let fox = Checksum(b"The quick brown fox jumps over the lazy dog");
+    assert_eq!(cfmt::format!("{fox :<crc32>}, {fox :<adler32>}, {fox :<fnv>}"), "414fa339, 5bdc0fda, 048fff90");
+
+    let empty = Checksum(b"");
+    assert_eq!(cfmt::format!("{empty :<crc32>}, {empty :<adler32>}, {empty :<fnv>}"), "00000000, 00000001, 811c9dc5");
+
+    let large = Checksum(&[0xFF; 100_000]);
+    assert_eq!(cfmt::format!("{large :<crc32>}, {large :<adler32>}"), "68c6cec4, 149a302c");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_checksum_invalid_spec() {
+    cfmt::format!("{0 :<md5>}", Checksum(b"abc"));
+}
+
+#[test]
+fn test_epoch() {
+    let t = Epoch(0);
+    assert_eq!(cfmt::format!("{t :<iso>}, {t :<date>}, {t :<time>}"), "1970-01-01T00:00:00Z, 1970-01-01, 00:00:00");
+
+    assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(1_700_000_000)), "2023-11-14T22:13:20Z");
+    assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(951_782_400)), "2000-02-29T00:00:00Z");
+    assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(4_107_542_400)), "2100-03-01T00:00:00Z");
+    assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(253_402_300_799)), "9999-12-31T23:59:59Z");
+
+    assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(-1)), "1969-12-31T23:59:59Z");
+    assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(-31_536_000)), "1969-01-01T00:00:00Z");
+    assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(-2_208_988_800)), "1900-01-01T00:00:00Z");
+    assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(-62_135_596_800)), "0001-01-01T00:00:00Z");
+
+    assert_eq!(cfmt::format!("{0 :<date>}", Epoch(i64::MAX)), "292277026596-12-04");
+    assert_eq!(cfmt::format!("{0 :<date>}", Epoch(i64::MIN)), "-292277022657-01-27");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_epoch_invalid_spec() {
+    cfmt::format!("{0 :<rfc2822>}", Epoch(0));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_tree() {
+    use core::fmt;
+
+    struct Node(u32, Vec<Node>);
+
+    impl TreeNode for Node {
+        fn label(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "node {}", self.0)
+        }
+
+        fn children(&self) -> &[Self] {
+            &self.1
+        }
+    }
+
+    let tree = Node(0, vec![Node(1, vec![Node(2, vec![]), Node(3, vec![Node(4, vec![])])]), Node(5, vec![Node(6, vec![])])]);
+
+    let uni = concat!("node 0\n", "├── node 1\n", "│   ├── node 2\n", "│   └── node 3\n", "│       └── node 4\n", "└── node 5\n", "    └── node 6",);
+
+    let ascii = concat!("node 0\n", "|-- node 1\n", "|   |-- node 2\n", "|   `-- node 3\n", "|       `-- node 4\n", "`-- node 5\n", "    `-- node 6",);
+
+    assert_eq!(cfmt::format!("{0 :<uni>}", Tree(&tree)), uni);
+    assert_eq!(cfmt::format!("{0 :<ascii>}", Tree(&tree)), ascii);
+    assert_eq!(cfmt::format!("{0 :<uni>}", Tree(&Node(7, vec![]))), "node 7");
+
+    let mut deep = Node(0, vec![]);
+    for index in 1..3000 {
+        deep = Node(index, vec![deep]);
+    }
+
+    let output = cfmt::format!("{0 :<ascii>}", Tree(&deep));
+    assert_eq!(output.lines().count(), 3000);
+    assert_eq!(output.lines().last(), Some(&*format!("{}`-- node 0", " ".repeat(4 * 2998))));
+}
+
+#[test]
+fn test_transition() {
+    use core::time::Duration;
+
+    #[derive(Debug)]
+    enum State {
+        Idle,
+        Running,
+    }
+
+    impl core::fmt::Display for State {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            core::fmt::Debug::fmt(self, f)
+        }
+    }
+
+    let transition = Transition::new(&State::Idle, &State::Running);
+
+    assert_eq!(cfmt::format!("{0 :<>}", transition), "Idle → Running");
+    assert_eq!(cfmt::format!("{0 :<ascii>}", transition), "Idle -> Running");
+    assert_eq!(cfmt::format!("{0 :<arrow=⇒>}", transition), "Idle ⇒ Running");
+    assert_eq!(cfmt::format!("{0 :<arrow=~~>>}", transition), "Idle ~~> Running");
+    assert_eq!(cfmt::format!("{0 :<elapsed>}", transition), "Idle → Running");
+
+    let transition = transition.with_elapsed(Duration::from_millis(250));
+    assert_eq!(cfmt::format!("{0 :<>}, {0 :<elapsed,arrow=|>}", transition), "Idle → Running, Idle | Running (250ms)");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_transition_invalid_spec() {
+    cfmt::format!("{0 :<arrow=>}", Transition::new(&1, &2));
+}
+
+#[test]
+fn test_uuid() {
+    let uuid = Uuid([0xa1, 0xa2, 0xa3, 0xa4, 0xb1, 0xb2, 0xc1, 0xc2, 0xd1, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8]);
+
+    assert_eq!(cfmt::format!("{0 :<>}", uuid), "a1a2a3a4-b1b2-c1c2-d1d2-d3d4d5d6d7d8");
+    assert_eq!(cfmt::format!("{0 :<simple>}", uuid), "a1a2a3a4b1b2c1c2d1d2d3d4d5d6d7d8");
+    assert_eq!(cfmt::format!("{0 :<urn>}", uuid), "urn:uuid:a1a2a3a4-b1b2-c1c2-d1d2-d3d4d5d6d7d8");
+    assert_eq!(cfmt::format!("{0 :<braced>}", uuid), "{a1a2a3a4-b1b2-c1c2-d1d2-d3d4d5d6d7d8}");
+    assert_eq!(cfmt::format!("{0 :<>}", Uuid([0; 16])), "00000000-0000-0000-0000-000000000000");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_uuid_invalid_spec() {
+    cfmt::format!("{0 :<hyphenated>}", Uuid([0; 16]));
+}
+
+#[test]
+fn test_spark() {
+    assert_eq!(cfmt::format!("{0 :<>}", Spark(&[])), "");
+    assert_eq!(cfmt::format!("{0 :<>}", Spark(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0])), "▁▂▃▄▅▆▇█");
+    assert_eq!(cfmt::format!("{0 :<>}", Spark(&[-2.0, -2.0, -2.0])), "▄▄▄");
+    assert_eq!(cfmt::format!("{0 :<>}", Spark(&[3.0])), "▄");
+    assert_eq!(cfmt::format!("{0 :<>}", Spark(&[10.0, f64::NAN, 0.0, 5.0])), "█ ▁▅");
+}
+
+#[test]
+fn test_escape() {
+    use core::fmt::{self, Write};
+
+    assert_eq!(cfmt::format!("{0 :<html>}", Escape(r#"<a href="x">&'é'</a>"#)), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;é&#39;&lt;/a&gt;");
+    assert_eq!(cfmt::format!("{0 :<json>}", Escape("\"a\\b\"\n\t\r\u{8}\u{c}\u{1}é")), r#"\"a\\b\"\n\t\r\b\f\u0001é"#);
+    assert_eq!(cfmt::format!("{0 :<shell>}, {1 :<shell>}", Escape("it's $HOME"), Escape("")), r#"'it'\''s $HOME', ''"#);
+    assert_eq!(cfmt::format!("{0 :<md>}", Escape("# *bold* [link](url) `code` 1.5!")), r"\# \*bold\* \[link\]\(url\) \`code\` 1\.5\!");
+    assert_eq!(cfmt::format!("{0 :<html>}", Escape("plain")), "plain");
+
+    /// Writer counting the number of written strings
+    struct WriteCount(usize);
+
+    impl Write for WriteCount {
+        fn write_str(&mut self, _: &str) -> fmt::Result {
+            self.0 += 1;
+            Ok(())
+        }
+    }
+
+    // Unescaped text is written in slices between the replaced chars
+    let mut count = WriteCount(0);
+    cfmt::write!(count, "{0 :<html>}", Escape("plain <b> text")).unwrap();
+    assert_eq!(count.0, 5);
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_escape_invalid_spec() {
+    cfmt::format!("{0 :<xml>}", Escape("a"));
+}
+
+#[test]
+fn test_c_array() {
+    let data = [0x00, 0x7f, 0x80, 0xab, 0xff];
+
+    assert_eq!(cfmt::format!("{0 :<>}", CArray(&data)), "{0x00, 0x7f, 0x80, 0xab, 0xff}");
+    assert_eq!(cfmt::format!("{0:# :<>}", CArray(&data)), "{0x00, 0x7F, 0x80, 0xAB, 0xFF}");
+    assert_eq!(cfmt::format!("{0 :<rust>}", CArray(&data)), "[0x00, 0x7f, 0x80, 0xab, 0xff]");
+    assert_eq!(cfmt::format!("{0 :<rust>}, {1 :<wrap=4>}", CArray(&[]), CArray(&[])), "[], {}");
+    assert_eq!(cfmt::format!("{0 :<wrap=2>}", CArray(&data)), "{\n    0x00, 0x7f,\n    0x80, 0xab,\n    0xff,\n}");
+    assert_eq!(cfmt::format!("{0:# :<rust,wrap=5>}", CArray(&data)), "[\n    0x00, 0x7F, 0x80, 0xAB, 0xFF,\n]");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_c_array_invalid_spec() {
+    cfmt::format!("{0 :<wrap=0>}", CArray(&[1]));
+}
+
+#[test]
+fn test_si() {
+    assert_eq!(cfmt::format!("{0 :<Hz>}", Si(1500.0)), "1.5 kHz");
+    assert_eq!(cfmt::format!("{0 :<Hz>}", Si(0.0015)), "1.5 mHz");
+    assert_eq!(cfmt::format!("{0 :<Hz>}, {0:.2 :<Hz>}", Si(1.0)), "1 Hz, 1.00 Hz");
+    assert_eq!(cfmt::format!("{0 :<s>}, {1 :<s>}", Si(2.5e-6), Si(7e-9)), "2.5 µs, 7 ns");
+    assert_eq!(cfmt::format!("{0 :<W>}, {1 :<W>}, {2 :<W>}", Si(-3.2e9), Si(4e12), Si(1e21)), "-3.2 GW, 4 TW, 1000 EW");
+    assert_eq!(cfmt::format!("{0 :<m>}, {1 :<m>}", Si(1e-12), Si(0.0)), "0.001 nm, 0 m");
+    assert_eq!(cfmt::format!("{0 :<>}, {1 :<>}", Si(42.0), Si(42000.0)), "42, 42 k");
+    assert_eq!(cfmt::format!("{0:.1 :<V>}, {1:.1 :<V>}, {2:.0 :<V>}", Si(999.96), Si(0.99996), Si(999.4)), "1.0 kV, 1.0 V, 999 V");
+    assert_eq!(cfmt::format!("{0 :<A>}, {1 :<A>}", Si(f64::NAN), Si(f64::NEG_INFINITY)), "NaN A, -inf A");
+}
+
+#[test]
+fn test_ago() {
+    let format = |seconds| cfmt::format!("{0 :<>} / {0 :<short>}", Ago(seconds));
+
+    assert_eq!(format(0), "just now / now");
+    assert_eq!(format(59), "just now / now");
+    assert_eq!(format(60), "1 minute ago / 1m");
+    assert_eq!(format(61), "1 minute ago / 1m");
+    assert_eq!(format(3_599), "59 minutes ago / 59m");
+    assert_eq!(format(3_600), "1 hour ago / 1h");
+    assert_eq!(format(86_399), "23 hours ago / 23h");
+    assert_eq!(format(86_400), "yesterday / 1d");
+    assert_eq!(format(172_800), "2 days ago / 2d");
+    assert_eq!(format(30 * 86_400), "1 month ago / 1mo");
+    assert_eq!(format(364 * 86_400), "12 months ago / 12mo");
+    assert_eq!(format(365 * 86_400), "1 year ago / 1y");
+    assert_eq!(format(i64::MAX), "292471208677 years ago / 292471208677y");
+
+    assert_eq!(format(-59), "just now / now");
+    assert_eq!(format(-180), "in 3 minutes / in 3m");
+    assert_eq!(format(-86_400), "tomorrow / in 1d");
+    assert_eq!(format(-3 * 86_400), "in 3 days / in 3d");
+    assert_eq!(format(i64::MIN), "in 292471208677 years / in 292471208677y");
+
+    assert_eq!(Ago::from_duration(core::time::Duration::from_secs(125)), Ago(125));
+    assert_eq!(Ago::from_duration(core::time::Duration::MAX), Ago(i64::MAX));
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_ago_invalid_spec() {
+    cfmt::format!("{0 :<long>}", Ago(1));
+}
+
+#[test]
+fn test_frac() {
+    assert_eq!(cfmt::format!("{0 :<>}, {1 :<>}, {2 :<>}", Frac(3, 4), Frac(6, 8), Frac(-10, 4)), "3/4, 3/4, -5/2");
+    assert_eq!(cfmt::format!("{0 :<>}, {1 :<>}, {2 :<>}", Frac(4, 2), Frac(0, 5), Frac(0, -5)), "2, 0, 0");
+    assert_eq!(cfmt::format!("{0 :<>}, {1 :<>}", Frac(3, -6), Frac(-3, -6)), "-1/2, 1/2");
+
+    assert_eq!(cfmt::format!("{0 :<mixed>}, {1 :<mixed>}, {2 :<mixed>}", Frac(3, 2), Frac(-22, 7), Frac(2, 6)), "1 1/2, -3 1/7, 1/3");
+    assert_eq!(cfmt::format!("{0 :<mixed>}, {1 :<mixed>}", Frac(9, 3), Frac(0, 1)), "3, 0");
+
+    assert_eq!(cfmt::format!("{0 :<dec>}, {1 :<dec>}", Frac(3, 4), Frac(1, -8)), "0.75, -0.125");
+    assert_eq!(cfmt::format!("{0:.2 :<dec>}, {0:.0 :<dec>}", Frac(2, 3)), "0.67, 1");
+
+    assert_eq!(cfmt::format!("{0 :<>}, {1 :<>}", Frac(i64::MIN, 2), Frac(i64::MIN, i64::MIN)), "-4611686018427387904, 1");
+    assert_eq!(cfmt::format!("{0 :<>}", Frac(1, i64::MIN)), "-1/9223372036854775808");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_frac_zero_denominator() {
+    cfmt::format!("{0 :<>}", Frac(1, 0));
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_frac_invalid_spec() {
+    cfmt::format!("{0 :<percent>}", Frac(1, 2));
+}
+
+#[test]
+fn test_or_default() {
+    assert_eq!(cfmt::format!("{0 :<(default)>}, {1 :<(default)>}", OrDefault(&0u16), OrDefault(&443u16)), "(default), 443");
+    assert_eq!(cfmt::format!("{0 :<->}, {1 :<->}", OrDefault(&""), OrDefault(&"localhost")), "-, localhost");
+    assert_eq!(cfmt::format!("{0 :<->}, {1 :<->}", OrDefault(&false), OrDefault(&true)), "-, true");
+    assert_eq!(cfmt::format!("{0 :<>}|{1 :<>}", OrDefault(&0.0), OrDefault(&1.5)), "|1.5");
+    assert_eq!(cfmt::format!("[{0:>4 :<->}] [{1:>4 :<->}] [{2:.2 :<->}]", OrDefault(&0), OrDefault(&7), OrDefault(&2.0)), "[   -] [   7] [2.00]");
+}
+
+#[test]
+fn test_level() {
+    let levels = ["ERROR", "WARN", "INFO", "DEBUG", "TRACE", "NOTICE", ""].map(|level| cfmt::format!("[{0 :<pad>}]", Level(level)));
+    assert_eq!(levels, ["[ERROR]", "[WARN ]", "[INFO ]", "[DEBUG]", "[TRACE]", "[NOTICE]", "[     ]"]);
+
+    assert_eq!(cfmt::format!("{0 :<color>}", Level("ERROR")), "\x1b[31mERROR\x1b[0m");
+    assert_eq!(cfmt::format!("{0 :<color>}", Level("warn")), "\x1b[33mwarn\x1b[0m");
+    assert_eq!(cfmt::format!("{0 :<pad,color>}|", Level("Info")), "\x1b[32mInfo\x1b[0m |");
+    assert_eq!(cfmt::format!("{0 :<color>}, {1 :<color>}", Level("debug"), Level("trace")), "\x1b[34mdebug\x1b[0m, \x1b[36mtrace\x1b[0m");
+    assert_eq!(cfmt::format!("{0 :<color,pad>}|", Level("FATAL")), "FATAL|");
+    assert_eq!(cfmt::format!("{0 :<>}", Level("INFO")), "INFO");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_level_invalid_spec() {
+    cfmt::format!("{0 :<pad=8>}", Level("INFO"));
+}
+
+#[test]
+fn test_ptr() {
+    assert_eq!(cfmt::format!("{0 :<>}, {1 :<>}", Ptr(0x7fff_5000), Ptr(0)), "0x7fff5000, 0x0");
+    assert_eq!(cfmt::format!("{0 :<>}", Ptr(usize::MAX)), format!("{:#x}", usize::MAX));
+
+    assert_eq!(cfmt::format!("{0 :<off=0x1000>}, {0 :<off=4096>}", Ptr(0x1024)), "0x1000+0x24, 0x1000+0x24");
+    assert_eq!(cfmt::format!("{0 :<off=0x1000>}, {0 :<off=0x1008>}", Ptr(0x1000)), "0x1000+0x0, 0x1008-0x8");
+
+    assert_eq!(cfmt::format!("{0 :<align>}, {1 :<align>}", Ptr(0x1000), Ptr(0x1001)), "0x1000 (aligned 4096), 0x1001 (aligned 1)");
+    assert_eq!(cfmt::format!("{0 :<align>}, {1 :<off=0x10,align>}", Ptr(0), Ptr(0x18)), "0x0 (null), 0x10+0x8 (aligned 8)");
+
+    let value = 0u64;
+    let ptr = Ptr::new(&value);
+    assert_eq!(ptr.0 % core::mem::align_of::<u64>(), 0);
+    assert_eq!(cfmt::format!("{0 :<>}", ptr), format!("{:p}", &value));
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_ptr_invalid_spec() {
+    cfmt::format!("{0 :<off=0xZZ>}", Ptr(1));
+}
+
+#[test]
+fn test_status() {
+    let health: Vec<_> = [i32::MIN, -1, 0, 1, i32::MAX].iter().map(|&x| cfmt::format!("{0 :<health>}", Status::new(x))).collect();
+    assert_eq!(health, ["🔴", "🔴", "🟡", "🟢", "🟢"]);
+
+    assert_eq!(cfmt::format!("{0 :<>}", Status::new(-7)), "🔴");
+
+    const BATTERY: &[(i32, &str)] = &[(0, "🪫"), (20, "🔋")];
+    assert_eq!(cfmt::format!("{0 :<>}{1 :<>}{2 :<>}", Status(0, BATTERY), Status(19, BATTERY), Status(20, BATTERY)), "🪫🪫🔋");
+    assert_eq!(cfmt::format!("{0 :<health>}", Status(20, BATTERY)), "🟢");
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_status_below_thresholds() {
+    cfmt::format!("{0 :<>}", Status(-1, &[(0, "ok")]));
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_status_invalid_spec() {
+    cfmt::format!("{0 :<mood>}", Status::new(1));
+}
+
+#[test]
+fn test_bidi() {
+    assert_eq!(cfmt::format!("{0 :<ltr>}", Bidi("abc")), "\u{2066}abc\u{2069}");
+    assert_eq!(cfmt::format!("{0 :<rtl>}", Bidi("עברית")), "\u{2067}עברית\u{2069}");
+    assert_eq!(cfmt::format!("{0 :<auto>}", Bidi("")), "\u{2068}\u{2069}");
+    assert_eq!(cfmt::format!("<{0:>6.2 :<ltr>}>", Bidi("abc")), "<\u{2066}    ab\u{2069}>");
+
+    let chars: Vec<_> = cfmt::format!("{0 :<rtl>}", Bidi("א")).chars().collect();
+    assert_eq!(chars, ['\u{2067}', 'א', '\u{2069}']);
+}
+
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_bidi_invalid_spec() {
+    cfmt::format!("{0 :<ttb>}", Bidi("abc"));
+}
@@ -0,0 +1 @@
+//! Empty library target, so that the compile-fail tests are built with the same features of the `custom-format` crate as the tests.
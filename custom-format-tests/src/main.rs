@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests;
 
+#[cfg(all(test, feature = "runtime"))]
+mod builtins;
+
 fn main() {
     println!("Test crate.")
 }
@@ -10,6 +10,116 @@ fn test_format_args() {
     cfmt::println!("{}", cfmt::format_args!("{}", "string"));
 }
 
+#[test]
+fn test_format_args_nl() {
+    let x = 1;
+    assert_eq!(format!("{}", cfmt::format_args_nl!()), "\n");
+    assert_eq!(format!("{}", cfmt::format_args_nl!("string")), "string\n");
+    assert_eq!(format!("{}", cfmt::format_args_nl!("{{{x}}}")), "{1}\n");
+    assert_eq!(format!("{}", cfmt::format_args_nl!(include_str!("../tests/fixtures/plain.txt"))), "{plain}\n\n");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_format_args_nl_custom() {
+    let x = format_args!("string");
+    assert_eq!(format!("{}", cfmt::format_args_nl!("{x :<upper>}")), "STRING\n");
+    assert_eq!(format!("{}", cfmt::format_args_nl!(only = "runtime", "{0 :<upper>}", x)), "STRING\n");
+}
+
+#[test]
+fn test_lazy_format() {
+    use std::cell::Cell;
+
+    let count = Cell::new(0);
+    let next = || {
+        count.set(count.get() + 1);
+        count.get()
+    };
+
+    let lazy = cfmt::lazy_format!("{} {0}", next());
+    assert_eq!(count.get(), 0);
+    assert_eq!(lazy.to_string(), "1 1");
+    assert_eq!(format!("[{}]", lazy), "[2 2]");
+
+    fn owned(s: String) -> impl std::fmt::Display {
+        cfmt::lazy_format!("{s}{}", s.len())
+    }
+
+    assert_eq!(owned("abc".to_owned()).to_string(), "abc3");
+    assert_eq!(cfmt::lazy_format!("string").to_string(), "string");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_lazy_format_custom() {
+    let x = "string";
+    let lazy = cfmt::lazy_format!("{0 :<upper>} {x}", format_args!("{}", x));
+    assert_eq!(lazy.to_string(), "STRING string");
+}
+
+#[test]
+fn test_owned_format_args() {
+    use std::cell::Cell;
+
+    let count = Cell::new(0);
+    let next = || {
+        count.set(count.get() + 1);
+        count.get()
+    };
+
+    let args = cfmt::owned_format_args!("{} {0} {x}", next(), x = next());
+    assert_eq!(count.get(), 2);
+    assert_eq!(args.to_string(), "1 1 2");
+    assert_eq!(format!("[{}]", args), "[1 1 2]");
+    assert_eq!(count.get(), 2);
+
+    fn owned(s: String) -> impl std::fmt::Display {
+        cfmt::owned_format_args!("{s}{}", s.len() + 1)
+    }
+
+    assert_eq!(owned("abc".to_owned()).to_string(), "abc4");
+    assert_eq!(cfmt::owned_format_args!("{{string}}").to_string(), "{string}");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_owned_format_args_custom() {
+    use core::fmt;
+
+    struct Upper(String);
+
+    impl cfmt::runtime::CustomFormat for Upper {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "upper" => f.write_str(&self.0.to_uppercase()),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    fn message(s: &str) -> impl fmt::Display {
+        cfmt::owned_format_args!(only = "runtime", "{0 :<upper>} {0 :<upper>}", Upper(s.to_owned()))
+    }
+
+    assert_eq!(message("string").to_string(), "STRING STRING");
+}
+
+#[test]
+fn test_concat_format() {
+    const VERSION: &str = cfmt::concat_format!("v{}.{}.{}-{}", 1, 2, 3, "beta");
+    assert_eq!(VERSION, "v1.2.3-beta");
+
+    assert_eq!(cfmt::concat_format!("{{}}"), "{}");
+    assert_eq!(cfmt::concat_format!("{0:?} {0} {1:?} {2}", "a\tb", '"', true), "\"a\\tb\" a\tb '\"' true");
+    assert_eq!(cfmt::concat_format!("{0:x} {0:X} {0:o} {0:b} {1} {2} {2:?}", 0xAB_u8, -3, 0.5), "ab AB 253 10101011 -3 0.5 0.5");
+    assert_eq!(cfmt::concat_format!("{} {:?}", 1e20, -2.0f32), format!("{} {:?}", 1e20, -2.0f32));
+    assert_eq!(cfmt::concat_format!("{} {:?} {}", 0.1f32, 0.1f32, 0.1), format!("{} {:?} {}", 0.1f32, 0.1f32, 0.1));
+    assert_eq!(cfmt::concat_format!("{} {} {:?} {}", -0, -0i8, -0.0, -0.0f32), format!("{} {} {:?} {}", -0, -0i8, -0.0, -0.0f32));
+    assert_eq!(cfmt::concat_format!("{} {}", 255u8, -128i8), "255 -128");
+    assert_eq!(cfmt::concat_format!(include_str!("../tests/fixtures/plain.txt")), "{plain}\n");
+}
+
 #[test]
 fn test_print() {
     cfmt::print!("string\n");
@@ -22,6 +132,80 @@ fn test_print() {
     cfmt::eprintln!("{}", "string");
 }
 
+#[test]
+fn test_dbg() {
+    let value = String::from("value");
+
+    cfmt::dbg!();
+    assert_eq!(cfmt::dbg!(1 + 1), 2);
+    assert_eq!(cfmt::dbg!(value.len(), 1 + 1,), (5, 2));
+    assert_eq!(cfmt::dbg!(value), "value");
+
+    let () = cfmt::dbg!("message");
+    let () = cfmt::dbg!("{}", 1);
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_dbg_custom() {
+    let () = cfmt::dbg!("{0 :<upper>}", format_args!("message"));
+    let () = cfmt::dbg!(only = "runtime", "{0 :<upper>}", format_args!("message"));
+}
+
+#[test]
+fn test_print_locked() {
+    let stdout = std::io::stdout();
+    let mut lock = stdout.lock();
+
+    cfmt::print_locked!(lock, "string\n");
+    cfmt::print_locked!(lock, "{}", "string\n");
+    cfmt::println_locked!(lock);
+    cfmt::println_locked!(lock, "string");
+    cfmt::println_locked!(&mut lock, "{}", "string");
+    drop(lock);
+
+    cfmt::print_flush!("string\n");
+    cfmt::print_flush!("{}", "string\n");
+    cfmt::println_flush!();
+    cfmt::println_flush!("string");
+    cfmt::println_flush!("{}", "string");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_print_locked_custom() {
+    let stdout = std::io::stdout();
+    let mut lock = stdout.lock();
+    let x = format_args!("string");
+
+    cfmt::print_locked!(lock, "{x :<upper>}\n");
+    cfmt::println_locked!(lock, only = "runtime", "{x :<upper>}");
+    drop(lock);
+
+    cfmt::print_flush!("{x :<upper>}\n");
+    cfmt::println_flush!(only = "runtime", "{x :<upper>}");
+}
+
+#[test]
+#[should_panic(expected = "failed printing to stdout: failing")]
+fn test_print_locked_error() {
+    use std::io;
+
+    struct Failing;
+
+    impl io::Write for Failing {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "failing"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    cfmt::println_locked!(Failing, "string");
+}
+
 #[test]
 fn test_write() {
     use std::io::Write;
@@ -34,179 +218,2089 @@ fn test_write() {
 }
 
 #[test]
-#[should_panic(expected = "string")]
-fn test_panic_1() {
-    cfmt::panic!("string");
+fn test_format_into() {
+    use std::fmt;
+
+    struct Failing;
+
+    impl fmt::Write for Failing {
+        fn write_str(&mut self, _: &str) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    let mut s = String::new();
+    let result: fmt::Result = cfmt::format_into!(&mut s, "string");
+    assert!(result.is_ok());
+    cfmt::format_into!(s, "{}", 1).unwrap();
+    cfmt::format_into!(&mut s, "{{{}}}", 2).unwrap();
+    assert_eq!(s, "string1{2}");
+
+    assert!(cfmt::format_into!(Failing, "string").is_err());
 }
 
+#[cfg(feature = "runtime")]
 #[test]
-#[should_panic(expected = "string")]
-fn test_panic_2() {
-    cfmt::panic!("{}", "string");
+fn test_format_into_custom() {
+    let mut s = String::new();
+    let x = format_args!("string");
+    cfmt::format_into!(&mut s, "{x :<upper>} ").unwrap();
+    cfmt::format_into!(&mut s, only = "runtime", "{0 :<lower>}", format_args!("STRING")).unwrap();
+    assert_eq!(s, "STRING string");
 }
 
 #[test]
-fn test_no_format_string() {
-    cfmt::println!();
-    cfmt::eprintln!();
+fn test_format_to_slice() {
+    let mut buf = [0u8; 8];
+    assert_eq!(cfmt::format_to_slice!(&mut buf, "{}-{}", 12, 34), Ok("12-34"));
+    assert_eq!(cfmt::format_to_slice!(&mut buf, "12345678"), Ok("12345678"));
+    assert_eq!(cfmt::format_to_slice!(&mut buf, ""), Ok(""));
+    assert!(cfmt::format_to_slice!(&mut buf, "123456789").is_err());
+    assert!(cfmt::format_to_slice!(&mut buf[..2], "{}", "éé").is_err());
+
+    let mut buf = [0u8; 3];
+    assert_eq!(cfmt::format_to_slice!(&mut buf, "{}", 'é'), Ok("é"));
 }
 
+#[cfg(feature = "runtime")]
 #[test]
-fn test_literal_format_string() {
-    assert_eq!(cfmt::format!("string"), "string");
+fn test_format_to_slice_custom() {
+    let mut buf = [0u8; 16];
+    let x = format_args!("string");
+    assert_eq!(cfmt::format_to_slice!(&mut buf, "{x :<upper>}!"), Ok("STRING!"));
+    assert_eq!(cfmt::format_to_slice!(&mut buf, only = "runtime", "{0 :<lower>}", format_args!("STRING")), Ok("string"));
 }
 
 #[test]
-fn test_std_fmt() {
-    assert_eq!(cfmt::format!("Hello"), "Hello");
-    assert_eq!(cfmt::format!("Hello, {}!", "world"), "Hello, world!");
-    assert_eq!(cfmt::format!("The number is {}", 1), "The number is 1");
-    assert_eq!(cfmt::format!("{:?}", (3, 4)), "(3, 4)");
-    assert_eq!(cfmt::format!("{value}", value = 4), "4");
-    let people = "Rustaceans";
-    assert_eq!(cfmt::format!("Hello {people}!"), "Hello Rustaceans!");
-    assert_eq!(cfmt::format!("{} {}", 1, 2), "1 2");
-    assert_eq!(cfmt::format!("{:04}", 42), "0042");
-    assert_eq!(cfmt::format!("{:#?}", (100, 200)), "(\n    100,\n    200,\n)");
-    assert_eq!(cfmt::format!("{1} {} {0} {}", 1, 2), "2 1 1 2");
-    assert_eq!(cfmt::format!("{argument}", argument = "test"), "test");
-    assert_eq!(cfmt::format!("{name} {}", 1, name = 2), "2 1");
-    assert_eq!(cfmt::format!("{a} {c} {b}", a = "a", b = 'b', c = 3), "a 3 b");
-    assert_eq!(cfmt::format!("Hello {:5}!", "x"), "Hello x    !");
-    assert_eq!(cfmt::format!("Hello {:1$}!", "x", 5), "Hello x    !");
-    assert_eq!(cfmt::format!("Hello {1:0$}!", 5, "x"), "Hello x    !");
-    assert_eq!(cfmt::format!("Hello {:width$}!", "x", width = 5), "Hello x    !");
-    let width = 5;
-    assert_eq!(cfmt::format!("Hello {:width$}!", "x"), "Hello x    !");
-    assert_eq!(cfmt::format!("Hello {:<5}!", "x"), "Hello x    !");
-    assert_eq!(cfmt::format!("Hello {:-<5}!", "x"), "Hello x----!");
-    assert_eq!(cfmt::format!("Hello {:^5}!", "x"), "Hello   x  !");
-    assert_eq!(cfmt::format!("Hello {:>5}!", "x"), "Hello     x!");
-    assert_eq!(cfmt::format!("Hello {:^15}!", cfmt::format!("{:?}", Some("hi"))), "Hello   Some(\"hi\")   !");
-    assert_eq!(cfmt::format!("Hello {:+}!", 5), "Hello +5!");
-    assert_eq!(cfmt::format!("{:#x}!", 27), "0x1b!");
-    assert_eq!(cfmt::format!("Hello {:05}!", 5), "Hello 00005!");
-    assert_eq!(cfmt::format!("Hello {:05}!", -5), "Hello -0005!");
-    assert_eq!(cfmt::format!("{:#010x}!", 27), "0x0000001b!");
-    assert_eq!(cfmt::format!("Hello {0} is {1:.5}", "x", 0.01), "Hello x is 0.01000");
-    assert_eq!(cfmt::format!("Hello {1} is {2:.0$}", 5, "x", 0.01), "Hello x is 0.01000");
-    assert_eq!(cfmt::format!("Hello {0} is {2:.1$}", "x", 5, 0.01), "Hello x is 0.01000");
-    assert_eq!(cfmt::format!("Hello {} is {:.*}", "x", 5, 0.01), "Hello x is 0.01000");
-    assert_eq!(cfmt::format!("Hello {1} is {2:.*}", 5, "x", 0.01), "Hello x is 0.01000");
-    assert_eq!(cfmt::format!("Hello {} is {2:.*}", "x", 5, 0.01), "Hello x is 0.01000");
-    assert_eq!(cfmt::format!("Hello {} is {number:.prec$}", "x", prec = 5, number = 0.01), "Hello x is 0.01000");
-    assert_eq!(cfmt::format!("{}, `{name:.*}`", "Hello", 3, name = 1234.56), "Hello, `1234.560`");
-    assert_eq!(cfmt::format!("{}, `{name:.*}`", "Hello", 3, name = "1234.56"), "Hello, `123`");
-    assert_eq!(cfmt::format!("{}, `{name:>8.*}`", "Hello", 3, name = "1234.56"), "Hello, `     123`");
-    assert_eq!(cfmt::format!("Hello {{}}"), "Hello {}");
-    assert_eq!(cfmt::format!("{{ Hello"), "{ Hello");
-    assert_eq!(cfmt::format!("{: ^+2$.*e}", 5, -0.01, 15), "  -1.00000e-2  ");
-    assert_eq!(cfmt::format!("Hello {::>9.*x? }!", 3, 1.0), "Hello ::::1.000!");
+fn test_format_fixed() {
+    const CAPACITY: usize = 8;
 
-    assert_eq!(cfmt::format!("{h}, {h}, {1}, {1}, {a}, {a}, {3}, {b}, {:.*}", 3, a = 1f64.abs(), b = &(1 + 4), c = 2, h = 0), "0, 0, 1, 1, 1, 1, 2, 5, 1.000");
+    let s = cfmt::format_fixed!(CAPACITY, "{}-{}", 12, 34).unwrap();
+    assert_eq!(s, "12-34");
+    assert_eq!((s.len(), s.capacity()), (5, 8));
+    assert_eq!(format!("[{:>7}] {:?}", s, s), "[  12-34] \"12-34\"");
+
+    assert_eq!(cfmt::format_fixed!(CAPACITY, "12345678").unwrap(), "12345678");
+    assert!(cfmt::format_fixed!(CAPACITY, "123456789").is_err());
+    assert!(cfmt::format_fixed!(3, "{}", "éé").is_err());
+    assert!(cfmt::format_fixed!(0, "").unwrap().is_empty());
+
+    let mut s = cfmt::format_fixed!(2 * CAPACITY, "string").unwrap();
+    s.clear();
+    assert_eq!(s, cfmt::buffer::FixedString::<16>::default());
 }
 
-#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[cfg(feature = "runtime")]
 #[test]
-fn test_custom_formatter() {
-    use core::fmt;
+fn test_format_fixed_custom() {
+    let x = format_args!("string");
+    assert_eq!(cfmt::format_fixed!(16, "{x :<upper>}!").unwrap(), "STRING!");
+    assert_eq!(cfmt::format_fixed!(16, only = "runtime", "{0 :<lower>}", format_args!("STRING")).unwrap(), "string");
+}
 
-    struct Custom<T>(T);
+#[test]
+fn test_io_write() {
+    use std::io;
 
-    impl<T: fmt::Display> fmt::Display for Custom<T> {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{}", self.0)
+    struct Failing;
+
+    impl io::Write for Failing {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "failing"))
         }
-    }
 
-    macro_rules! impl_custom_format {
-        (match spec { $($spec:literal => $func:expr $(,)?)* }) => {
-            use cfmt::compile_time::{spec, CustomFormat};
-            $(
-                impl<T: fmt::Display> CustomFormat<{ spec($spec) }> for Custom<T> {
-                    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                        ($func as fn(&Self, &mut fmt::Formatter) -> fmt::Result)(self, f)
-                    }
-                }
-            )*
-        };
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
     }
 
-    impl_custom_format!(match spec {
-        "" => |this, f| write!(f, "({} with spec '')", this.0),
-        "3xxGxx" => |this, f| write!(f, "({} with spec '3xxGxx')", this.0),
-    });
+    let mut v = Vec::new();
+    let result: io::Result<()> = cfmt::io_write!(v, "string");
+    assert!(result.is_ok());
+    cfmt::io_write!(&mut v, "{}", 1).unwrap();
+    cfmt::io_writeln!(v).unwrap();
+    cfmt::io_writeln!(v, "{{}}").unwrap();
+    assert_eq!(v, b"string1\n{}\n");
 
-    impl<T: fmt::Display> cfmt::runtime::CustomFormat for Custom<T> {
-        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
-            write!(f, "({} with runtime spec '{}')", self.0, spec)
-        }
-    }
+    let error = cfmt::io_writeln!(Failing, "string").unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::Other);
+}
 
-    let (g, h) = (Custom(0), Custom(0));
+#[cfg(feature = "runtime")]
+#[test]
+fn test_io_write_custom() {
+    let mut v = Vec::new();
+    let x = format_args!("string");
+    cfmt::io_write!(v, "{x :<upper>} ").unwrap();
+    cfmt::io_writeln!(v, only = "runtime", "{0 :<lower>}", format_args!("STRING")).unwrap();
+    assert_eq!(v, b"STRING string\n");
+}
 
-    let result = cfmt::format!(
-        "aaaa }} {{}}{} {{{{ \" {:#.*} #{h :<z> } {e \u{3A}3xx\u{47}xx  }, {:?}, { :}, {:?}, {},,{}, {8 :<>}",
-        "ok",
-        5,
-        Custom(0.01),
-        (),
-        Custom(1f64.abs()),
-        std::format!("{:?}, {}", (3, 4), 5),
-        r = &1 + 4,
-        b = 2,
-        c = Custom(6),
-        e = { g },
-    );
+#[cfg(feature = "std")]
+#[test]
+fn test_write_bytes() {
+    use std::io;
 
-    assert_eq!(
-        result,
-        "aaaa } {}ok {{ \" 0.01 #(0 with runtime spec 'z') (0 with spec '3xxGxx'), (), (1 with spec ''), \"(3, 4), 5\", 5,,2, (6 with runtime spec '')"
-    );
+    let mut v = Vec::new();
+    let bytes = vec![0x00, 0xAB, 0xFF];
+    cfmt::write_bytes!(v, "{0} {bytes :<raw>} {bytes :<hex>} {1 :<HEX>}", bytes.len(), &bytes[1..]).unwrap();
+    cfmt::write_bytes!(&mut v, only = "runtime", "|{:>3}|{0:?}", 1).unwrap();
+    assert_eq!(v, b"3 \x00\xAB\xFF 00abff ABFF|  1|1");
+
+    let error = cfmt::write_bytes!(v, "{0 :<base64>}", [1u8]).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+    let mut buffer = [0u8; 4];
+    let error = cfmt::write_bytes!(&mut buffer[..], "ab{0 :<raw>}", [1u8; 4]).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::WriteZero);
 }
 
-#[cfg(feature = "compile-time")]
 #[test]
-fn test_spec() {
-    assert_eq!(cfmt::compile_time::spec(""), 0);
-    assert_eq!(cfmt::compile_time::spec("AB"), 0x4241);
-    assert_eq!(cfmt::compile_time::spec("é"), 0xA9C3);
-    assert_eq!(cfmt::compile_time::spec("\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0~"), 0x7E000000000000000000000000000000);
+#[should_panic(expected = "string")]
+fn test_panic_1() {
+    cfmt::panic!("string");
+}
+
+#[test]
+#[should_panic(expected = "string")]
+fn test_panic_2() {
+    cfmt::panic!("{}", "string");
+}
+
+#[test]
+#[should_panic(expected = "not yet implemented")]
+fn test_todo_1() {
+    cfmt::todo!();
 }
 
 #[cfg(feature = "runtime")]
 #[test]
-fn test_custom_formatter_runtime() {
-    use core::fmt;
+#[should_panic(expected = "not yet implemented: missing handler for CLICK")]
+fn test_todo_2() {
+    cfmt::todo!("missing handler for {0 :<upper>}", format_args!("click"));
+}
 
-    struct Custom;
+#[test]
+#[should_panic(expected = "not implemented")]
+fn test_unimplemented_1() {
+    cfmt::unimplemented!();
+}
 
-    impl cfmt::runtime::CustomFormat for Custom {
-        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
-            write!(f, "{}", spec)
+#[cfg(feature = "runtime")]
+#[test]
+#[should_panic(expected = "not implemented: KEY")]
+fn test_unimplemented_2() {
+    cfmt::unimplemented!("{0 :<upper>}", format_args!("key"));
+}
+
+#[test]
+#[should_panic(expected = "internal error: entered unreachable code")]
+fn test_unreachable_1() {
+    cfmt::unreachable!();
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+#[should_panic(expected = "internal error: entered unreachable code: state 3 (INVALID)")]
+fn test_unreachable_2() {
+    cfmt::unreachable!("state {} ({1 :<upper>})", 3, format_args!("invalid"));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_bail_ensure() {
+    use std::error::Error;
+
+    fn bail(x: u8) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if x > 1 {
+            cfmt::bail!("too large: {}", x);
         }
+        cfmt::bail!("string")
     }
 
-    assert_eq!(cfmt::format!("{ :<x>}", Custom), "x");
+    fn ensure(x: u8) -> Result<u8, String> {
+        cfmt::ensure!(x < 10, "too large: {x}");
+        cfmt::ensure!(x % 2 == 0,);
+        Ok(x)
+    }
+
+    assert_eq!(bail(2).unwrap_err().to_string(), "too large: 2");
+    assert_eq!(bail(0).unwrap_err().to_string(), "string");
+    assert_eq!(ensure(4), Ok(4));
+    assert_eq!(ensure(10).unwrap_err(), "too large: 10");
+    assert_eq!(ensure(3).unwrap_err(), "condition failed: `x % 2 == 0`");
+}
+
+#[cfg(all(feature = "runtime", feature = "alloc"))]
+#[test]
+fn test_bail_ensure_custom() {
+    fn check(x: u8) -> Result<(), String> {
+        cfmt::ensure!(x < 10, "too large: {0 :<upper>}", format_args!("{:#x}", x));
+        cfmt::bail!(only = "runtime", "small: {0 :<upper>}", format_args!("{:#x}", x))
+    }
+
+    assert_eq!(check(0xab).unwrap_err(), "too large: 0XAB");
+    assert_eq!(check(0xa).unwrap_err(), "too large: 0XA");
+    assert_eq!(check(9).unwrap_err(), "small: 0X9");
 }
 
 #[cfg(feature = "runtime")]
 #[test]
-#[should_panic(expected = "a formatting trait implementation returned an error")]
-fn test_custom_formatter_runtime_panic() {
+fn test_assert() {
     use core::fmt;
 
     struct Hex(u8);
 
     impl cfmt::runtime::CustomFormat for Hex {
-        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
-            match spec {
-                "x" => write!(f, "{:#02x}", self.0),
-                "X" => write!(f, "{:#02X}", self.0),
-                _ => Err(fmt::Error),
-            }
+        fn fmt(&self, _: &mut fmt::Formatter, _: &str) -> fmt::Result {
+            std::panic!("message formatted while the assertion succeeds: {:#x}", self.0)
         }
     }
 
-    cfmt::format!("{ :<>}", Hex(0xAB));
+    let value = Hex(1);
+    cfmt::assert!(true);
+    cfmt::assert!(1 + 1 == 2, "{value :<x>}");
+    cfmt::assert_eq!(1, 1);
+    cfmt::assert_eq!(1, 1, "{value :<x>}",);
+    cfmt::assert_ne!(1, 2);
+    cfmt::assert_ne!(1, 2, "{value :<x>}");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+#[should_panic(expected = "invalid value: 0XAB")]
+fn test_assert_message() {
+    let value = 0xABu8;
+    cfmt::assert!(value < 0x80, "invalid value: {0 :<upper>}", format_args!("{:#x}", value));
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+#[should_panic(expected = "assertion `left == right` failed: values differ at INDEX 0\n  left: 1\n right: 2")]
+fn test_assert_eq_message() {
+    cfmt::assert_eq!(1, 2, "values differ at {0 :<upper>} {1}", format_args!("index"), 0);
+}
+
+#[test]
+#[should_panic(expected = "assertion `left != right` failed: 1 == 1\n  left: 1\n right: 1")]
+fn test_assert_ne_message() {
+    cfmt::assert_ne!(1, 1, "{0} == {0}", 1);
+}
+
+#[test]
+#[should_panic(expected = "assertion failed: 1 + 1 == 3")]
+fn test_assert_without_message() {
+    cfmt::assert!(1 + 1 == 3);
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_debug_assert() {
+    let value = 1;
+    cfmt::debug_assert!(value == 1);
+    cfmt::debug_assert!(value == 1, "{0 :<upper>}", format_args!("{}", value));
+    cfmt::debug_assert_eq!(value, 1, "{0 :<upper>}", format_args!("{}", value));
+    cfmt::debug_assert_ne!(value, 2, "{0 :<upper>}", format_args!("{}", value));
+
+    let result = std::panic::catch_unwind(|| cfmt::debug_assert_eq!(value, 2, "{0 :<upper>}", format_args!("value")));
+    assert_eq!(result.is_err(), cfg!(debug_assertions));
+}
+
+#[cfg(all(feature = "runtime", debug_assertions))]
+#[test]
+#[should_panic(expected = "assertion `left != right` failed: VALUE\n  left: 1\n right: 1")]
+fn test_debug_assert_ne_message() {
+    cfmt::debug_assert_ne!(1, 1, "{0 :<upper>}", format_args!("value"));
+}
+
+#[test]
+fn test_no_format_string() {
+    cfmt::println!();
+    cfmt::eprintln!();
+}
+
+#[test]
+fn test_literal_format_string() {
+    assert_eq!(cfmt::format!("string"), "string");
+    assert_eq!(cfmt::format!(""), "");
+    assert_eq!(cfmt::format!("   "), "   ");
+    assert_eq!(cfmt::format!(" \t\n "), " \t\n ");
+    assert_eq!(cfmt::format!("{{}}"), "{}");
+    assert_eq!(cfmt::format!("{{x :%a}} {{{{}}}}"), "{x :%a} {{}}");
+    assert_eq!(format!("{}", cfmt::format_args!("{{}}")), "{}");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_crlf_format_string() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            write!(f, "{:#X}{:?}", self.0, spec)
+        }
+    }
+
+    let value = Hex(0xab);
+
+    assert_eq!(cfmt::format!("a\r\n{value :x\r\n}\r\nb\r\n"), "a\r\n0xab\r\nb\r\n");
+    assert_eq!(cfmt::format!("{value :x \t\r\n\u{2000}}\r\n{0:>3\r\n}", 1), "0xab\r\n  1");
+    assert_eq!(cfmt::format!("{value :<X>\r\n}|{value :<X\r\n>}"), r#"0xAB"X"|0xAB"X\r\n""#);
+    assert_eq!(
+        cfmt::format!(
+            "line 1 {value :x}\r\n\
+             line 2 {value :<>}"
+        ),
+        "line 1 0xab\r\nline 2 0xAB\"\""
+    );
+    assert_eq!(cfmt::format!(include_str!("../tests/fixtures/crlf.txt")), "first 0xab\r\nsecond 0xAB\"X\"\r\n");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_colons_in_custom_specifiers() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            fmt::Display::fmt(&cfmt::Padded(format_args!("{:#x}", self.0)), f)
+        }
+    }
+
+    impl CustomFormat<{ spec("12:%a") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "12:{:x}", self.0)
+        }
+    }
+
+    impl CustomFormat<{ spec(">6:x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, ">{}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "5:foo" => write!(f, "foo{}", self.0),
+                "X" => f.pad(&format!("{:#X}", self.0)),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let value = Hex(0xab);
+
+    // The text after the separator is the whole custom format specifier, even if it starts with padding flags
+    assert_eq!(cfmt::format!("[{value :12:%a}]"), "[12:ab]");
+    assert_eq!(cfmt::format!("[{value :>6:x}]"), "[>171]");
+    assert_eq!(cfmt::format!("[{value :<5:foo>}]"), "[foo171]");
+    assert_eq!(cfmt::format!("[{value:>4 :<5:foo>}]"), "[foo171]");
+
+    // Padding flags are written before the separator
+    assert_eq!(cfmt::format!("[{value:>6 :x}]"), "[  0xab]");
+    assert_eq!(cfmt::format!("[{value:6 :x}]"), "[0xab  ]");
+    assert_eq!(cfmt::format!("[{value:é^9 :<X>}]"), "[éé0xABééé]");
+    assert_eq!(cfmt::format!("[{value:>2 :x}]"), "[0xab]");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_padded_custom_output() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt::{self, Write};
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "X" => write!(f, "{:#X}", self.0),
+                "w" => write!(f, "{:?}", f.width()),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let value = Hex(0xab);
+    let width = 7;
+
+    // The implementations ignore the padding flags, which are applied to the whole custom output
+    assert_eq!(cfmt::format!("[{value:>6 :x}] [{value:>6! :x}]"), "[0xab] [  0xab]");
+    assert_eq!(cfmt::format!("[{value:*^8! :<X>}] [{0:é<width$! :<X>}]", value), "[**0xAB**] [0xABééé]");
+    assert_eq!(cfmt::format!("[{value:6! :x}] [{value:>2! :x}] [{value:! :x}]"), "[0xab  ] [0xab] [0xab]");
+
+    // The implementation receives a formatter without flags
+    assert_eq!(cfmt::format!("[{value:>8 :<w>}] [{value:>8! :<w>}]"), "[Some(8)] [    None]");
+
+    let mut s = String::new();
+    cfmt::write!(s, "{value:>6! :x}|{value:<6! :<X>}|").unwrap();
+    assert_eq!(s, "  0xab|0xAB  |");
+}
+
+#[cfg(all(feature = "debug-expansion", feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_debug_expansion() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, _: &str) -> fmt::Result {
+            write!(f, "{:#X}", self.0)
+        }
+    }
+
+    let h = Hex(0xab);
+
+    // The original format string is only added to the generated code, and doesn't alter the output
+    assert_eq!(cfmt::format!("{h :x} {0:>3} {h :<X>}", 1), "0xab   1 0xAB");
+    assert_eq!(format!("{}", cfmt::format_args!("{{{h :x}}}")), "{0xab}");
+
+    let mut s = String::new();
+    fmt::Write::write_fmt(&mut s, cfmt::format_args!("{h :<X>}")).unwrap();
+    assert_eq!(s, "0xAB");
+}
+
+#[test]
+fn test_std_fmt() {
+    assert_eq!(cfmt::format!("Hello"), "Hello");
+    assert_eq!(cfmt::format!("Hello, {}!", "world"), "Hello, world!");
+    assert_eq!(cfmt::format!("The number is {}", 1), "The number is 1");
+    assert_eq!(cfmt::format!("{:?}", (3, 4)), "(3, 4)");
+    assert_eq!(cfmt::format!("{value}", value = 4), "4");
+    let people = "Rustaceans";
+    assert_eq!(cfmt::format!("Hello {people}!"), "Hello Rustaceans!");
+    assert_eq!(cfmt::format!("{} {}", 1, 2), "1 2");
+    assert_eq!(cfmt::format!("{:04}", 42), "0042");
+    assert_eq!(cfmt::format!("{:#?}", (100, 200)), "(\n    100,\n    200,\n)");
+    assert_eq!(cfmt::format!("{1} {} {0} {}", 1, 2), "2 1 1 2");
+    assert_eq!(cfmt::format!("{argument}", argument = "test"), "test");
+    assert_eq!(cfmt::format!("{name} {}", 1, name = 2), "2 1");
+    assert_eq!(cfmt::format!("{a} {c} {b}", a = "a", b = 'b', c = 3), "a 3 b");
+    assert_eq!(cfmt::format!("Hello {:5}!", "x"), "Hello x    !");
+    assert_eq!(cfmt::format!("Hello {:1$}!", "x", 5), "Hello x    !");
+    assert_eq!(cfmt::format!("Hello {1:0$}!", 5, "x"), "Hello x    !");
+    assert_eq!(cfmt::format!("Hello {:width$}!", "x", width = 5), "Hello x    !");
+    let width = 5;
+    assert_eq!(cfmt::format!("Hello {:width$}!", "x"), "Hello x    !");
+    assert_eq!(cfmt::format!("Hello {:<5}!", "x"), "Hello x    !");
+    assert_eq!(cfmt::format!("Hello {:-<5}!", "x"), "Hello x----!");
+    assert_eq!(cfmt::format!("Hello {:^5}!", "x"), "Hello   x  !");
+    assert_eq!(cfmt::format!("Hello {:>5}!", "x"), "Hello     x!");
+    assert_eq!(cfmt::format!("Hello {:★>5}!", "x"), "Hello ★★★★x!");
+    assert_eq!(cfmt::format!("Hello {:★^6}!", "é"), "Hello ★★é★★★!");
+    assert_eq!(cfmt::format!("Hello {:^15}!", cfmt::format!("{:?}", Some("hi"))), "Hello   Some(\"hi\")   !");
+    assert_eq!(cfmt::format!("Hello {:+}!", 5), "Hello +5!");
+    assert_eq!(cfmt::format!("{:#x}!", 27), "0x1b!");
+    assert_eq!(cfmt::format!("Hello {:05}!", 5), "Hello 00005!");
+    assert_eq!(cfmt::format!("Hello {:05}!", -5), "Hello -0005!");
+    assert_eq!(cfmt::format!("{:#010x}!", 27), "0x0000001b!");
+    assert_eq!(cfmt::format!("Hello {0} is {1:.5}", "x", 0.01), "Hello x is 0.01000");
+    assert_eq!(cfmt::format!("Hello {1} is {2:.0$}", 5, "x", 0.01), "Hello x is 0.01000");
+    assert_eq!(cfmt::format!("Hello {0} is {2:.1$}", "x", 5, 0.01), "Hello x is 0.01000");
+    assert_eq!(cfmt::format!("Hello {} is {:.*}", "x", 5, 0.01), "Hello x is 0.01000");
+    assert_eq!(cfmt::format!("Hello {1} is {2:.*}", 5, "x", 0.01), "Hello x is 0.01000");
+    assert_eq!(cfmt::format!("Hello {} is {2:.*}", "x", 5, 0.01), "Hello x is 0.01000");
+    assert_eq!(cfmt::format!("Hello {} is {number:.prec$}", "x", prec = 5, number = 0.01), "Hello x is 0.01000");
+    assert_eq!(cfmt::format!("{}, `{name:.*}`", "Hello", 3, name = 1234.56), "Hello, `1234.560`");
+    assert_eq!(cfmt::format!("{}, `{name:.*}`", "Hello", 3, name = "1234.56"), "Hello, `123`");
+    assert_eq!(cfmt::format!("{}, `{name:>8.*}`", "Hello", 3, name = "1234.56"), "Hello, `     123`");
+    assert_eq!(cfmt::format!("Hello {{}}"), "Hello {}");
+    assert_eq!(cfmt::format!("{{ Hello"), "{ Hello");
+    assert_eq!(cfmt::format!("{: ^+2$.*e}", 5, -0.01, 15), "  -1.00000e-2  ");
+    assert_eq!(cfmt::format!("Hello {::>9.*x? }!", 3, 1.0), "Hello ::::1.000!");
+
+    assert_eq!(cfmt::format!("{h}, {h}, {1}, {1}, {a}, {a}, {3}, {b}, {:.*}", 3, a = 1f64.abs(), b = &(1 + 4), c = 2, h = 0), "0, 0, 1, 1, 1, 1, 2, 5, 1.000");
+}
+
+#[test]
+fn test_named_argument_expressions() {
+    let g = 1;
+    assert_eq!(cfmt::format!("{e}", e = { g }), "1");
+    assert_eq!(
+        cfmt::format!(
+            "{e}, {}",
+            2,
+            e = {
+                let (a, b) = (g, 2);
+                a + b
+            }
+        ),
+        "3, 2"
+    );
+    assert_eq!(
+        cfmt::format!(
+            "{e}",
+            e = match g {
+                0 => "zero",
+                _ => "other",
+            }
+        ),
+        "other"
+    );
+    assert_eq!(cfmt::format!("{e}", e = if g > 0 { 1 } else { 2 }), "1");
+
+    macro_rules! call {
+        ($fmt:literal, $f:expr) => {
+            ($f)()
+        };
+        ($fmt:literal, $f:expr, $a:expr, $b:expr) => {
+            ($f)(*$a, *$b)
+        };
+    }
+
+    assert_eq!(cfmt::fmt_inner!([call!], [], "{f}", f = || 1), 1);
+    assert_eq!(cfmt::fmt_inner!([call!], [], "{f}{a}{b}", f = |a, b| a + b, a = 1, b = 2), 3);
+    assert_eq!(cfmt::fmt_inner!([call!], [], "{f}{}{}", 1, 2, f = |a: i32, b: i32| -> i32 { a * b }), 2);
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_turbofish_arguments() {
+    use core::fmt;
+    use core::marker::PhantomData;
+    use std::collections::HashMap;
+
+    struct Pair<A, B>(A, B);
+
+    impl<A: fmt::Display, B: fmt::Display> Pair<A, B> {
+        fn new(a: A, b: B) -> Self {
+            Self(a, b)
+        }
+    }
+
+    impl<A: fmt::Display, B: fmt::Display> cfmt::runtime::CustomFormat for Pair<A, B> {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            write!(f, "({}{}{})", self.0, spec, self.1)
+        }
+    }
+
+    fn size_of<A, B>() -> usize {
+        core::mem::size_of::<(A, B)>()
+    }
+
+    // Commas between generic arguments are not argument separators, since each argument is parsed as a whole expression
+    assert_eq!(cfmt::format!("{0 :<,>}", Pair::<u8, u16>::new(1, 2)), "(1,2)");
+    assert_eq!(cfmt::format!("{0 :<;>} {1}", Pair::<u8, &str>::new(1, "a"), 3), "(1;a) 3");
+    assert_eq!(cfmt::format!("{p :<->}, {n}", p = Pair::<i8, i8>::new(-1, 1), n = size_of::<u8, u32>()), "(-1-1), 8");
+    assert_eq!(cfmt::format!("{}, {}", HashMap::<u8, u8>::new().len(), Vec::<(u8, u16)>::with_capacity(2).len()), "0, 0");
+    assert_eq!(cfmt::format!("{0:?}, {1}", PhantomData::<(u8, u16)>, size_of::<u16, u16>()), "PhantomData<(u8, u16)>, 4");
+    assert_eq!(format!("{}", cfmt::format_args!("{0 :<,>}", Pair::<u8, u16>::new(3, 4))), "(3,4)");
+
+    let mut s = String::new();
+    fmt::Write::write_fmt(&mut s, cfmt::format_args!("{0 :<, >} {x :<+>}", Pair::<u8, u8>::new(5, 6), x = Pair::<u8, u8>::new(7, 8))).unwrap();
+    assert_eq!(s, "(5, 6) (7+8)");
+}
+
+#[test]
+fn test_raw_keyword_arguments() {
+    // Raw identifiers are referenced without their `r#` prefix in the format string, like with the standard macros
+    assert_eq!(cfmt::format!("{type}", r#type = 1), "1");
+    assert_eq!(cfmt::format!("{type}, {match:>3}, {0}", 0, r#type = 1, r#match = 2), "1,   2, 0");
+
+    let r#type = "t";
+    let r#match = 4;
+    assert_eq!(cfmt::format!("{type}, {match:match$}"), "t,    4");
+    assert_eq!(std::format!("{type}, {match:match$}"), "t,    4");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_raw_keyword_arguments_custom_formatter() {
+    use core::fmt;
+
+    struct Kw;
+
+    impl cfmt::runtime::CustomFormat for Kw {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            write!(f, "<{}>", spec)
+        }
+    }
+
+    let r#type = Kw;
+    assert_eq!(cfmt::format!("{type :<x>}, {fn :<y>}", r#fn = Kw), "<x>, <y>");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_custom_formatter() {
+    use core::fmt;
+
+    struct Custom<T>(T);
+
+    impl<T: fmt::Display> fmt::Display for Custom<T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    cfmt::impl_custom_format!(impl[T: fmt::Display] Custom<T>, match spec {
+        "" => |this, f| write!(f, "({} with spec '')", this.0),
+        "3xxGxx" => |this, f| write!(f, "({} with spec '3xxGxx')", this.0),
+    });
+
+    impl<T: fmt::Display> cfmt::runtime::CustomFormat for Custom<T> {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            write!(f, "({} with runtime spec '{}')", self.0, spec)
+        }
+    }
+
+    let (g, h) = (Custom(0), Custom(0));
+
+    let result = cfmt::format!(
+        "aaaa }} {{}}{} {{{{ \" {:#.*} #{h :<z> } {e \u{3A}3xx\u{47}xx  }, {:?}, { :}, {:?}, {},,{}, {8 :<>}",
+        "ok",
+        5,
+        Custom(0.01),
+        (),
+        Custom(1f64.abs()),
+        std::format!("{:?}, {}", (3, 4), 5),
+        r = &1 + 4,
+        b = 2,
+        c = Custom(6),
+        e = { g },
+    );
+
+    assert_eq!(
+        result,
+        "aaaa } {}ok {{ \" 0.01 #(0 with runtime spec 'z') (0 with spec '3xxGxx'), (), (1 with spec ''), \"(3, 4), 5\", 5,,2, (6 with runtime spec '')"
+    );
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_custom_formatter_std_flags() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Year(i32);
+
+    impl CustomFormat<{ spec("%Y") }> for Year {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.pad(&format!("{:04}", self.0))
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Year {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "%y" => f.pad(&format!("{:02}", self.0 % 100)),
+                "%f" => write!(f, "{:.*}", f.precision().unwrap_or(1), self.0 as f64),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let x = Year(836);
+    let (w, prec) = (6, 3);
+
+    assert_eq!(cfmt::format!("{x:★>10 :%Y}"), "★★★★★★0836");
+    assert_eq!(cfmt::format!("[{x:<6 :%Y}], [{x:^w$ :<%y>}]"), "[0836  ], [  36  ]");
+    assert_eq!(cfmt::format!("{x: :%Y}, {x:.2 :<%f>}, {x:.prec$ :<%f>}, {1:.* :<%f>}", 0, x), "0836, 836.00, 836.000, 836");
+    assert_eq!(cfmt::format!("{:é>1$ :%Y}|{2}", x, 5, "end"), "é0836|end");
+    assert_eq!(cfmt::format!("{0 :%Y}, {1:>6 :<%y>}", &x, &Year(1999)), "0836,     99");
+    assert_eq!(cfmt::format!("{a:.*}, {}, {x:.* :<%f>}, {:.* :<%f>}, {}", 2, "mid", 1, 3, x, "end", a = 1.5), "1.50, mid, 836.0, 836.000, end");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_custom_formatter_flags() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Flags;
+
+    impl Flags {
+        fn write(f: &mut fmt::Formatter) -> fmt::Result {
+            let align = match f.align() {
+                Some(fmt::Alignment::Left) => "<",
+                Some(fmt::Alignment::Right) => ">",
+                Some(fmt::Alignment::Center) => "^",
+                None => "",
+            };
+
+            let (fill, plus, minus, alternate, zero) = (f.fill(), f.sign_plus(), f.sign_minus(), f.alternate(), f.sign_aware_zero_pad());
+            write!(f, "{}{} {:?} {:?} {}{}{}{}", fill, align, f.width(), f.precision(), plus as u8, minus as u8, alternate as u8, zero as u8)
+        }
+    }
+
+    impl CustomFormat<{ spec("f") }> for Flags {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            Flags::write(f)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Flags {
+        fn fmt(&self, f: &mut fmt::Formatter, _: &str) -> fmt::Result {
+            Flags::write(f)
+        }
+    }
+
+    let x = Flags;
+
+    assert_eq!(cfmt::format!("{x :f}"), "  None None 0000");
+    assert_eq!(cfmt::format!("{x:+#08.3 :f}"), "  Some(8) Some(3) 1011");
+    assert_eq!(cfmt::format!("{x:*^-5 :<f>}"), "*^ Some(5) None 0100");
+    assert_eq!(cfmt::format!("{x:<#.0 :<f>}"), " < None Some(0) 0010");
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_custom_formatter_captured_counts() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Float(f64);
+
+    impl CustomFormat<{ spec("%f") }> for Float {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match (f.width(), f.precision()) {
+                (Some(width), Some(precision)) => write!(f, "{:>width$.precision$}", self.0, width = width, precision = precision),
+                (None, Some(precision)) => write!(f, "{:.*}", precision, self.0),
+                _ => write!(f, "{}", self.0),
+            }
+        }
+    }
+
+    let x = Float(1.5);
+    let (prec, w) = (3, 8);
+
+    assert_eq!(cfmt::format!("{v:.prec$ :%f}", v = x), "1.500");
+    assert_eq!(cfmt::format!("[{x:w$.prec$ :%f}]"), "[   1.500]");
+    assert_eq!(cfmt::format!("{v:.p$ :%f}, {v :%f}", v = x, p = 1), "1.5, 1.5");
+    assert_eq!(cfmt::format!("{0:.prec$ :%f}, {0:.1$ :%f}", x, 2), "1.500, 1.50");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_formatter_ext() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use cfmt::runtime::FormatterExt;
+    use core::fmt;
+
+    struct Date {
+        year: i32,
+        month: u8,
+        day: u8,
+    }
+
+    impl CustomFormat<{ spec("%y") }> for Date {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.pad_display(&format_args!("{:02}", self.year % 100))
+        }
+    }
+
+    impl CustomFormat<{ spec("%m") }> for Date {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.forward(&self.month)
+        }
+    }
+
+    impl CustomFormat<{ spec("%d") }> for Date {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.forward(&self.day)
+        }
+    }
+
+    impl CustomFormat<{ spec("%D") }> for Date {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let month = cfmt::custom_formatter!("%m", self);
+            let day = cfmt::custom_formatter!("%d", self);
+            let year = cfmt::custom_formatter!("%y", self);
+            f.pad_display(&format_args!("{:02}/{:02}/{}", month, day, year))
+        }
+    }
+
+    impl CustomFormat<{ spec("%M") }> for Date {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.forward(&cfmt::custom_formatter!("%m", self))
+        }
+    }
+
+    let date = Date { year: 1836, month: 5, day: 18 };
+
+    assert_eq!(cfmt::format!("[{date :%D}]"), "[05/18/36]");
+    assert_eq!(cfmt::format!("[{date:12 :%D}]"), "[05/18/36    ]");
+    assert_eq!(cfmt::format!("[{date:*>12 :%D}], [{date:-^11 :%D}]"), "[****05/18/36], [-05/18/36--]");
+    assert_eq!(cfmt::format!("[{date:4 :%D}]"), "[05/18/36]");
+    assert_eq!(cfmt::format!("[{date:03 :%m}], [{date:<3 :%M}], [{date:>4 :%y}]"), "[005], [5  ], [  36]");
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_specs_attribute() {
+    use core::fmt;
+
+    struct Time<T> {
+        hour: T,
+        minute: T,
+    }
+
+    #[custom_format::specs]
+    impl<T: fmt::Display> Time<T> {
+        fn spec_H(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:0>2}", self.hour)
+        }
+
+        #[spec("%M")]
+        fn minute(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:0>2}", self.minute)
+        }
+
+        #[spec("spec_H")]
+        fn raw_hour(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}", self.hour)
+        }
+
+        fn unmapped(&self) -> String {
+            cfmt::format!("{0 :%H}:{0 :%M}", *self)
+        }
+    }
+
+    let time = Time { hour: 7, minute: 5 };
+
+    assert_eq!(cfmt::format!("{time :%H}:{time :%M}, {time :spec_H}"), "07:05, 7");
+    assert_eq!(time.unmapped(), "07:05");
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_derive_custom_format() {
+    use cfmt::compile_time::CustomFormat;
+    use core::fmt;
+
+    fn fmt_time<T: fmt::Display>(time: &Time<T>, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:0>2}:{:0>2}", time.hour, time.minute)
+    }
+
+    fn fmt_padded<T: fmt::Display>(value: &T, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:0>2}", value)
+    }
+
+    #[derive(CustomFormat)]
+    #[custom_format(spec = "%R", with = "fmt_time")]
+    struct Time<T: fmt::Display> {
+        #[custom_format(spec = "%H", with = "fmt_padded")]
+        #[custom_format(spec = "%k")]
+        hour: T,
+        #[custom_format(spec = "%M", with = "fmt_padded")]
+        minute: T,
+    }
+
+    #[derive(CustomFormat)]
+    struct Pair<'a>(#[custom_format(spec = "first")] &'a str, #[custom_format(spec = "second")] &'a str);
+
+    fn iso(date: &Date, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}", date.year, date.month)
+    }
+
+    fn fmt_date(date: &Date, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}/{:04}", date.month, date.year)
+    }
+
+    #[derive(CustomFormat)]
+    #[custom_format(with = "iso")]
+    #[custom_format(rename = "%D", with = "fmt_date")]
+    struct Date {
+        #[custom_format()]
+        #[custom_format(rename = "%Y")]
+        year: i32,
+        #[custom_format(rename = "%m", with = "fmt_padded")]
+        month: u8,
+    }
+
+    let time = Time { hour: 7, minute: 5 };
+    assert_eq!(cfmt::format!("{time :%R}, {time :%H}h{time :%M}, {time :%k}"), "07:05, 07h05, 7");
+
+    let pair = Pair("a", "b");
+    assert_eq!(cfmt::format!("{pair :second}{pair :first}"), "ba");
+
+    let date = Date { year: 2023, month: 7 };
+    assert_eq!(cfmt::format!("{date :iso}, {date :%D}, {date :year}, {date :%Y}-{date :%m}"), "2023-07, 07/2023, 2023, 2023-07");
+}
+
+#[cfg(all(feature = "compile-time", feature = "std"))]
+#[test]
+fn test_derive_custom_format_errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/derive/*.rs");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_include_str() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, _: &str) -> fmt::Result {
+            write!(f, "{:#X}", self.0)
+        }
+    }
+
+    let value = Hex(0xab);
+
+    assert_eq!(cfmt::format!(include_str!("../tests/fixtures/hex.txt"), name = "value"), "value: 0xab (0xAB)\n");
+    assert_eq!(format!("{}", cfmt::format_args!(include_str!("../tests/fixtures/hex.txt"), name = 1)), "1: 0xab (0xAB)\n");
+
+    let name = "captured";
+    assert_eq!(cfmt::format!(include_str!("../tests/fixtures/hex.txt")), "captured: 0xab (0xAB)\n");
+    assert_eq!(cfmt::format!(only = "runtime", include_str!("../tests/fixtures/plain.txt")), "{plain}\n");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_only_option() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt::{self, Write};
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, _: &str) -> fmt::Result {
+            write!(f, "{:#X}", self.0)
+        }
+    }
+
+    let h = Hex(0xab);
+
+    assert_eq!(cfmt::format!(only = "compile-time", "{h :x}, {}", 1), "0xab, 1");
+    assert_eq!(cfmt::format!(only = "runtime", "{h :<x>}, {0:?}", 1), "0xAB, 1");
+    assert_eq!(cfmt::format!(only = "runtime", "no specifier"), "no specifier");
+    assert_eq!(format!("{}", cfmt::format_args!(only = "compile-time", "{0 :x}", h)), "0xab");
+
+    let mut s = String::new();
+    cfmt::writeln!(s, only = "compile-time", "{h :x}").unwrap();
+    assert_eq!(s, "0xab\n");
+}
+
+#[cfg(all(feature = "compile-time", not(feature = "nightly-spec-str")))]
+#[test]
+fn test_spec() {
+    assert_eq!(cfmt::compile_time::spec(""), 0);
+    assert_eq!(cfmt::compile_time::spec("AB"), 0x4241);
+    assert_eq!(cfmt::compile_time::spec("é"), 0xA9C3);
+    assert_eq!(cfmt::compile_time::spec("\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0~"), 0x7E000000000000000000000000000000);
+}
+
+#[cfg(all(feature = "compile-time", not(feature = "nightly-spec-str")))]
+#[test]
+fn test_spec_long() {
+    use cfmt::compile_time::spec;
+
+    assert_eq!(spec("%Y-%m-%dT%H:%M:%S%.3fZ"), 0xFF99034CECA454BE14A8DCFDFEE7BBE3);
+    assert_ne!(spec("0123456789abcdefg"), spec("0123456789abcdefh"));
+    assert_eq!(spec("0123456789abcdefg") >> 120, 0xFF);
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_compile_time_long_spec() {
+    use cfmt::compile_time::spec;
+
+    let timestamp = cfmt::compile_time::from_fn::<{ spec("%Y-%m-%dT%H:%M:%S%.3fZ") }, _>(|f| f.write_str("1970-01-01T00:00:00.000Z"));
+    assert_eq!(cfmt::format!("{timestamp :%Y-%m-%dT%H:%M:%S%.3fZ}"), "1970-01-01T00:00:00.000Z");
+}
+
+#[cfg(feature = "nightly-spec-str")]
+#[test]
+fn test_spec_str() {
+    use cfmt::compile_time::CustomFormat;
+
+    use core::fmt;
+
+    struct Date;
+
+    impl CustomFormat<"%Y-%m-%dT%H:%M:%S%.3fZ"> for Date {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("1970-01-01T00:00:00.000Z")
+        }
+    }
+
+    impl CustomFormat<"%Y"> for Date {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("1970")
+        }
+    }
+
+    assert_eq!(cfmt::compile_time::spec("%Y"), "%Y");
+    assert_eq!(cfmt::format!("{0 :%Y}, {0 :%Y-%m-%dT%H:%M:%S%.3fZ}", Date), "1970, 1970-01-01T00:00:00.000Z");
+    assert_eq!(cfmt::format!("{0 :bold,bright_cyan,bg_blue}", cfmt::style::Styled("a")), "\x1b[1;96;44ma\x1b[0m");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime", feature = "alloc"))]
+#[test]
+fn test_custom_format_pointers() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn empty_is_valid(&self) -> bool {
+            false
+        }
+
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "X" => write!(f, "{:#X}", self.0),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let mut value = Hex(0x12);
+    let (boxed, rc, arc) = (Box::new(Hex(0x34)), Rc::new(Hex(0x56)), Arc::new(Hex(0x78)));
+
+    assert_eq!(cfmt::format!("{0 :x} {0 :<X>}", &&value), "0x12 0x12");
+    assert_eq!(cfmt::format!("{0 :x} {0 :<X>}", &mut value), "0x12 0x12");
+    assert_eq!(cfmt::format!("{boxed :x} {rc :x} {arc :x}"), "0x34 0x56 0x78");
+    assert_eq!(cfmt::format!("{boxed :<X>} {rc :<X>} {arc :<X>}"), "0x34 0x56 0x78");
+    assert!(!cfmt::runtime::CustomFormat::empty_is_valid(&&rc));
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_compile_time_from_fn() {
+    use cfmt::compile_time::{from_fn, spec};
+
+    let value = 0xab;
+    let hex = from_fn::<{ spec("x") }, _>(|f| write!(f, "{:#x}", value));
+    let upper_hex = from_fn::<{ spec("X") }, _>(|f| write!(f, "{:#X}", value));
+
+    assert_eq!(cfmt::format!("{hex :x}, {upper_hex :X}, {hex:>6 :x}"), "0xab, 0xAB, 0xab");
+    assert_eq!(cfmt::custom_formatter!("x", &hex).to_string(), "0xab");
+    assert_eq!(format!("{:?}", hex), "FromFn { .. }");
+
+    let padded = from_fn::<{ spec("pad") }, _>(|f| f.pad("ab"));
+    assert_eq!(cfmt::format!("[{padded:>4 :pad}]"), "[  ab]");
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_custom_formatter_forwarded_traits() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    #[derive(Debug)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    impl CustomFormat<{ spec("xy") }> for Point {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "({}, {})", self.x, self.y)
+        }
+    }
+
+    let point = cfmt::custom_formatter!("xy", &Point { x: 1, y: -2 });
+    assert_eq!(format!("{}", point), "(1, -2)");
+    assert_eq!(format!("{:?}", point), "Point { x: 1, y: -2 }");
+    assert_eq!(format!("{:?}", [&point]), "[Point { x: 1, y: -2 }]");
+
+    let value = cfmt::custom_formatter!("x", &42u8);
+    assert_eq!(format!("{:x}, {:#X}, {:o}, {:#010b}", value, value, value, value), "2a, 0x2A, 52, 0b00101010");
+
+    let float = cfmt::custom_formatter!("x", &1234.5f64);
+    assert_eq!(format!("{:e}, {:E}", float, float), "1.2345e3, 1.2345E3");
+}
+
+#[cfg(all(feature = "compile-time", not(feature = "nightly-spec-str")))]
+#[test]
+fn test_custom_format_with_spec() {
+    use cfmt::compile_time::{CustomFormat, CustomFormatter, Spec};
+    use core::fmt;
+
+    struct Tagged(u8);
+
+    impl<const SPEC: Spec> CustomFormat<SPEC> for Tagged {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+
+        fn fmt_with_spec(&self, f: &mut fmt::Formatter, spec: &'static str) -> fmt::Result {
+            match spec {
+                "hex" => write!(f, "{:#x}", self.0),
+                _ => write!(f, "<{}>{}</{}>", spec, self.0, spec),
+            }
+        }
+    }
+
+    let tagged = Tagged(42);
+    let long_spec = "a-very-long-tag-name-above-sixteen-bytes";
+
+    assert_eq!(cfmt::format!("{tagged :b}, {tagged :hex}, {tagged :}"), "<b>42</b>, 0x2a, <>42</>");
+    assert_eq!(cfmt::format!("{tagged :a-very-long-tag-name-above-sixteen-bytes}"), format!("<{0}>42</{0}>", long_spec));
+    assert_eq!(cfmt::format!("{0 :b}", &&tagged), "<b>42</b>");
+    assert_eq!(cfmt::custom_formatter!("i", &tagged).to_string(), "<i>42</i>");
+    assert_eq!(CustomFormatter::<_, { cfmt::compile_time::spec("i") }>::new(&tagged).to_string(), "42");
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_spec_str_conversion() {
+    use cfmt::compile_time::{spec, spec_str};
+
+    assert_eq!(spec_str(spec("")).as_str(), Some(""));
+    assert_eq!(spec_str(spec("%Y")).as_str(), Some("%Y"));
+    assert_eq!(spec_str(spec("0123456789abcdef")).as_str(), Some("0123456789abcdef"));
+    assert_eq!(format!("[{:>4}], {:?}", spec_str(spec("%Y")), spec_str(spec("%Y"))), "[  %Y], \"%Y\"");
+}
+
+#[cfg(all(feature = "compile-time", not(feature = "nightly-spec-str")))]
+#[test]
+fn test_spec_str_conversion_not_recoverable() {
+    use cfmt::compile_time::{spec, spec_str};
+
+    let hashed = spec_str(spec("0123456789abcdefg"));
+    assert_eq!(hashed.as_str(), None);
+    assert_eq!(hashed.to_string(), format!("{:#034x}", spec("0123456789abcdefg")));
+    assert_eq!(format!("{:?}", hashed), format!("SpecStr({:#034x})", spec("0123456789abcdefg")));
+
+    assert_eq!(spec_str(0xFF).as_str(), None);
+    assert_eq!(spec_str(spec("a\0\0")), spec_str(spec("a")));
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_custom_format_prefix() {
+    use cfmt::compile_time::{spec, CustomFormat, CustomFormatPrefix};
+    use core::fmt;
+
+    struct Time {
+        second: u8,
+        nanoseconds: u32,
+    }
+
+    impl CustomFormat<{ spec("%S") }> for Time {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:02}", self.second)
+        }
+    }
+
+    impl CustomFormatPrefix<{ spec("%N") }> for Time {
+        fn fmt(&self, f: &mut fmt::Formatter, rest: &str) -> fmt::Result {
+            match rest.parse::<u32>() {
+                Ok(n @ 1..=9) => write!(f, "{:0width$}", self.nanoseconds / 10u32.pow(9 - n), width = n as usize),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    impl CustomFormatPrefix<{ spec("rest") }> for Time {
+        fn fmt(&self, f: &mut fmt::Formatter, rest: &str) -> fmt::Result {
+            f.pad(rest)
+        }
+    }
+
+    // An implementation for the whole format specifier takes precedence over the implementation for its prefix
+    impl CustomFormat<{ spec("%N(0)") }> for Time {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("-")
+        }
+    }
+
+    impl CustomFormat<{ spec("s(2)") }> for Time {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:.2}", f64::from(self.second) + f64::from(self.nanoseconds) / 1e9)
+        }
+    }
+
+    cfmt::deprecate_spec!(Time, "rest");
+
+    let time = Time { second: 5, nanoseconds: 123456789 };
+
+    assert_eq!(cfmt::format!("{time :%S}.{time :%N(3)}, {time :%S}.{time :%N(9)}"), "05.123, 05.123456789");
+    assert_eq!(cfmt::format!("{time :%N(0)}, {time :%N(1)}, {time :s(2)}"), "-, 1, 5.12");
+    assert_eq!(cfmt::custom_prefix_formatter!("%N", "1", &&time).to_string(), "1");
+
+    // Deprecated prefixes are still valid
+    #[allow(deprecated)]
+    {
+        assert_eq!(cfmt::format!("[{time :rest()}], [{time :rest(a(b))}], [{time:>4 :rest(c)}]"), "[], [a(b)], [   c]");
+    }
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_compile_time_runtime_fallback() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Date {
+        year: i32,
+        month: u8,
+    }
+
+    impl CustomFormat<{ spec("%Y") }> for Date {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:04}", self.year)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Date {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "%Y" => write!(f, "runtime {}", self.year),
+                "%m" => write!(f, "{:02}", self.month),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let date = Date { year: 2023, month: 7 };
+
+    assert_eq!(cfmt::format!("{date :%Y|<>}-{date :%m|<>}"), "2023-07");
+    assert_eq!(cfmt::format!("{0 :%m|<>}, {0 :%Y|<>}", &date), "07, 2023");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_impl_runtime_custom_format() {
+    use core::fmt;
+
+    struct Wrapper<T>(T);
+
+    cfmt::impl_runtime_custom_format!(impl[T: fmt::Display] Wrapper<T>, match spec {
+        "" => |this, f| write!(f, "{}", this.0),
+        "[]" => |this, f| write!(f, "[{}]", this.0),
+    });
+
+    assert_eq!(cfmt::format!("{0 :<>}, {0 :<[]>}", Wrapper('a')), "a, [a]");
+    assert!(cfmt::runtime::CustomFormat::empty_is_valid(&Wrapper(0)));
+    assert!(fmt::Write::write_fmt(&mut String::new(), format_args!("{}", cfmt::runtime::CustomFormatter::new("()", &Wrapper(0)))).is_err());
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_define_specs() {
+    use cfmt::compile_time::spec;
+    use cfmt::runtime::CustomFormat;
+    use core::fmt;
+
+    struct Wrapper<T>(T);
+
+    fn brackets<T: fmt::Display>(this: &Wrapper<T>, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}]", this.0)
+    }
+
+    cfmt::define_specs!(impl[T: fmt::Display] Wrapper<T>, match spec {
+        "" as EMPTY => |this, f| write!(f, "{}", this.0),
+        "[]" as BRACKETS => brackets,
+        "%a very long format specifier" as LONG => |_, f| f.write_str("long"),
+    });
+
+    let value = Wrapper(1);
+
+    assert_eq!(Wrapper::<u8>::SUPPORTED_SPECS, ["", "[]", "%a very long format specifier"]);
+    assert_eq!((Wrapper::<u8>::EMPTY, Wrapper::<u8>::BRACKETS), (spec(""), spec("[]")));
+    assert_eq!(Wrapper::<u8>::LONG, spec("%a very long format specifier"));
+    assert!(value.empty_is_valid());
+
+    assert_eq!(cfmt::format!("{value :}, {value :[]}, {value :%a very long format specifier}"), "1, [1], long");
+    assert_eq!(cfmt::format!("{0 :<>}, {0 :<[]>}", Wrapper('a')), "a, [a]");
+
+    assert!(Wrapper::<u8>::check_spec("[]").is_ok());
+    let error = Wrapper::<u8>::check_spec("()").unwrap_err();
+    assert_eq!((error.spec(), error.supported()), ("()", Wrapper::<u8>::SUPPORTED_SPECS));
+    assert_eq!(error.to_string(), "unsupported format specifier `()`, expected one of: ``, `[]`, `%a very long format specifier`");
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime"))]
+#[test]
+fn test_empty_spec_display_fallback() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Custom;
+
+    impl CustomFormat<{ spec("") }> for Custom {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("compile-time")
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Custom {
+        fn fmt(&self, f: &mut fmt::Formatter, _: &str) -> fmt::Result {
+            f.write_str("runtime")
+        }
+    }
+
+    impl fmt::Display for Custom {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("display")
+        }
+    }
+
+    let (x, s) = (Custom, "abc");
+
+    assert_eq!(cfmt::format!("{x :}, {x :<>}, {x :< >}"), "compile-time, runtime, runtime");
+    assert_eq!(cfmt::format!("{s :}, {s :<>}, {0 :}, {1:>4 :}, {1:>3 :<>}", 5, 'c'), "abc, abc, 5,    c,   c");
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_deprecate_spec() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl CustomFormat<{ spec("hex") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl CustomFormat<{ spec("h") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:x}", self.0)
+        }
+    }
+
+    cfmt::deprecate_spec!(Hex, "hex", "h");
+
+    let value = Hex(0xab);
+    assert_eq!(cfmt::format!("{value :x}"), "0xab");
+
+    // Deprecated format specifiers are still valid
+    #[allow(deprecated)]
+    {
+        assert_eq!(cfmt::format!("{value :hex}, {value :h}"), "0xab, ab");
+        assert_eq!(format!("{}", cfmt::format_args!("{value :hex}")), "0xab");
+    }
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_custom_formatter_runtime() {
+    use core::fmt;
+
+    struct Custom;
+
+    impl cfmt::runtime::CustomFormat for Custom {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            write!(f, "{}", spec)
+        }
+    }
+
+    assert_eq!(cfmt::format!("{ :<x>}", Custom), "x");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_ambient_provider() {
+    use std::cell::Cell;
+
+    std::thread_local! {
+        static CLOCK: Cell<u32> = Cell::new(0);
+    }
+
+    cfmt::runtime::set_provider("now", |f| CLOCK.with(|clock| write!(f, "t={}", clock.get())));
+
+    assert_eq!(cfmt::format!("[{:<now>}] {} {x}", "hello", x = 1), "[t=0] hello 1");
+
+    CLOCK.with(|clock| clock.set(5));
+    assert_eq!(cfmt::format!("[{:<now> }] {:<now>}, {0}", "end"), "[t=5] t=5, end");
+
+    cfmt::runtime::set_provider("now", |f| f.write_str("later"));
+    assert_eq!(cfmt::format!("{:<now>}"), "later");
+
+    assert!(cfmt::runtime::remove_provider("now"));
+    assert!(!cfmt::runtime::remove_provider("now"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_ambient_provider_missing() {
+    cfmt::format!("{:<missing>}");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_count_write() {
+    use cfmt::runtime::CountWrite;
+    use core::fmt::Write;
+
+    let mut count = CountWrite::new();
+    assert_eq!((count.bytes(), count.chars()), (0, 0));
+
+    count.write_str("abc").unwrap();
+    assert_eq!((count.bytes(), count.chars()), (3, 3));
+
+    count.write_str("é€😀").unwrap();
+    assert_eq!((count.bytes(), count.chars()), (12, 6));
+
+    count.write_char('ß').unwrap();
+    assert_eq!((count.bytes(), count.chars()), (14, 7));
+
+    let count = CountWrite::measure(format_args!("{:>5}|{}", "€", 1.5));
+    assert_eq!((count.bytes(), count.chars()), (11, 9));
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_custom_formatter_pair() {
+    use cfmt::runtime::CustomFormat2;
+    use core::fmt;
+
+    struct Count(u32);
+
+    impl CustomFormat2 for Count {
+        fn fmt(&self, other: &Self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "ratio" => f.pad(&format!("{}/{}", self.0, other.0)),
+                "percent" => write!(f, "{:.*}%", f.precision().unwrap_or(0), 100.0 * self.0 as f64 / other.0 as f64),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    struct Meters(f64);
+    struct Seconds(f64);
+
+    impl CustomFormat2<Seconds> for Meters {
+        fn fmt(&self, other: &Seconds, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "speed" => write!(f, "{} m/s", self.0 / other.0),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let (done, total) = (Count(3), Count(8));
+
+    assert_eq!(cfmt::format!("{done, total :<ratio>}"), "3/8");
+    assert_eq!(cfmt::format!("{done,total :<ratio>}, {1, 0 :<ratio>}", done, total), "3/8, 8/3");
+    assert_eq!(cfmt::format!("{, :<ratio>} {, :<ratio>}", Count(1), Count(2), Count(3), Count(4)), "1/2 3/4");
+    assert_eq!(cfmt::format!("[{done, total:>6 :<ratio>}] {done, total:.1 :<percent>}"), "[   3/8] 37.5%");
+    assert_eq!(cfmt::format!("{0, 1 :<speed>}", Meters(100.0), Seconds(8.0)), "12.5 m/s");
+    assert_eq!(format!("{}", cfmt::format_args!("{done, total :<ratio>}")), "3/8");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_custom_formatter_debug_fallback() {
+    use core::fmt;
+
+    #[derive(Debug)]
+    struct Date {
+        year: i32,
+        month: u8,
+    }
+
+    impl cfmt::runtime::CustomFormat for Date {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "%Y" => write!(f, "{:04}", self.year),
+                "%Y-%m" => {
+                    write!(f, "{:04}-", self.year)?;
+                    match self.month {
+                        1..=12 => write!(f, "{:02}", self.month),
+                        _ => Err(fmt::Error),
+                    }
+                }
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let date = Date { year: 2023, month: 7 };
+    let invalid = Date { year: 2023, month: 13 };
+
+    assert_eq!(cfmt::format!("{date :<%Y>|?}, {date :<%Y-%m>|?}"), "2023, 2023-07");
+    assert_eq!(cfmt::format!("{date :<%q>|?}"), "Date { year: 2023, month: 7 }");
+    assert_eq!(cfmt::format!("{0 :<%Y-%m>|?}", invalid), "Date { year: 2023, month: 13 }");
+    assert_eq!(cfmt::format!("[{date:>6 :<%q>|?}]"), format!("[{:>6?}]", date));
+    assert_eq!(cfmt::format!("{date:#?} {date:# :<%q>|?}"), format!("{0:#?} {0:#?}", date));
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_debug_quoted() {
+    use core::fmt;
+
+    struct Name(&'static str);
+
+    impl cfmt::runtime::CustomFormat for Name {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "name" => write!(f, "{}", self.0),
+                "?" => write!(f, "<{}>", self.0),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let name = Name("O'Brien \"Jr\"\n\t\u{7f}é");
+    let output = cfmt::format!("{name :<name>}");
+
+    assert_eq!(cfmt::format!("{name :<name?>}"), format!("{:?}", output));
+    assert_eq!(cfmt::format!("[{name:>30 :<name?>}]"), format!("[{:>30}]", format!("{:?}", output)));
+    assert_eq!(cfmt::format!("{0 :<??>}, {0 :<?>}", Name("a")), r#""<a>", <a>"#);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_template() {
+    use cfmt::runtime::{Argument, Template, TemplateError};
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "hex" => write!(f, "{:#x}", self.0),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let template = Template::new("{0} {1 :<hex>}", 2).unwrap();
+    assert_eq!(template.arg_count(), 2);
+
+    let (name, value) = ("value", Hex(0xAB));
+    let args = [Argument::display(&name), Argument::custom(&value)];
+    assert_eq!(template.render(&args).unwrap().to_string(), "value 0xab");
+
+    let template = Template::new("{{{ :<hex>}}} {}{{}}{0 :<hex>}", 2).unwrap();
+    assert_eq!(template.render(&[Argument::custom(&Hex(1)), Argument::display(&2)]).unwrap().to_string(), "{0x1} 2{}0x1");
+    assert_eq!(Template::new("", 0).unwrap().render(&[]).unwrap().to_string(), "");
+
+    assert_eq!(Template::new("{0} {1 :<hex>}", 1).unwrap_err(), TemplateError::MissingArgument(1));
+    assert_eq!(Template::new("{} {} {}", 2).unwrap_err(), TemplateError::MissingArgument(2));
+    assert_eq!(Template::new("{0", 1).unwrap_err(), TemplateError::InvalidFormatString);
+    assert_eq!(Template::new("0}", 1).unwrap_err(), TemplateError::InvalidFormatString);
+    assert_eq!(Template::new("{x}", 1).unwrap_err(), TemplateError::InvalidArgument);
+    assert_eq!(Template::new("{0:>5}", 1).unwrap_err(), TemplateError::InvalidArgument);
+    assert_eq!(Template::new("{0 :hex}", 1).unwrap_err(), TemplateError::InvalidSpecifier);
+
+    assert_eq!(template.render(&args[..1]).unwrap_err(), TemplateError::ArgumentCount { expected: 2, found: 1 });
+    assert_eq!(template.render(&[Argument::display(&1), Argument::display(&2)]).unwrap_err(), TemplateError::ArgumentKind(0));
+    assert_eq!(template.render(&[Argument::custom(&Hex(1)), Argument::custom(&Hex(2))]).unwrap_err(), TemplateError::ArgumentKind(1));
+
+    let template = Template::new("{0 :<oct>}", 1).unwrap();
+    let rendered = template.render(&args[1..]).unwrap();
+    assert!(fmt::write(&mut String::new(), format_args!("{}", rendered)).is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_format_cow() {
+    use std::borrow::Cow;
+
+    let x = 1;
+
+    let borrowed: [Cow<'static, str>; 3] = [cfmt::format_cow!(""), cfmt::format_cow!("string"), cfmt::format_cow!("{{{{x}}}}")];
+    assert!(borrowed.iter().all(|s| matches!(s, Cow::Borrowed(_))));
+    assert_eq!(borrowed, ["", "string", "{{x}}"]);
+
+    let owned: [Cow<'static, str>; 3] = [cfmt::format_cow!("{}", 1), cfmt::format_cow!("{x}"), cfmt::format_cow!("{{{x}}}")];
+    assert!(owned.iter().all(|s| matches!(s, Cow::Owned(_))));
+    assert_eq!(owned, ["1", "1", "{1}"]);
+}
+
+#[cfg(all(feature = "runtime", feature = "alloc"))]
+#[test]
+fn test_format_cow_custom() {
+    let x = format_args!("string");
+    assert_eq!(cfmt::format_cow!("{x :<upper>}"), "STRING");
+    assert_eq!(cfmt::format_cow!(only = "runtime", "string"), "string");
+}
+
+#[cfg(all(feature = "compile-time", feature = "alloc"))]
+#[test]
+fn test_format_rows() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#04x}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "X" => write!(f, "{:#04X}", self.0),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    struct Entry {
+        name: &'static str,
+        value: Hex,
+    }
+
+    let entries = vec![Entry { name: "first", value: Hex(1) }, Entry { name: "second", value: Hex(0xab) }, Entry { name: "third", value: Hex(0xff) }];
+
+    let rows = cfmt::format_rows!(entries.iter().enumerate(), |(index, entry)| "{index}: {0 :x}", entry.value);
+    assert_eq!(rows, ["0: 0x01", "1: 0xab", "2: 0xff"]);
+
+    let mut calls = 0;
+    let rows = cfmt::format_rows!(&entries, |entry| "{0 :x}{0 :<X>}", {
+        calls += 1;
+        Hex(entry.value.0)
+    });
+    assert_eq!(rows, ["0x010x01", "0xab0xAB", "0xff0xFF"]);
+    assert_eq!(calls, entries.len());
+
+    let rows = cfmt::format_rows!(entries, |Entry { name, value }| "{name:<8}|{value :x}|{value :<X>}");
+    assert_eq!(rows, ["first   |0x01|0x01", "second  |0xab|0xAB", "third   |0xff|0xFF"]);
+
+    let rows = cfmt::format_rows!(Vec::<Entry>::new(), |entry| "{0 :x}", entry.value);
+    assert!(rows.is_empty());
+}
+
+#[cfg(all(feature = "compile-time", feature = "alloc"))]
+#[test]
+fn test_custom_formatter_render() {
+    use cfmt::compile_time::{spec, CustomFormat};
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl CustomFormat<{ spec("x") }> for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:#x}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "X" => write!(f, "{:#X}", self.0),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let h = Hex(0xab);
+
+    let compile_time_formatter = cfmt::custom_formatter!("x", &h);
+    assert_eq!(compile_time_formatter.render(), "0xab");
+    assert_eq!(compile_time_formatter.render(), compile_time_formatter.to_string());
+
+    let runtime_formatter = cfmt::runtime::CustomFormatter::new("X", &h);
+    assert_eq!(runtime_formatter.render(), "0xAB");
+    assert_eq!(runtime_formatter.render(), runtime_formatter.to_string());
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_custom_formatter_arguments() {
+    let name = "Straße";
+    assert_eq!(cfmt::format!("{0 :<>}", format_args!("Hello {}!", name)), "Hello Straße!");
+    assert_eq!(cfmt::format!("{0 :<upper>}", format_args!("Hello {}!", name)), "HELLO STRASSE!");
+    assert_eq!(cfmt::format!("{0 :<lower>}, {1}", format_args!("Hello {:X}", 0xAB), 1), "hello ab, 1");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_custom_formatter_runtime_empty_is_valid() {
+    use core::fmt;
+
+    struct NonEmpty;
+
+    impl cfmt::runtime::CustomFormat for NonEmpty {
+        fn empty_is_valid(&self) -> bool {
+            false
+        }
+
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            f.write_str(spec)
+        }
+    }
+
+    assert_eq!(cfmt::format!("{0 :<x>}", NonEmpty), "x");
+
+    let value: &dyn cfmt::runtime::CustomFormat = &NonEmpty;
+    assert!(!value.empty_is_valid());
+    assert_eq!(cfmt::format!("{value :<x>}"), "x");
+
+    let result = std::panic::catch_unwind(|| cfmt::format!("{0 :< >}", NonEmpty));
+
+    if cfg!(debug_assertions) {
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with("empty format specifier for type `"));
+        assert!(message.ends_with("::NonEmpty`"));
+    } else {
+        assert_eq!(result.unwrap(), " ");
+    }
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+fn test_custom_formatter_runtime_raw_spec() {
+    use cfmt::runtime::normalize_spec;
+    use core::fmt;
+
+    struct Bytes;
+
+    impl cfmt::runtime::CustomFormat for Bytes {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match normalize_spec(spec) {
+                "\u{e9}" => f.write_str("composed"),
+                "e\u{301}" => f.write_str("decomposed"),
+                spec => write!(f, "{:x?}", spec.as_bytes()),
+            }
+        }
+    }
+
+    assert_eq!(cfmt::format!("{0 :<\u{e9}>}, {0 :<e\u{301}>}", Bytes), "composed, decomposed");
+    assert_eq!(cfmt::format!("{0 :< e\u{301}\t>}", Bytes), "decomposed");
+    assert_eq!(cfmt::format!("{0 :<A\u{30a}>}", Bytes), "[41, cc, 8a]");
+}
+
+#[cfg(feature = "runtime")]
+#[test]
+#[should_panic(expected = "a formatting trait implementation returned an error")]
+fn test_custom_formatter_runtime_panic() {
+    use core::fmt;
+
+    struct Hex(u8);
+
+    impl cfmt::runtime::CustomFormat for Hex {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "x" => write!(f, "{:#02x}", self.0),
+                "X" => write!(f, "{:#02X}", self.0),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    cfmt::format!("{ :<>}", Hex(0xAB));
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime", feature = "std"))]
+#[test]
+fn test_only_option_errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/only/*.rs");
+}
+
+#[cfg(all(feature = "compile-time", feature = "std", not(feature = "nightly-spec-str")))]
+#[test]
+fn test_missing_impl_errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/missing-impl/*.rs");
+}
+
+#[cfg(all(feature = "compile-time", not(feature = "runtime")))]
+#[test]
+fn test_missing_runtime_feature() {
+    trybuild::TestCases::new().compile_fail("tests/ui/missing-runtime/*.rs");
+}
+
+#[cfg(all(feature = "runtime", not(feature = "compile-time")))]
+#[test]
+fn test_missing_compile_time_feature() {
+    trybuild::TestCases::new().compile_fail("tests/ui/missing-compile-time/*.rs");
+}
+
+#[cfg(all(feature = "runtime", feature = "log"))]
+#[test]
+fn test_log() {
+    use std::sync::Mutex;
+
+    struct Logger(Mutex<Vec<(log::Level, String, String)>>);
+
+    impl log::Log for Logger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            self.0.lock().unwrap().push((record.level(), record.target().to_owned(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Logger = Logger(Mutex::new(Vec::new()));
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Debug);
+
+    let x = format_args!("value");
+    let evaluated = std::cell::Cell::new(false);
+
+    cfmt::log::error!("{x :<upper>}");
+    cfmt::log::warn!(target: "custom", "{0 :<upper>} {1}", x, 1);
+    cfmt::log::info!("{}", 2);
+    cfmt::log::debug!(target: "custom", "{x :<lower>}",);
+    cfmt::log::trace!("{:?}", evaluated.set(true));
+
+    let target = module_path!().to_owned();
+    let records = std::mem::take(&mut *LOGGER.0.lock().unwrap());
+
+    assert_eq!(
+        records,
+        [
+            (log::Level::Error, target.clone(), "VALUE".to_owned()),
+            (log::Level::Warn, "custom".to_owned(), "VALUE 1".to_owned()),
+            (log::Level::Info, target, "2".to_owned()),
+            (log::Level::Debug, "custom".to_owned(), "value".to_owned()),
+        ]
+    );
+
+    // Arguments are not evaluated when the level is disabled
+    assert!(!evaluated.get());
+}
+
+#[cfg(all(feature = "runtime", feature = "tracing"))]
+#[test]
+fn test_tracing() {
+    use std::fmt::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    struct Visitor<'a>(&'a mut String);
+
+    impl Visit for Visitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let _ = write!(self.0, "{}={:?};", field.name(), value);
+        }
+    }
+
+    struct Recorder(Arc<Mutex<Vec<(Level, String, String)>>>);
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            *metadata.level() <= Level::DEBUG
+        }
+
+        fn new_span(&self, _: &Attributes) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event) {
+            let mut fields = String::new();
+            event.record(&mut Visitor(&mut fields));
+            self.0.lock().unwrap().push((*event.metadata().level(), event.metadata().target().to_owned(), fields));
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    let records = Arc::new(Mutex::new(Vec::new()));
+
+    tracing::subscriber::with_default(Recorder(records.clone()), || {
+        let id = 42;
+        let x = format_args!("value");
+        let evaluated = std::cell::Cell::new(false);
+
+        cfmt::tracing::info!(%id, "started at {x :<upper>}");
+        cfmt::tracing::error!(target: "custom", count = 5, name = "a", "{0 :<upper>} {1}", x, 1);
+        cfmt::tracing::warn!("{}", 2,);
+        cfmt::tracing::debug!(?id, "{x :<lower>}");
+        cfmt::tracing::event!(Level::INFO, id);
+        cfmt::tracing::event!(target: "custom", Level::ERROR, "{x :<upper>}");
+        cfmt::tracing::trace!("{:?}", evaluated.set(true));
+
+        // Arguments are not evaluated when the level is disabled
+        assert!(!evaluated.get());
+    });
+
+    let target = module_path!().to_owned();
+
+    assert_eq!(
+        *records.lock().unwrap(),
+        [
+            (Level::INFO, target.clone(), "message=started at VALUE;id=42;".to_owned()),
+            (Level::ERROR, "custom".to_owned(), "message=VALUE 1;count=5;name=\"a\";".to_owned()),
+            (Level::WARN, target.clone(), "message=2;".to_owned()),
+            (Level::DEBUG, target.clone(), "message=value;id=42;".to_owned()),
+            (Level::INFO, target, "id=42;".to_owned()),
+            (Level::ERROR, "custom".to_owned(), "message=VALUE;".to_owned()),
+        ]
+    );
+}
+
+#[cfg(feature = "compile-time")]
+#[test]
+fn test_style() {
+    use cfmt::style::Styled;
+
+    assert_eq!(cfmt::format!("{0 :red}", Styled("a")), "\x1b[31ma\x1b[0m");
+    assert_eq!(cfmt::format!("{0 :bright_cyan,bold}", Styled(1)), "\x1b[96;1m1\x1b[0m");
+    assert_eq!(cfmt::format!("{0 :bg_blue}", Styled(1)), "\x1b[44m1\x1b[0m");
+    assert_eq!(cfmt::format!("{0 :bold,dim,strike}", Styled("a")), "\x1b[1;2;9ma\x1b[0m");
+    assert_eq!(cfmt::format!("{0:<3 :underline}|", Styled('x')), "\x1b[4mx  \x1b[0m|");
+
+    let msg = "styled";
+    cfmt::cprint!("{msg :green,bold} ");
+    cfmt::cprintln!("{msg:?} {0 :italic} {0}", 1);
+    cfmt::cprintln!();
+}
+
+#[cfg(all(feature = "compile-time", feature = "runtime", feature = "alloc"))]
+#[test]
+fn test_assert_custom_format_eq() {
+    use core::fmt;
+
+    struct Year(u16);
+
+    impl cfmt::compile_time::CustomFormat<{ cfmt::compile_time::spec("%Y") }> for Year {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl cfmt::runtime::CustomFormat for Year {
+        fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+            match spec {
+                "%y" => write!(f, "{:02}", self.0 % 100),
+                _ => Err(fmt::Error),
+            }
+        }
+    }
+
+    let year = Year(1836);
+    cfmt::assert_custom_format_eq!(year, "%Y", "1836");
+    cfmt::assert_custom_format_eq!(year, <"%y">, String::from("36"),);
+    cfmt::assert_custom_format_eq!(Year(2005), <"%y">, "05");
+}
+
+#[cfg(all(feature = "runtime", feature = "alloc"))]
+#[test]
+#[should_panic(expected = "assertion `format(value, spec) == expected` failed\n  spec: \"upper\" (runtime)\n  type: core::fmt::Arguments")]
+fn test_assert_custom_format_eq_failed() {
+    cfmt::assert_custom_format_eq!(format_args!("abc"), <"upper">, "abc");
+}
+
+#[cfg(all(feature = "runtime", feature = "no-std-print"))]
+#[test]
+fn test_global_writer() {
+    use cfmt::io::GlobalWriter;
+
+    use core::fmt;
+    use std::sync::Mutex;
+
+    struct Buffer(Mutex<String>);
+
+    impl GlobalWriter for Buffer {
+        fn write_str(&self, s: &str) -> fmt::Result {
+            self.0.lock().unwrap().push_str(s);
+            Ok(())
+        }
+    }
+
+    static BUFFER: Buffer = Buffer(Mutex::new(String::new()));
+
+    cfmt::io::set_global_writer(&BUFFER).unwrap();
+    assert!(cfmt::io::set_global_writer(&BUFFER).is_err());
+    assert!(cfmt::io::global_writer().is_some());
+
+    let x = format_args!("string");
+    cfmt::print!("{x :<upper>} ");
+    cfmt::println!("{}", 1);
+    cfmt::println!();
+    cfmt::println!(only = "runtime", "{0 :<lower>}", format_args!("STRING"));
+
+    assert_eq!(*BUFFER.0.lock().unwrap(), "STRING 1\n\nstring\n");
+}
+
+#[cfg(all(feature = "runtime", feature = "wasm"))]
+#[test]
+fn test_console_log() {
+    // The web console is only available on WebAssembly targets, so the macros are only type-checked
+    let _log = || {
+        let x = format_args!("string");
+        cfmt::console_log!();
+        cfmt::console_log!("{x :<upper>} {}", 1);
+        cfmt::console_error!("{{}}");
+        cfmt::console_error!(only = "runtime", "{0 :<lower>}", format_args!("STRING"));
+    };
+}
+
+#[cfg(feature = "defmt")]
+#[test]
+fn test_defmt_render() {
+    assert_eq!(cfmt::defmt::render(format_args!("{}", 1)).as_str(), "1");
+
+    let long = "x".repeat(cfmt::defmt::MESSAGE_CAPACITY);
+    assert_eq!(cfmt::defmt::render(format_args!("{}{}", long, 1)).as_str(), long);
+}
+
+#[cfg(all(feature = "runtime", feature = "defmt"))]
+#[test]
+fn test_defmt() {
+    // A global logger is required for transmitting messages, so the macros are only type-checked
+    let _log = || {
+        let x = format_args!("string");
+        cfmt::defmt::println!("{x :<upper>} {}", 1);
+        cfmt::defmt::error!("{{}}");
+        cfmt::defmt::warn!("{0 :<lower>}", format_args!("STRING"));
+        cfmt::defmt::info!("{}", 1);
+        cfmt::defmt::debug!("{x :<upper>}");
+        cfmt::defmt::trace!("{x :<upper>}");
+        defmt::info!("{}", cfmt::runtime::CustomFormatter::new("upper", &x));
+    };
 }
@@ -0,0 +1,11 @@
+use custom_format as cfmt;
+
+#[derive(cfmt::compile_time::CustomFormat)]
+struct Date {
+    #[custom_format(rename = "%Y")]
+    year: i32,
+    #[custom_format(rename = "%Y")]
+    month: u8,
+}
+
+fn main() {}
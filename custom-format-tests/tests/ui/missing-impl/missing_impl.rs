@@ -0,0 +1,16 @@
+use custom_format as cfmt;
+use custom_format::compile_time::{spec, CustomFormat};
+
+use core::fmt;
+
+struct Hex(u8);
+
+impl CustomFormat<{ spec("x") }> for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+fn main() {
+    let _ = cfmt::format!("{0 :x}, {0 :%q}", Hex(0xAB));
+}
@@ -0,0 +1,16 @@
+use custom_format as cfmt;
+use custom_format::compile_time::{spec, CustomFormatPrefix};
+
+use core::fmt;
+
+struct Nanoseconds(u32);
+
+impl CustomFormatPrefix<{ spec("%N") }> for Nanoseconds {
+    fn fmt(&self, f: &mut fmt::Formatter, rest: &str) -> fmt::Result {
+        write!(f, "{}{}", self.0, rest)
+    }
+}
+
+fn main() {
+    let _ = cfmt::format!("{0 :%N(3)}, {0 :%Q(3)}", Nanoseconds(0));
+}
@@ -0,0 +1,5 @@
+use custom_format as cfmt;
+
+fn main() {
+    cfmt::println!("{0 :<x>}", 0);
+}
@@ -0,0 +1,6 @@
+use custom_format as cfmt;
+
+fn main() {
+    let mut s = String::new();
+    cfmt::write!(s, only = "runtime", "{0 :x}", 0).unwrap();
+}
@@ -0,0 +1,5 @@
+use custom_format as cfmt;
+
+fn main() {
+    let _ = cfmt::format!(only = "std", "{}", 0);
+}
@@ -15,45 +15,35 @@ fn main() {
             nanoseconds: u32,
         }
 
-        macro_rules! impl_custom_format_for_datetime {
-            (match spec { $($spec:literal => $func:expr $(,)?)* }) => {
-                use cfmt::compile_time::{spec, CustomFormat};
-                $(
-                    impl CustomFormat<{ spec($spec) }> for DateTime {
-                        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                            ($func as fn(&Self, &mut fmt::Formatter) -> fmt::Result)(self, f)
-                        }
-                    }
-                )*
-            };
-        }
-
         // Static format specifiers, checked at compile-time
-        impl_custom_format_for_datetime!(match spec {
-            // Year with pad for at least 4 digits
-            "%Y" => |this, f| write!(f, "{:04}", this.year),
-            // Year % 100 (00..99)
-            "%y" => |this, f| write!(f, "{:02}", (this.year % 100).abs()),
-            // Month of the year, zero-padded (01..12)
-            "%m" => |this, f| write!(f, "{:02}", this.month),
-            // Day of the month, zero-padded (01..31)
-            "%d" => |this, f| write!(f, "{:02}", this.month_day),
-            // Hour of the day, 24-hour clock, zero-padded (00..23)
-            "%H" => |this, f| write!(f, "{:02}", this.hour),
-            // Minute of the hour (00..59)
-            "%M" => |this, f| write!(f, "{:02}", this.minute),
-            // Second of the minute (00..60)
-            "%S" => |this, f| write!(f, "{:02}", this.second),
-            // Date (%m/%d/%y)
-            "%D" => {
-                |this, f| {
-                    let month = cfmt::custom_formatter!("%m", this);
-                    let day = cfmt::custom_formatter!("%d", this);
-                    let year = cfmt::custom_formatter!("%y", this);
-                    write!(f, "{}/{}/{}", month, day, year)
+        cfmt::impl_custom_format!(
+            DateTime,
+            match spec {
+                // Year with pad for at least 4 digits
+                "%Y" => |this, f| write!(f, "{:04}", this.year),
+                // Year % 100 (00..99)
+                "%y" => |this, f| write!(f, "{:02}", (this.year % 100).abs()),
+                // Month of the year, zero-padded (01..12)
+                "%m" => |this, f| write!(f, "{:02}", this.month),
+                // Day of the month, zero-padded (01..31)
+                "%d" => |this, f| write!(f, "{:02}", this.month_day),
+                // Hour of the day, 24-hour clock, zero-padded (00..23)
+                "%H" => |this, f| write!(f, "{:02}", this.hour),
+                // Minute of the hour (00..59)
+                "%M" => |this, f| write!(f, "{:02}", this.minute),
+                // Second of the minute (00..60)
+                "%S" => |this, f| write!(f, "{:02}", this.second),
+                // Date (%m/%d/%y)
+                "%D" => {
+                    |this, f| {
+                        let month = cfmt::custom_formatter!("%m", this);
+                        let day = cfmt::custom_formatter!("%d", this);
+                        let year = cfmt::custom_formatter!("%y", this);
+                        write!(f, "{}/{}/{}", month, day, year)
+                    }
                 }
             }
-        });
+        );
 
         // Dynamic format specifiers, checked at runtime
         impl cfmt::runtime::CustomFormat for DateTime {
@@ -0,0 +1,243 @@
+//! Provides fixed-size buffers and counting writers for formatting without allocation.
+
+use core::fmt;
+
+/// Writer formatting into a byte slice, like a stack buffer, without allocation.
+///
+/// Writing a string which doesn't fit in the remaining space fails, without writing any part of the string,
+/// so that the written bytes are always valid UTF-8.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format::buffer::SliceWriter;
+///
+/// use core::fmt::Write;
+///
+/// let mut buf = [0u8; 8];
+/// let mut writer = SliceWriter::new(&mut buf);
+///
+/// write!(writer, "{}-{}", 12, 34).unwrap();
+/// assert_eq!(writer.as_str(), "12-34");
+///
+/// assert!(write!(writer, "{}", 5678).is_err());
+/// assert_eq!(writer.into_str(), "12-34");
+/// ```
+#[derive(Debug)]
+pub struct SliceWriter<'a> {
+    /// Inner buffer
+    buf: &'a mut [u8],
+    /// Number of bytes written
+    len: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Construct a new [`SliceWriter`] value
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Returns the number of bytes written
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no bytes have been written
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the written string
+    pub fn as_str(&self) -> &str {
+        // Only whole strings are written, so the written bytes are always valid UTF-8
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+
+    /// Returns the written string, borrowing the inner buffer
+    pub fn into_str(self) -> &'a str {
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+
+        match self.buf.get_mut(self.len..end) {
+            Some(dst) => {
+                dst.copy_from_slice(s.as_bytes());
+                self.len = end;
+                Ok(())
+            }
+            None => Err(fmt::Error),
+        }
+    }
+}
+
+/// String with a fixed capacity of `N` bytes, stored inline without allocation.
+///
+/// Writing a string which doesn't fit in the remaining capacity fails, without writing any part of the string.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format::buffer::FixedString;
+///
+/// use core::fmt::Write;
+///
+/// let mut s = FixedString::<8>::new();
+///
+/// write!(s, "{}-{}", 12, 34).unwrap();
+/// assert_eq!(s, "12-34");
+/// assert_eq!(s.len(), 5);
+///
+/// assert!(write!(s, "{}", 5678).is_err());
+/// assert_eq!(s.as_str(), "12-34");
+/// ```
+#[derive(Clone, Copy)]
+pub struct FixedString<const N: usize> {
+    /// Inner buffer
+    buf: [u8; N],
+    /// Number of bytes written
+    len: usize,
+}
+
+impl<const N: usize> FixedString<N> {
+    /// Construct a new empty [`FixedString`] value
+    pub fn new() -> Self {
+        Self { buf: [0; N], len: 0 }
+    }
+
+    /// Returns the capacity in bytes
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the length in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the string is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove the content of the string
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the content of the string
+    pub fn as_str(&self) -> &str {
+        // Only whole strings are written, so the written bytes are always valid UTF-8
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+    }
+}
+
+impl<const N: usize> Default for FixedString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::ops::Deref for FixedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Write for FixedString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        fmt::Write::write_str(&mut SliceWriter { buf: &mut self.buf, len: self.len }, s)?;
+        self.len += s.len();
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> PartialEq for FixedString<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for FixedString<N> {}
+
+impl<const N: usize> PartialEq<str> for FixedString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for FixedString<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Writer counting the number of bytes and chars written, without storing them.
+///
+/// It can be used for measuring the output length before padding it, without allocation.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format::buffer::CountWrite;
+///
+/// let count = CountWrite::measure(format_args!("{}-{}", "né", 42));
+/// assert_eq!((count.bytes(), count.chars()), (6, 5));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CountWrite {
+    /// Number of bytes written
+    bytes: usize,
+    /// Number of chars written
+    chars: usize,
+}
+
+impl CountWrite {
+    /// Construct a new [`CountWrite`] value
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count the bytes and chars of the formatted arguments
+    pub fn measure(args: fmt::Arguments) -> Self {
+        let mut count = Self::new();
+        // Writing to a `CountWrite` never fails, but a formatting trait implementation may return an error
+        let _ = fmt::Write::write_fmt(&mut count, args);
+        count
+    }
+
+    /// Returns the number of bytes written
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the number of chars written
+    pub fn chars(&self) -> usize {
+        self.chars
+    }
+}
+
+impl fmt::Write for CountWrite {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.bytes += s.len();
+        self.chars += s.chars().count();
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+use core::time::Duration;
+
+/// Number of seconds elapsed since an event, formatted as a fuzzy relative time.
+///
+/// Negative values represent events in the future.
+///
+/// Supported format specifiers:
+///
+/// - `""`: long form, like `"just now"`, `"3 minutes ago"`, `"yesterday"` or `"in 2 hours"`.
+/// - `"short"`: short form, like `"now"`, `"3m"`, `"1d"` or `"in 2h"`.
+///
+/// The elapsed time is truncated to the largest unit among minutes, hours, days, months (30 days) and years (365 days).
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Ago;
+///
+/// assert_eq!(cfmt::format!("{0 :<>}, {0 :<short>}", Ago(59)), "just now, now");
+/// assert_eq!(cfmt::format!("{0 :<>}, {0 :<short>}", Ago(185)), "3 minutes ago, 3m");
+/// assert_eq!(cfmt::format!("{0 :<>}, {0 :<short>}", Ago(-7200)), "in 2 hours, in 2h");
+/// assert_eq!(cfmt::format!("{0 :<>}", Ago(100_000)), "yesterday");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ago(pub i64);
+
+impl Ago {
+    /// Construct a new [`Ago`] value from the time elapsed since an event, saturating at [`i64::MAX`] seconds
+    pub fn from_duration(elapsed: Duration) -> Self {
+        Self(if elapsed.as_secs() > i64::MAX as u64 { i64::MAX } else { elapsed.as_secs() as i64 })
+    }
+}
+
+/// Units with their number of seconds, their long name and their short name, in decreasing order
+const UNITS: [(u64, &str, &str); 5] =
+    [(365 * 86_400, "year", "y"), (30 * 86_400, "month", "mo"), (86_400, "day", "d"), (3_600, "hour", "h"), (60, "minute", "m")];
+
+impl CustomFormat for Ago {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let short = match spec {
+            "" => false,
+            "short" => true,
+            _ => return Err(fmt::Error),
+        };
+
+        let future = self.0 < 0;
+        let seconds = self.0.unsigned_abs();
+
+        let (count, long_name, short_name) = match UNITS.iter().find(|&&(unit, _, _)| seconds >= unit) {
+            Some(&(unit, long_name, short_name)) => (seconds / unit, long_name, short_name),
+            None => return f.write_str(if short { "now" } else { "just now" }),
+        };
+
+        match (short, future) {
+            (true, false) => write!(f, "{}{}", count, short_name),
+            (true, true) => write!(f, "in {}{}", count, short_name),
+            (false, false) if count == 1 && long_name == "day" => f.write_str("yesterday"),
+            (false, true) if count == 1 && long_name == "day" => f.write_str("tomorrow"),
+            (false, false) => write!(f, "{} {}{} ago", count, long_name, if count == 1 { "" } else { "s" }),
+            (false, true) => write!(f, "in {} {}{}", count, long_name, if count == 1 { "" } else { "s" }),
+        }
+    }
+}
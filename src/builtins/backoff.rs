@@ -0,0 +1,76 @@
+use super::options;
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+use core::time::Duration;
+
+/// Retry schedule, from a base delay and a number of attempts.
+///
+/// Each delay is rendered like the [`Debug`](fmt::Debug) implementation of [`Duration`], and delays are separated by `, `.
+///
+/// The format specifier is a `,`-separated list of options:
+///
+/// - `exp`: exponential backoff, doubling the delay at each attempt (*default*).
+/// - `lin`: linear backoff, adding the base delay at each attempt.
+/// - `cap=D`: maximum delay, where `D` is an integer followed by a `ms` or `s` unit, like `cap=2s`.
+///
+/// Delays which overflow a [`Duration`] are saturated.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Backoff;
+///
+/// use core::time::Duration;
+///
+/// let backoff = Backoff(Duration::from_millis(100), 5);
+/// assert_eq!(cfmt::format!("{0 :<exp>}", backoff), "100ms, 200ms, 400ms, 800ms, 1.6s");
+/// assert_eq!(cfmt::format!("{0 :<lin>}", backoff), "100ms, 200ms, 300ms, 400ms, 500ms");
+/// assert_eq!(cfmt::format!("{0 :<exp,cap=500ms>}", backoff), "100ms, 200ms, 400ms, 500ms, 500ms");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Backoff(pub Duration, pub u32);
+
+/// Parse a duration with a `ms` or `s` unit
+fn parse_duration(value: &str) -> Result<Duration, fmt::Error> {
+    if let Some(millis) = value.strip_suffix("ms") {
+        millis.parse().map(Duration::from_millis).map_err(|_| fmt::Error)
+    } else if let Some(secs) = value.strip_suffix('s') {
+        secs.parse().map(Duration::from_secs).map_err(|_| fmt::Error)
+    } else {
+        Err(fmt::Error)
+    }
+}
+
+impl CustomFormat for Backoff {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut linear = false;
+        let mut cap = None;
+
+        for option in options(spec) {
+            match option {
+                ("exp", None) => linear = false,
+                ("lin", None) => linear = true,
+                ("cap", Some(value)) => cap = Some(parse_duration(value)?),
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        for attempt in 0..self.1 {
+            let factor = if linear { attempt.saturating_add(1) } else { 1u32.checked_shl(attempt).unwrap_or(u32::MAX) };
+
+            let mut delay = self.0.saturating_mul(factor);
+            if let Some(cap) = cap {
+                delay = delay.min(cap);
+            }
+
+            if attempt > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{:?}", delay)?;
+        }
+
+        Ok(())
+    }
+}
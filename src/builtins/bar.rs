@@ -0,0 +1,70 @@
+use super::{options, single_char};
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Progress bar, for a ratio between `0.0` and `1.0`. Out of range values are clamped.
+///
+/// The format specifier is a `,`-separated list of options:
+///
+/// - `width=N`: number of glyphs inside the bar (*default: 20*).
+/// - `fill=C`: glyph for the completed part (*default: `#`*).
+/// - `empty=C`: glyph for the remaining part (*default: `-`*).
+/// - `pct`: append the rounded percentage after the bar.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Bar;
+///
+/// assert_eq!(cfmt::format!("{0 :<width=20>}", Bar(0.25)), "[#####---------------]");
+/// assert_eq!(cfmt::format!("{0 :<width=4,fill=█,empty=░,pct>}", Bar(0.5)), "[██░░] 50%");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bar(pub f64);
+
+impl CustomFormat for Bar {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut width = 20;
+        let mut fill = '#';
+        let mut empty = '-';
+        let mut pct = false;
+
+        for option in options(spec) {
+            match option {
+                ("width", Some(value)) => width = value.parse().map_err(|_| fmt::Error)?,
+                ("fill", value) => fill = single_char(value)?,
+                ("empty", value) => empty = single_char(value)?,
+                ("pct", None) => pct = true,
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        // `NaN` values are clamped to zero
+        let ratio = if self.0 >= 1.0 {
+            1.0
+        } else if self.0 > 0.0 {
+            self.0
+        } else {
+            0.0
+        };
+
+        let filled = (ratio * width as f64 + 0.5) as usize;
+
+        f.write_str("[")?;
+        for _ in 0..filled {
+            fmt::Write::write_char(f, fill)?;
+        }
+        for _ in filled..width {
+            fmt::Write::write_char(f, empty)?;
+        }
+        f.write_str("]")?;
+
+        if pct {
+            write!(f, " {}%", (ratio * 100.0 + 0.5) as u32)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Integer displayed in several bases at once.
+///
+/// The format specifier is a `,`-separated list of bases among `dec`, `hex`, `oct` and `bin`,
+/// written in the provided order. The `all` specifier is equivalent to `dec,hex,oct,bin`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Bases;
+///
+/// assert_eq!(cfmt::format!("{0 :<all>}", Bases(255u8)), "dec=255 hex=0xff oct=0o377 bin=0b11111111");
+/// assert_eq!(cfmt::format!("{0 :<bin,dec>}", Bases(5)), "bin=0b101 dec=5");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bases<T>(pub T);
+
+impl<T: fmt::Display + fmt::LowerHex + fmt::Octal + fmt::Binary> CustomFormat for Bases<T> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let spec = match spec {
+            "all" => "dec,hex,oct,bin",
+            _ => spec,
+        };
+
+        for (index, base) in spec.split(',').enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+
+            match base {
+                "dec" => write!(f, "dec={}", self.0)?,
+                "hex" => write!(f, "hex={:#x}", self.0)?,
+                "oct" => write!(f, "oct={:#o}", self.0)?,
+                "bin" => write!(f, "bin={:#b}", self.0)?,
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        Ok(())
+    }
+}
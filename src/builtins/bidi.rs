@@ -0,0 +1,41 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt::{self, Write};
+
+/// Text wrapped in Unicode bidirectional isolates, so that its direction doesn't affect the layout of the surrounding text.
+///
+/// The following format specifiers are supported:
+///
+/// - `"ltr"`: isolate the text as left-to-right, with `U+2066 LEFT-TO-RIGHT ISOLATE`.
+/// - `"rtl"`: isolate the text as right-to-left, with `U+2067 RIGHT-TO-LEFT ISOLATE`.
+/// - `"auto"`: isolate the text with the direction of its first strong char, with `U+2068 FIRST STRONG ISOLATE`.
+///
+/// The isolate is always closed with `U+2069 POP DIRECTIONAL ISOLATE`.
+/// The text is padded inside the isolate with the flags of the formatter.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Bidi;
+///
+/// assert_eq!(cfmt::format!("user {0 :<rtl>} logged in", Bidi("שלום")), "user \u{2067}שלום\u{2069} logged in");
+/// assert_eq!(cfmt::format!("[{0:<4 :<auto>}]", Bidi("ab")), "[\u{2068}ab  \u{2069}]");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Bidi<'a>(pub &'a str);
+
+impl CustomFormat for Bidi<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let isolate = match spec {
+            "ltr" => '\u{2066}',
+            "rtl" => '\u{2067}',
+            "auto" => '\u{2068}',
+            _ => return Err(fmt::Error),
+        };
+
+        f.write_char(isolate)?;
+        f.pad(self.0)?;
+        f.write_char('\u{2069}')
+    }
+}
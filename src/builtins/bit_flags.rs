@@ -0,0 +1,70 @@
+use super::options;
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Set of bit flags, rendered with the names of the known flags and the residual unknown bits, like `"A | B | 0x10"`.
+///
+/// The table associates a name to a mask, and a name is written if all the bits of its nonzero mask are set.
+/// The remaining bits not covered by any written name are written in hexadecimal, so that no bit is silently dropped.
+/// A zero value is written as `0x0`.
+///
+/// The format specifier is a `,`-separated list of options:
+///
+/// - `sep=S`: separator between the flags (*default: ` | `*). It cannot contain a `,` char.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::BitFlags;
+///
+/// const TABLE: &[(&str, u64)] = &[("SYN", 0x02), ("ACK", 0x10), ("FIN", 0x01)];
+///
+/// assert_eq!(cfmt::format!("{0 :<>}", BitFlags(0x12, TABLE)), "SYN | ACK");
+/// assert_eq!(cfmt::format!("{0 :<>}", BitFlags(0x43, TABLE)), "SYN | FIN | 0x40");
+/// assert_eq!(cfmt::format!("{0 :<sep=+>}", BitFlags(0x11, TABLE)), "ACK+FIN");
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitFlags<'a>(pub u64, pub &'a [(&'a str, u64)]);
+
+impl CustomFormat for BitFlags<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut separator = " | ";
+
+        for option in options(spec) {
+            match option {
+                ("sep", Some(value)) => separator = value,
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        if self.0 == 0 {
+            return f.write_str("0x0");
+        }
+
+        let mut residual = self.0;
+        let mut first = true;
+
+        for &(name, mask) in self.1 {
+            if mask != 0 && self.0 & mask == mask {
+                if !first {
+                    f.write_str(separator)?;
+                }
+                f.write_str(name)?;
+
+                residual &= !mask;
+                first = false;
+            }
+        }
+
+        if residual != 0 {
+            if !first {
+                f.write_str(separator)?;
+            }
+            write!(f, "{:#x}", residual)?;
+        }
+
+        Ok(())
+    }
+}
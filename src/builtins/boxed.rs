@@ -0,0 +1,156 @@
+use super::options;
+use crate::runtime::CustomFormat;
+
+use core::fmt::{self, Write};
+
+/// Value drawn inside a box with an optional title, for CLI summaries.
+///
+/// The content is the [`Display`](fmt::Display) output of the value, which can span multiple lines.
+/// The width of the formatter is the minimum inner width of the box, and widths are measured in chars.
+/// The value is formatted twice: once to measure its lines, and once to write them.
+///
+/// The format specifier is a `,`-separated list of options:
+///
+/// - `title=T`: title written in the top border. It cannot contain a `,` char.
+/// - `ascii`: draw the box with ASCII chars only.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Boxed;
+///
+/// let summary = "passed: 12\nfailed: 0";
+///
+/// assert_eq!(
+///     cfmt::format!("{0 :<title=Tests>}", Boxed(&summary)),
+///     "┌─ Tests ────┐\n│ passed: 12 │\n│ failed: 0  │\n└────────────┘"
+/// );
+///
+/// assert_eq!(cfmt::format!("{0:6 :<ascii>}", Boxed(&42)), "+--------+\n| 42     |\n+--------+");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Boxed<'a, D>(pub &'a D);
+
+/// Glyphs used for drawing a box
+struct Glyphs {
+    /// Horizontal border
+    horizontal: char,
+    /// Vertical border
+    vertical: char,
+    /// Corners, in the order top left, top right, bottom left, bottom right
+    corners: [char; 4],
+}
+
+/// Unicode box glyphs
+const UNICODE: Glyphs = Glyphs { horizontal: '─', vertical: '│', corners: ['┌', '┐', '└', '┘'] };
+
+/// ASCII box glyphs
+const ASCII: Glyphs = Glyphs { horizontal: '-', vertical: '|', corners: ['+', '+', '+', '+'] };
+
+/// Writer measuring the maximum line width of the written text, in chars
+#[derive(Default)]
+struct LineWidth {
+    /// Maximum width of the complete lines
+    max: usize,
+    /// Width of the current line
+    current: usize,
+}
+
+impl Write for LineWidth {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '\n' {
+                self.max = self.max.max(self.current);
+                self.current = 0;
+            } else {
+                self.current += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writer drawing the vertical borders around each written line
+struct BoxLines<'a, 'b> {
+    /// Inner formatter
+    f: &'a mut fmt::Formatter<'b>,
+    /// Box glyphs
+    glyphs: &'a Glyphs,
+    /// Inner width of the box
+    width: usize,
+    /// Width of the current line
+    current: usize,
+}
+
+impl BoxLines<'_, '_> {
+    /// Pad the current line and write the right border
+    fn end_line(&mut self) -> fmt::Result {
+        for _ in self.current..self.width {
+            self.f.write_char(' ')?;
+        }
+        self.current = 0;
+        writeln!(self.f, " {}", self.glyphs.vertical)
+    }
+}
+
+impl Write for BoxLines<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '\n' {
+                self.end_line()?;
+                write!(self.f, "{} ", self.glyphs.vertical)?;
+            } else {
+                self.f.write_char(c)?;
+                self.current += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<D: fmt::Display> CustomFormat for Boxed<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut title = "";
+        let mut glyphs = &UNICODE;
+
+        for option in options(spec) {
+            match option {
+                ("title", Some(value)) => title = value,
+                ("ascii", None) => glyphs = &ASCII,
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        let mut line_width = LineWidth::default();
+        write!(line_width, "{}", self.0)?;
+
+        let title_width = title.chars().count();
+        let min_width = if title.is_empty() { 0 } else { title_width + 2 };
+        let width = line_width.max.max(line_width.current).max(min_width).max(f.width().unwrap_or(0));
+
+        let [top_left, top_right, bottom_left, bottom_right] = glyphs.corners;
+        let horizontal = glyphs.horizontal;
+
+        f.write_char(top_left)?;
+        let mut top_border = width + 2;
+        if !title.is_empty() {
+            write!(f, "{} {} ", horizontal, title)?;
+            top_border -= title_width + 3;
+        }
+        for _ in 0..top_border {
+            f.write_char(horizontal)?;
+        }
+        write!(f, "{}\n{} ", top_right, glyphs.vertical)?;
+
+        let mut lines = BoxLines { f, glyphs, width, current: 0 };
+        write!(lines, "{}", self.0)?;
+        lines.end_line()?;
+
+        f.write_char(bottom_left)?;
+        for _ in 0..width + 2 {
+            f.write_char(horizontal)?;
+        }
+        f.write_char(bottom_right)
+    }
+}
@@ -0,0 +1,67 @@
+use super::options;
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Binary data displayed as an array literal, for code generation.
+///
+/// The format specifier is a `,`-separated list of options:
+///
+/// - `rust`: write a Rust array literal like `[0x00, 0x01]`, instead of a C array initializer like `{0x00, 0x01}`.
+/// - `wrap=N`: write `N` elements per line, with each line indented by 4 spaces.
+///
+/// Hexadecimal digits are written in uppercase with the alternate flag (`#`).
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::CArray;
+///
+/// assert_eq!(cfmt::format!("{0 :<>}", CArray(&[0, 1, 0xab])), "{0x00, 0x01, 0xab}");
+/// assert_eq!(cfmt::format!("{0:# :<rust>}", CArray(&[0, 1, 0xab])), "[0x00, 0x01, 0xAB]");
+/// assert_eq!(cfmt::format!("{0 :<wrap=2>}", CArray(&[0, 1, 2])), "{\n    0x00, 0x01,\n    0x02,\n}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CArray<'a>(pub &'a [u8]);
+
+impl CustomFormat for CArray<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut delimiters = ("{", "}");
+        let mut wrap = None;
+
+        for option in options(spec) {
+            match option {
+                ("rust", None) => delimiters = ("[", "]"),
+                ("wrap", Some(value)) => match value.parse() {
+                    Ok(0) | Err(_) => return Err(fmt::Error),
+                    Ok(n) => wrap = Some(n),
+                },
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        let (open, close) = delimiters;
+        f.write_str(open)?;
+
+        for (index, byte) in self.0.iter().enumerate() {
+            match wrap {
+                Some(n) if index % n == 0 => f.write_str(if index == 0 { "\n    " } else { ",\n    " })?,
+                _ if index > 0 => f.write_str(", ")?,
+                _ => (),
+            }
+
+            if f.alternate() {
+                write!(f, "0x{:02X}", byte)?;
+            } else {
+                write!(f, "0x{:02x}", byte)?;
+            }
+        }
+
+        if wrap.is_some() && !self.0.is_empty() {
+            f.write_str(",\n")?;
+        }
+
+        f.write_str(close)
+    }
+}
@@ -0,0 +1,94 @@
+use super::{options, single_char};
+use crate::runtime::CustomFormat;
+
+use core::fmt::{self, Write};
+
+/// Optional value of a CSV or TSV cell.
+///
+/// The format specifier is a `,`-separated list of options:
+///
+/// - `none=TEXT`: sentinel written for a `None` value (*default: empty*).
+/// - `q`: quote the value if it contains the delimiter, a `"` char or a line break, doubling the inner `"` chars.
+/// - `delim=C`: field delimiter checked for quoting (*default: `,`*).
+///
+/// When quoting is enabled, the value is formatted twice: once to check if it needs quoting, and once to write it.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Cell;
+///
+/// assert_eq!(cfmt::format!("{0 :<q>},{1 :<q,none=NA>}", Cell(&Some("a,b")), Cell::<u32>(&None)), r#""a,b",NA"#);
+/// assert_eq!(cfmt::format!("{0 :<q,delim=;>}", Cell(&Some("a,b"))), "a,b");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cell<'a, T>(pub &'a Option<T>);
+
+/// Writer checking if the written text needs quoting
+struct QuoteCheck {
+    /// Field delimiter
+    delimiter: char,
+    /// True if the text needs quoting
+    needs_quoting: bool,
+}
+
+impl Write for QuoteCheck {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.needs_quoting |= s.contains(|c| c == self.delimiter || c == '"' || c == '\n' || c == '\r');
+        Ok(())
+    }
+}
+
+/// Writer doubling the `"` chars of the written text
+struct QuoteEscape<'a, 'b> {
+    /// Inner formatter
+    f: &'a mut fmt::Formatter<'b>,
+}
+
+impl Write for QuoteEscape<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (index, part) in s.split('"').enumerate() {
+            if index > 0 {
+                self.f.write_str("\"\"")?;
+            }
+            self.f.write_str(part)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Display> CustomFormat for Cell<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut none = "";
+        let mut quote = false;
+        let mut delimiter = ',';
+
+        for option in options(spec) {
+            match option {
+                ("none", Some(value)) => none = value,
+                ("q", None) => quote = true,
+                ("delim", value) => delimiter = single_char(value)?,
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        let value = match self.0 {
+            Some(value) => value,
+            None => return f.write_str(none),
+        };
+
+        if quote {
+            let mut check = QuoteCheck { delimiter, needs_quoting: false };
+            write!(check, "{}", value)?;
+
+            if check.needs_quoting {
+                f.write_str("\"")?;
+                write!(QuoteEscape { f }, "{}", value)?;
+                return f.write_str("\"");
+            }
+        }
+
+        write!(f, "{}", value)
+    }
+}
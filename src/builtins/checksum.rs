@@ -0,0 +1,67 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Checksum of a byte slice, rendered as 8 lowercase hexadecimal digits.
+///
+/// | Format specifier | Algorithm                   |
+/// |------------------|-----------------------------|
+/// | `crc32`          | CRC-32 (IEEE 802.3)         |
+/// | `adler32`        | Adler-32                    |
+/// | `fnv`            | 32-bit FNV-1a               |
+///
+/// The checksum is computed at each formatting, so it is intended for debug logging rather than for hot paths.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Checksum;
+///
+/// let data = Checksum(b"123456789");
+/// assert_eq!(cfmt::format!("{data :<crc32>}, {data :<adler32>}, {data :<fnv>}"), "cbf43926, 091e01de, bb86b11c");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Checksum<'a>(pub &'a [u8]);
+
+impl Checksum<'_> {
+    /// Compute the CRC-32 checksum, with the reflected `0x04C11DB7` polynomial
+    fn crc32(&self) -> u32 {
+        let crc =
+            self.0.iter().fold(!0u32, |crc, &byte| (0..8).fold(crc ^ u32::from(byte), |crc, _| if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 }));
+        !crc
+    }
+
+    /// Compute the Adler-32 checksum
+    fn adler32(&self) -> u32 {
+        const MOD_ADLER: u32 = 65521;
+
+        let (a, b) = self.0.iter().fold((1u32, 0u32), |(a, b), &byte| {
+            let a = (a + u32::from(byte)) % MOD_ADLER;
+            (a, (b + a) % MOD_ADLER)
+        });
+        (b << 16) | a
+    }
+
+    /// Compute the 32-bit FNV-1a hash
+    fn fnv(&self) -> u32 {
+        self.0.iter().fold(0x811C_9DC5u32, |hash, &byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193))
+    }
+}
+
+impl CustomFormat for Checksum<'_> {
+    fn empty_is_valid(&self) -> bool {
+        false
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let checksum = match spec {
+            "crc32" => self.crc32(),
+            "adler32" => self.adler32(),
+            "fnv" => self.fnv(),
+            _ => return Err(fmt::Error),
+        };
+
+        write!(f, "{:08x}", checksum)
+    }
+}
@@ -0,0 +1,97 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Date and time split into numeric fields, formatted with a strftime-like pattern.
+///
+/// The format specifier is a pattern where the following directives are replaced, and other chars are written as is:
+///
+/// | Directive | Description                                          |
+/// |-----------|------------------------------------------------------|
+/// | `%Y`      | Year with pad for at least 4 digits                  |
+/// | `%y`      | Year % 100 (00..99)                                  |
+/// | `%m`      | Month of the year, zero-padded (01..12)              |
+/// | `%d`      | Day of the month, zero-padded (01..31)               |
+/// | `%H`      | Hour of the day, 24-hour clock, zero-padded (00..23) |
+/// | `%M`      | Minute of the hour (00..59)                          |
+/// | `%S`      | Second of the minute (00..60)                        |
+/// | `%nN`     | Nanoseconds with `n` digits                          |
+/// | `%D`      | Date (`%m/%d/%y`)                                    |
+/// | `%F`      | ISO 8601 date (`%Y-%m-%d`)                           |
+/// | `%T`      | 24-hour time (`%H:%M:%S`)                            |
+/// | `%%`      | Literal `%` char                                     |
+///
+/// The fields are not validated.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::DateParts;
+///
+/// let dt = DateParts { year: 1836, month: 5, day: 18, hour: 23, minute: 45, second: 54, nanoseconds: 123456789 };
+/// assert_eq!(cfmt::format!("{dt :<%F %T.%6N>}"), "1836-05-18 23:45:54.123456");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DateParts {
+    /// Year
+    pub year: i32,
+    /// Month of the year (1..12)
+    pub month: u8,
+    /// Day of the month (1..31)
+    pub day: u8,
+    /// Hour of the day (0..23)
+    pub hour: u8,
+    /// Minute of the hour (0..59)
+    pub minute: u8,
+    /// Second of the minute (0..60)
+    pub second: u8,
+    /// Nanoseconds of the second (0..999_999_999)
+    pub nanoseconds: u32,
+}
+
+impl DateParts {
+    /// Write the nanoseconds with the specified number of digits
+    fn fmt_nanoseconds(&self, f: &mut fmt::Formatter, digits: usize) -> fmt::Result {
+        match digits {
+            0 => Err(fmt::Error),
+            1..=9 => write!(f, "{:0width$}", self.nanoseconds / 10u32.pow(9 - digits as u32), width = digits),
+            _ => write!(f, "{:09}{:0width$}", self.nanoseconds, 0, width = digits - 9),
+        }
+    }
+}
+
+impl CustomFormat for DateParts {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut remaining = spec;
+
+        while let Some(position) = remaining.find('%') {
+            f.write_str(&remaining[..position])?;
+
+            let directive = &remaining[position + 1..];
+            let digits_len = directive.bytes().take_while(u8::is_ascii_digit).count();
+            let (digits, directive) = directive.split_at(digits_len);
+
+            let mut chars = directive.chars();
+            match (digits, chars.next()) {
+                ("", Some('Y')) => write!(f, "{:04}", self.year)?,
+                ("", Some('y')) => write!(f, "{:02}", (self.year % 100).abs())?,
+                ("", Some('m')) => write!(f, "{:02}", self.month)?,
+                ("", Some('d')) => write!(f, "{:02}", self.day)?,
+                ("", Some('H')) => write!(f, "{:02}", self.hour)?,
+                ("", Some('M')) => write!(f, "{:02}", self.minute)?,
+                ("", Some('S')) => write!(f, "{:02}", self.second)?,
+                ("", Some('D')) => write!(f, "{:02}/{:02}/{:02}", self.month, self.day, (self.year % 100).abs())?,
+                ("", Some('F')) => write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)?,
+                ("", Some('T')) => write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?,
+                ("", Some('%')) => f.write_str("%")?,
+                (digits, Some('N')) if !digits.is_empty() => self.fmt_nanoseconds(f, digits.parse().map_err(|_| fmt::Error)?)?,
+                _ => return Err(fmt::Error),
+            }
+
+            remaining = chars.as_str();
+        }
+
+        f.write_str(remaining)
+    }
+}
@@ -0,0 +1,36 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Pair of values displayed as a difference, which is useful in assertion messages.
+///
+/// The following format specifiers are supported:
+///
+/// - `""`: write `a -> b` if the values are different, or only `a` if they are equal.
+/// - `"unified"`: write `- a` and `+ b` on two lines if the values are different, or only `  a` if they are equal.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Diff;
+///
+/// assert_eq!(cfmt::format!("{0 :<>}", Diff(&1, &2)), "1 -> 2");
+/// assert_eq!(cfmt::format!("{0 :<unified>}", Diff(&"a", &"b")), "- a\n+ b");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Diff<'a, T>(pub &'a T, pub &'a T);
+
+impl<T: PartialEq + fmt::Display> CustomFormat for Diff<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let Self(a, b) = *self;
+
+        match (spec, a == b) {
+            ("", true) => write!(f, "{}", a),
+            ("", false) => write!(f, "{} -> {}", a, b),
+            ("unified", true) => write!(f, "  {}", a),
+            ("unified", false) => write!(f, "- {}\n+ {}", a, b),
+            _ => Err(fmt::Error),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use super::{options, single_char};
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Digit string formatted with a grouping mask, like a phone or a credit card number.
+///
+/// The format specifier is a mask where each `#` is replaced by the next digit of the input,
+/// and any other char is a literal separator written between digits. Non-digit chars of the input are ignored.
+///
+/// The mask can be followed by `,`-separated options, so it cannot contain a `,` char:
+///
+/// - `trunc`: digits exceeding the mask are dropped instead of being appended after it.
+/// - `pad=C`: missing digits are replaced by the char `C` instead of ending the output early.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Digits;
+///
+/// let number = Digits("(555) 123 4567");
+/// assert_eq!(cfmt::format!("{0 :<###-###-####>}", number), "555-123-4567");
+/// assert_eq!(cfmt::format!("{0 :<#### ####>}, {0 :<#### ####,trunc>}", Digits("1234567890")), "1234 567890, 1234 5678");
+/// assert_eq!(cfmt::format!("{0 :<##/##>}, {0 :<##/##,pad=_>}", Digits("123")), "12/3, 12/3_");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Digits<'a>(pub &'a str);
+
+impl CustomFormat for Digits<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let (mask, spec) = spec.split_once(',').unwrap_or((spec, ""));
+
+        let mut truncate = false;
+        let mut pad = None;
+
+        for option in options(spec) {
+            match option {
+                ("trunc", None) => truncate = true,
+                ("pad", value) => pad = Some(single_char(value)?),
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        if !mask.contains('#') {
+            return Err(fmt::Error);
+        }
+
+        let mut digits = self.0.chars().filter(char::is_ascii_digit);
+        let mut literal_start = 0;
+        let mut complete = true;
+
+        for (index, c) in mask.char_indices() {
+            if c != '#' {
+                continue;
+            }
+
+            match digits.next().or(pad) {
+                Some(digit) => {
+                    f.write_str(&mask[literal_start..index])?;
+                    fmt::Write::write_char(f, digit)?;
+                    literal_start = index + 1;
+                }
+                None => {
+                    complete = false;
+                    break;
+                }
+            }
+        }
+
+        if complete {
+            f.write_str(&mask[literal_start..])?;
+        }
+
+        if !truncate {
+            for digit in digits {
+                fmt::Write::write_char(f, digit)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Number of seconds since the Unix epoch (1970-01-01T00:00:00Z), formatted as a UTC date and time.
+///
+/// | Format specifier | Description                                     |
+/// |------------------|-------------------------------------------------|
+/// | `iso`            | ISO 8601 date and time (`%Y-%m-%dT%H:%M:%SZ`)   |
+/// | `date`           | ISO 8601 date (`%Y-%m-%d`)                      |
+/// | `time`           | 24-hour time (`%H:%M:%S`)                       |
+///
+/// Negative values represent instants before the Unix epoch, and dates use the proleptic Gregorian calendar.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Epoch;
+///
+/// let t = Epoch(1_000_000_000);
+/// assert_eq!(cfmt::format!("{t :<iso>}"), "2001-09-09T01:46:40Z");
+/// assert_eq!(cfmt::format!("{t :<date>} at {t :<time>}"), "2001-09-09 at 01:46:40");
+/// assert_eq!(cfmt::format!("{0 :<iso>}", Epoch(-1)), "1969-12-31T23:59:59Z");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Epoch(pub i64);
+
+impl Epoch {
+    /// Number of seconds in a day
+    const SECONDS_PER_DAY: i64 = 86_400;
+
+    /// Write the date part
+    fn fmt_date(days: i64, f: &mut fmt::Formatter) -> fmt::Result {
+        let (year, month, day) = civil_from_days(days);
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// Write the time part
+    fn fmt_time(seconds: i64, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+}
+
+impl CustomFormat for Epoch {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let days = self.0.div_euclid(Self::SECONDS_PER_DAY);
+        let seconds = self.0.rem_euclid(Self::SECONDS_PER_DAY);
+
+        match spec {
+            "iso" => {
+                Self::fmt_date(days, f)?;
+                f.write_str("T")?;
+                Self::fmt_time(seconds, f)?;
+                f.write_str("Z")
+            }
+            "date" => Self::fmt_date(days, f),
+            "time" => Self::fmt_time(seconds, f),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+/// Convert a number of days since the Unix epoch to a `(year, month, day)` date in the proleptic Gregorian calendar.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    // Shift the epoch to 0000-03-01, so that the leap day is the last day of the year
+    let days = days + 719_468;
+
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u8;
+    let year = era * 400 + year_of_era + i64::from(month <= 2);
+
+    (year, month, day)
+}
@@ -0,0 +1,100 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt::{self, Write};
+
+/// Text escaped for interpolation into structured output.
+///
+/// The following format specifiers are supported:
+///
+/// - `"html"`: escape the `&`, `<`, `>`, `"` and `'` chars with HTML entities.
+/// - `"json"`: escape the content of a JSON string, without the surrounding quotes.
+/// - `"shell"`: quote the text for POSIX shells, with single quotes.
+/// - `"md"`: escape the Markdown special chars with backslashes.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Escape;
+///
+/// let text = Escape("<b>Tom & Jerry's</b>");
+/// assert_eq!(cfmt::format!("{0 :<html>}", text), "&lt;b&gt;Tom &amp; Jerry&#39;s&lt;/b&gt;");
+/// assert_eq!(cfmt::format!("{0 :<shell>}", text), r#"'<b>Tom & Jerry'\''s</b>'"#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Escape<'a>(pub &'a str);
+
+/// Escape sequence replacing a char
+enum Replacement {
+    /// Fixed escape sequence
+    Str(&'static str),
+    /// Char prefixed by a backslash
+    Backslash(char),
+    /// JSON escape sequence with the code point of the char, like `\u001b`
+    Unicode(char),
+}
+
+impl Escape<'_> {
+    /// Write the text, replacing the chars for which the provided function returns an escape sequence
+    fn fmt_replaced(&self, f: &mut fmt::Formatter, escape: impl Fn(char) -> Option<Replacement>) -> fmt::Result {
+        let mut start = 0;
+
+        for (index, c) in self.0.char_indices() {
+            if let Some(replacement) = escape(c) {
+                // Unescaped text is written in slices between the replaced chars
+                f.write_str(&self.0[start..index])?;
+                start = index + c.len_utf8();
+
+                match replacement {
+                    Replacement::Str(s) => f.write_str(s)?,
+                    Replacement::Backslash(c) => {
+                        f.write_char('\\')?;
+                        f.write_char(c)?;
+                    }
+                    Replacement::Unicode(c) => write!(f, "\\u{:04x}", c as u32)?,
+                }
+            }
+        }
+
+        f.write_str(&self.0[start..])
+    }
+}
+
+impl CustomFormat for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        match spec {
+            "html" => self.fmt_replaced(f, |c| match c {
+                '&' => Some(Replacement::Str("&amp;")),
+                '<' => Some(Replacement::Str("&lt;")),
+                '>' => Some(Replacement::Str("&gt;")),
+                '"' => Some(Replacement::Str("&quot;")),
+                '\'' => Some(Replacement::Str("&#39;")),
+                _ => None,
+            }),
+            "json" => self.fmt_replaced(f, |c| match c {
+                '"' => Some(Replacement::Str("\\\"")),
+                '\\' => Some(Replacement::Str("\\\\")),
+                '\n' => Some(Replacement::Str("\\n")),
+                '\r' => Some(Replacement::Str("\\r")),
+                '\t' => Some(Replacement::Str("\\t")),
+                '\u{8}' => Some(Replacement::Str("\\b")),
+                '\u{c}' => Some(Replacement::Str("\\f")),
+                '\0'..='\u{1f}' => Some(Replacement::Unicode(c)),
+                _ => None,
+            }),
+            "shell" => {
+                f.write_char('\'')?;
+                self.fmt_replaced(f, |c| match c {
+                    '\'' => Some(Replacement::Str("'\\''")),
+                    _ => None,
+                })?;
+                f.write_char('\'')
+            }
+            "md" => self.fmt_replaced(f, |c| match c {
+                '\\' | '`' | '*' | '_' | '{' | '}' | '[' | ']' | '(' | ')' | '#' | '+' | '-' | '.' | '!' | '|' | '<' | '>' => Some(Replacement::Backslash(c)),
+                _ => None,
+            }),
+            _ => Err(fmt::Error),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Fraction, from a numerator and a denominator.
+///
+/// Supported format specifiers:
+///
+/// - `""`: reduced fraction, like `"3/4"`, or an integer like `"2"` when the denominator is reduced to one.
+/// - `"mixed"`: reduced mixed number, like `"1 1/2"`.
+/// - `"dec"`: decimal approximation, like `"0.75"`. The precision of the formatter is applied to the displayed number.
+///
+/// A negative denominator is normalized by moving the sign to the numerator. A zero denominator makes the formatting fail.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Frac;
+///
+/// assert_eq!(cfmt::format!("{0 :<>}, {0 :<mixed>}, {0 :<dec>}", Frac(6, 4)), "3/2, 1 1/2, 1.5");
+/// assert_eq!(cfmt::format!("{0 :<>}, {0:.3 :<dec>}", Frac(2, -3)), "-2/3, -0.667");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frac(pub i64, pub i64);
+
+/// Compute the greatest common divisor of two numbers
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+impl CustomFormat for Frac {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        if self.1 == 0 {
+            return Err(fmt::Error);
+        }
+
+        // Magnitudes are computed in `u64` so that `i64::MIN` doesn't overflow
+        let negative = self.0 != 0 && ((self.0 < 0) != (self.1 < 0));
+        let divisor = gcd(self.0.unsigned_abs(), self.1.unsigned_abs());
+        let (numerator, denominator) = (self.0.unsigned_abs() / divisor, self.1.unsigned_abs() / divisor);
+        let sign = if negative { "-" } else { "" };
+
+        match spec {
+            "" if denominator == 1 => write!(f, "{}{}", sign, numerator),
+            "" => write!(f, "{}{}/{}", sign, numerator, denominator),
+            "mixed" => match (numerator / denominator, numerator % denominator) {
+                (integer, 0) => write!(f, "{}{}", sign, integer),
+                (0, remainder) => write!(f, "{}{}/{}", sign, remainder, denominator),
+                (integer, remainder) => write!(f, "{}{} {}/{}", sign, integer, remainder, denominator),
+            },
+            "dec" => {
+                let value = self.0 as f64 / self.1 as f64;
+                match f.precision() {
+                    Some(precision) => write!(f, "{:.*}", precision, value),
+                    None => write!(f, "{}", value),
+                }
+            }
+            _ => Err(fmt::Error),
+        }
+    }
+}
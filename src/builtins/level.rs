@@ -0,0 +1,68 @@
+use super::options;
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Log level name, like `"INFO"` or `"ERROR"`.
+///
+/// The format specifier is a `,`-separated list of options:
+///
+/// - `pad`: right-pad the level with spaces to 5 chars, so that log lines are aligned.
+/// - `color`: wrap the level in the ANSI color of its severity, compared case-insensitively:
+///   red for `ERROR`, yellow for `WARN`, green for `INFO`, blue for `DEBUG` and cyan for `TRACE`.
+///   Other levels are not colored. The padding is written after the color reset sequence.
+///
+/// The `color` option is the only switch enabling colors: this crate has no `color` feature nor global toggle,
+/// and the `style::Styled` wrapper also always writes its ANSI codes.
+/// Callers which may not write to a terminal can disable colors by selecting the format string without the option.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Level;
+///
+/// assert_eq!(cfmt::format!("[{0 :<pad>}] [{1 :<pad>}]", Level("INFO"), Level("ERROR")), "[INFO ] [ERROR]");
+/// assert_eq!(cfmt::format!("{0 :<color>}", Level("WARN")), "\x1b[33mWARN\x1b[0m");
+/// assert_eq!(cfmt::format!("{0 :<color,pad>}|", Level("info")), "\x1b[32minfo\x1b[0m |");
+///
+/// let colored = false;
+/// let line = if colored { cfmt::format!("{0 :<color,pad>}|", Level("DEBUG")) } else { cfmt::format!("{0 :<pad>}|", Level("DEBUG")) };
+/// assert_eq!(line, "DEBUG|");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Level<'a>(pub &'a str);
+
+/// Width of the padded level
+const PADDED_WIDTH: usize = 5;
+
+/// ANSI color codes associated to level names
+const COLORS: [(&str, u8); 5] = [("ERROR", 31), ("WARN", 33), ("INFO", 32), ("DEBUG", 34), ("TRACE", 36)];
+
+impl CustomFormat for Level<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut pad = false;
+        let mut color = false;
+
+        for option in options(spec) {
+            match option {
+                ("pad", None) => pad = true,
+                ("color", None) => color = true,
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        match COLORS.iter().find(|(name, _)| name.eq_ignore_ascii_case(self.0)) {
+            Some(&(_, code)) if color => write!(f, "\x1b[{}m{}\x1b[0m", code, self.0)?,
+            _ => f.write_str(self.0)?,
+        }
+
+        if pad {
+            for _ in self.0.chars().count()..PADDED_WIDTH {
+                f.write_str(" ")?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+//! Provides ready-made types implementing [`runtime::CustomFormat`](crate::runtime::CustomFormat).
+//!
+//! Unless stated otherwise, an unsupported format specifier makes the formatting fail with [`fmt::Error`](core::fmt::Error).
+
+mod ago;
+mod backoff;
+mod bar;
+mod bases;
+mod bidi;
+mod bit_flags;
+mod boxed;
+mod c_array;
+mod cell;
+mod checksum;
+mod date;
+mod diff;
+mod digits;
+mod epoch;
+mod escape;
+mod frac;
+mod level;
+mod or_default;
+mod ptr;
+mod rate;
+mod sci_aligned;
+mod si;
+mod spark;
+mod status;
+mod transition;
+mod uuid;
+mod wrapping;
+
+pub use ago::Ago;
+pub use backoff::Backoff;
+pub use bar::Bar;
+pub use bases::Bases;
+pub use bidi::Bidi;
+pub use bit_flags::BitFlags;
+pub use boxed::Boxed;
+pub use c_array::CArray;
+pub use cell::Cell;
+pub use checksum::Checksum;
+pub use date::DateParts;
+pub use diff::Diff;
+pub use digits::Digits;
+pub use epoch::Epoch;
+pub use escape::Escape;
+pub use frac::Frac;
+pub use level::Level;
+pub use or_default::OrDefault;
+pub use ptr::Ptr;
+pub use rate::Rate;
+pub use sci_aligned::SciAligned;
+pub use si::Si;
+pub use spark::Spark;
+pub use status::Status;
+pub use transition::Transition;
+pub use uuid::Uuid;
+pub use wrapping::Wrap;
+
+#[cfg(has_num_saturating)]
+pub use wrapping::Sat;
+
+#[cfg(feature = "alloc")]
+mod tree;
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use tree::{Tree, TreeNode};
+
+/// Split a format specifier into `,`-separated options, each with an optional value after a `=` char
+fn options(spec: &str) -> impl Iterator<Item = (&str, Option<&str>)> {
+    spec.split(',').filter(|option| !option.is_empty()).map(|option| match option.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (option, None),
+    })
+}
+
+/// Parse an option value containing exactly one char
+fn single_char(value: Option<&str>) -> Result<char, core::fmt::Error> {
+    let mut chars = value.ok_or(core::fmt::Error)?.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(core::fmt::Error),
+    }
+}
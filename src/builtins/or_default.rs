@@ -0,0 +1,30 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Value replaced by a placeholder when it is equal to its [`Default`] value, for decluttering output where most values are defaults.
+///
+/// The format specifier is the placeholder, which is padded like a string with the flags of the formatter.
+/// Otherwise, the value is formatted with its [`Display`](fmt::Display) implementation.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::OrDefault;
+///
+/// assert_eq!(cfmt::format!("{0 :<(default)>}, {1 :<(default)>}", OrDefault(&0), OrDefault(&8080)), "(default), 8080");
+/// assert_eq!(cfmt::format!("[{0:>5 :<->}]", OrDefault(&String::new())), "[    -]");
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrDefault<'a, T>(pub &'a T);
+
+impl<T: Default + PartialEq + fmt::Display> CustomFormat for OrDefault<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        if *self.0 == T::default() {
+            f.pad(spec)
+        } else {
+            fmt::Display::fmt(self.0, f)
+        }
+    }
+}
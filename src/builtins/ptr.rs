@@ -0,0 +1,68 @@
+use super::options;
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Memory address, for low-level debugging.
+///
+/// The address is written in hexadecimal, like `"0x7fff5000"`. The format specifier is a `,`-separated list of options:
+///
+/// - `off=BASE`: write the address as an offset from a base address, like `"0x1000+0x24"`.
+///   The base address is written in hexadecimal with a `0x` prefix, or in decimal.
+/// - `align`: append the largest power of two dividing the address, like `"0x1000 (aligned 4096)"`.
+///   The null address is annotated with `(null)` instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Ptr;
+///
+/// assert_eq!(cfmt::format!("{0 :<>}", Ptr(0x7fff_5000)), "0x7fff5000");
+/// assert_eq!(cfmt::format!("{0 :<off=0x1000>}", Ptr(0x1024)), "0x1000+0x24");
+/// assert_eq!(cfmt::format!("{0 :<align>}", Ptr(0x1000)), "0x1000 (aligned 4096)");
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ptr(pub usize);
+
+impl Ptr {
+    /// Construct a new [`Ptr`] value from the address of a pointer
+    pub fn new<T: ?Sized>(ptr: *const T) -> Self {
+        Self(ptr as *const () as usize)
+    }
+}
+
+/// Parse an address written in hexadecimal with a `0x` prefix, or in decimal
+fn parse_address(value: &str) -> Result<usize, fmt::Error> {
+    match value.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).map_err(|_| fmt::Error),
+        None => value.parse().map_err(|_| fmt::Error),
+    }
+}
+
+impl CustomFormat for Ptr {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut base = None;
+        let mut align = false;
+
+        for option in options(spec) {
+            match option {
+                ("off", Some(value)) => base = Some(parse_address(value)?),
+                ("align", None) => align = true,
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        match base {
+            Some(base) if self.0 >= base => write!(f, "{:#x}+{:#x}", base, self.0 - base)?,
+            Some(base) => write!(f, "{:#x}-{:#x}", base, base - self.0)?,
+            None => write!(f, "{:#x}", self.0)?,
+        }
+
+        match (align, self.0) {
+            (false, _) => Ok(()),
+            (true, 0) => f.write_str(" (null)"),
+            (true, address) => write!(f, " (aligned {})", 1usize << address.trailing_zeros()),
+        }
+    }
+}
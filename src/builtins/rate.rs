@@ -0,0 +1,52 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+use core::time::Duration;
+
+/// Rate or frequency, in events per second.
+///
+/// Supported format specifiers:
+///
+/// - `""`: events per second, like `"12.5/s"`.
+/// - `"hz"`: frequency in hertz, like `"12.5 Hz"`.
+/// - `"per=s"`, `"per=min"`, `"per=h"`: events per second, minute or hour.
+///
+/// The precision of the formatter is applied to the displayed number.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Rate;
+///
+/// use core::time::Duration;
+///
+/// let rate = Rate::new(25.0, Duration::from_secs(2));
+/// assert_eq!(cfmt::format!("{0 :<>}, {0 :<hz>}, {0 :<per=min>}", rate), "12.5/s, 12.5 Hz, 750/min");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rate(pub f64);
+
+impl Rate {
+    /// Construct a new [`Rate`] value from a number of events occurring during the provided duration
+    pub fn new(count: f64, elapsed: Duration) -> Self {
+        Self(count / elapsed.as_secs_f64())
+    }
+}
+
+impl CustomFormat for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let (value, unit) = match spec {
+            "" | "per=s" => (self.0, "/s"),
+            "hz" => (self.0, " Hz"),
+            "per=min" => (self.0 * 60.0, "/min"),
+            "per=h" => (self.0 * 3600.0, "/h"),
+            _ => return Err(fmt::Error),
+        };
+
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*}{}", precision, value, unit),
+            None => write!(f, "{}{}", value, unit),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt::{self, Write};
+
+/// Floating-point number in scientific notation with a fixed width, for aligning columns of numbers.
+///
+/// The format specifier is the number of digits of the mantissa after the decimal point.
+/// The output always has a sign, and the exponent has a sign and at least two digits, like in `"+1.23e+04"`.
+/// Exponents of three digits only happen for magnitudes below `1e-99` or above `1e+99`.
+///
+/// `NaN` and infinite values are right-aligned to the same width.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::SciAligned;
+///
+/// assert_eq!(cfmt::format!("{0 :<2>}", SciAligned(12345.0)), "+1.23e+04");
+/// assert_eq!(cfmt::format!("{0 :<2>}", SciAligned(-0.000123)), "-1.23e-04");
+/// assert_eq!(cfmt::format!("{0 :<0>}", SciAligned(7.0)), "+7e+00");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SciAligned(pub f64);
+
+/// Writer rewriting the exponent of a number formatted with the `{:e}` format specifier
+struct ExponentWriter<'a, 'b> {
+    /// Inner formatter
+    f: &'a mut fmt::Formatter<'b>,
+    /// Exponent sign and absolute value, after the `e` char is written
+    exponent: Option<(bool, u32)>,
+}
+
+impl Write for ExponentWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match (&mut self.exponent, c) {
+                (None, 'e') => self.exponent = Some((false, 0)),
+                (None, c) => self.f.write_char(c)?,
+                (Some((negative, _)), '-') => *negative = true,
+                (Some((_, value)), c) => *value = *value * 10 + c.to_digit(10).ok_or(fmt::Error)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CustomFormat for SciAligned {
+    fn empty_is_valid(&self) -> bool {
+        false
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let precision: usize = spec.parse().map_err(|_| fmt::Error)?;
+
+        if !self.0.is_finite() {
+            // Sign, mantissa with optional decimal point, and 4 chars for the exponent
+            let width = 2 + if precision > 0 { precision + 1 } else { 0 } + 4;
+            return if self.0.is_nan() { write!(f, "{:>width$}", "NaN", width = width) } else { write!(f, "{:>+width$}", self.0, width = width) };
+        }
+
+        let mut writer = ExponentWriter { f, exponent: None };
+        write!(writer, "{:+.*e}", precision, self.0)?;
+
+        match writer.exponent {
+            Some((negative, value)) => write!(writer.f, "e{}{:02}", if negative { '-' } else { '+' }, value),
+            None => Err(fmt::Error),
+        }
+    }
+}
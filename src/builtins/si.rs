@@ -0,0 +1,77 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// SI prefixes with the multiplier and the divisor for scaling a value, in increasing order.
+///
+/// Only one of the multiplier and the divisor is different from `1.0`, so that the scaling is exact for exact decimal values.
+const PREFIXES: [(&str, f64, f64); 10] = [
+    ("n", 1e9, 1.0),
+    ("µ", 1e6, 1.0),
+    ("m", 1e3, 1.0),
+    ("", 1.0, 1.0),
+    ("k", 1.0, 1e3),
+    ("M", 1.0, 1e6),
+    ("G", 1.0, 1e9),
+    ("T", 1.0, 1e12),
+    ("P", 1.0, 1e15),
+    ("E", 1.0, 1e18),
+];
+
+/// Index of the empty prefix in [`PREFIXES`]
+const UNIT_INDEX: usize = 3;
+
+/// Quantity displayed with an SI prefix, from `n` (nano) to `E` (exa).
+///
+/// The format specifier is the unit suffix, like `"Hz"` or `"V"`.
+/// The prefix is chosen so that the displayed number is in the range `[1, 1000)`, except for values outside of the supported prefixes.
+///
+/// The precision of the formatter is applied to the displayed number, and is taken into account when choosing the prefix.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Si;
+///
+/// assert_eq!(cfmt::format!("{0 :<Hz>}", Si(1500.0)), "1.5 kHz");
+/// assert_eq!(cfmt::format!("{0 :<V>}", Si(0.000_25)), "250 µV");
+/// assert_eq!(cfmt::format!("{0:.2 :<W>}", Si(-2_345_678.0)), "-2.35 MW");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Si(pub f64);
+
+impl CustomFormat for Si {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let abs = if self.0 < 0.0 { -self.0 } else { self.0 };
+
+        // Half of the last displayed digit at the lower prefix, for selecting a prefix when the value at the lower prefix is rounded up to 1000
+        let mut half_digit = 0.0;
+        if let Some(precision) = f.precision() {
+            half_digit = 0.5e-3;
+            for _ in 0..precision {
+                half_digit /= 10.0;
+            }
+        }
+
+        let index = if abs == 0.0 || !abs.is_finite() {
+            UNIT_INDEX
+        } else {
+            PREFIXES.iter().rposition(|&(_, multiplier, divisor)| abs * multiplier / divisor + half_digit >= 1.0).unwrap_or(0)
+        };
+
+        let (prefix, multiplier, divisor) = PREFIXES[index];
+        let value = self.0 * multiplier / divisor;
+
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*}", precision, value)?,
+            None => write!(f, "{}", value)?,
+        }
+
+        if !prefix.is_empty() || !spec.is_empty() {
+            write!(f, " {}{}", prefix, spec)?;
+        }
+
+        Ok(())
+    }
+}
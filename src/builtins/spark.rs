@@ -0,0 +1,53 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Block elements used for the sparkline, from the lowest to the highest value
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Slice of values displayed as a one-line sparkline.
+///
+/// Values are scaled between the minimum and maximum of the slice, and a constant slice is displayed with middle blocks.
+/// `NaN` values are displayed as spaces. Only the empty format specifier is supported.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Spark;
+///
+/// assert_eq!(cfmt::format!("{0 :<>}", Spark(&[1.0, 5.0, 22.0, 13.0, 5.0])), "▁▂█▅▂");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Spark<'a>(pub &'a [f64]);
+
+impl CustomFormat for Spark<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        if !spec.is_empty() {
+            return Err(fmt::Error);
+        }
+
+        let (min, max) = self
+            .0
+            .iter()
+            .filter(|x| !x.is_nan())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &x| (if x < min { x } else { min }, if x > max { x } else { max }));
+
+        let range = max - min;
+
+        for &value in self.0 {
+            let block = if value.is_nan() {
+                ' '
+            } else if range > 0.0 && range.is_finite() {
+                let index = ((value - min) / range * (BLOCKS.len() - 1) as f64 + 0.5) as usize;
+                BLOCKS[index.min(BLOCKS.len() - 1)]
+            } else {
+                BLOCKS[BLOCKS.len() / 2 - 1]
+            };
+
+            fmt::Write::write_char(f, block)?;
+        }
+
+        Ok(())
+    }
+}
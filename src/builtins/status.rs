@@ -0,0 +1,52 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// Status value rendered as a glyph, like an emoji status indicator.
+///
+/// The table associates a glyph to a threshold, and the glyph of the greatest threshold less than or equal to the value is written.
+/// The table must be sorted by increasing thresholds, and the formatting fails if the value is less than all thresholds.
+///
+/// Supported format specifiers:
+///
+/// - `""`: use the table of the value.
+/// - `"health"`: use the [`Status::HEALTH`] table, mapping negative values to `"🔴"`, zero to `"🟡"` and positive values to `"🟢"`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Status;
+///
+/// assert_eq!(cfmt::format!("{0 :<health>}{1 :<health>}{2 :<health>}", Status::new(-2), Status::new(0), Status::new(5)), "🔴🟡🟢");
+///
+/// const LOAD: &[(i32, &str)] = &[(0, "low"), (50, "medium"), (90, "high")];
+/// assert_eq!(cfmt::format!("{0 :<>}, {1 :<>}", Status(42, LOAD), Status(95, LOAD)), "low, high");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Status<'a>(pub i32, pub &'a [(i32, &'a str)]);
+
+impl Status<'static> {
+    /// Table mapping negative values to `"🔴"`, zero to `"🟡"` and positive values to `"🟢"`
+    pub const HEALTH: &'static [(i32, &'static str)] = &[(i32::MIN, "🔴"), (0, "🟡"), (1, "🟢")];
+
+    /// Construct a new [`Status`] value with the [`Status::HEALTH`] table
+    pub fn new(value: i32) -> Self {
+        Self(value, Self::HEALTH)
+    }
+}
+
+impl CustomFormat for Status<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let table = match spec {
+            "" => self.1,
+            "health" => Status::HEALTH,
+            _ => return Err(fmt::Error),
+        };
+
+        match table.iter().rev().find(|&&(threshold, _)| threshold <= self.0) {
+            Some(&(_, glyph)) => f.write_str(glyph),
+            None => Err(fmt::Error),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use super::options;
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+use core::time::Duration;
+
+/// Transition between two states, like in state-machine logs.
+///
+/// The format specifier is a `,`-separated list of options:
+///
+/// - `arrow=S`: arrow glyph written between the states (*default: `→`*). It cannot contain a `,` char.
+/// - `ascii`: shorthand for `arrow=->`.
+/// - `elapsed`: append the elapsed time of the transition if available, like `(1.5s)`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Transition;
+///
+/// use core::time::Duration;
+///
+/// let transition = Transition::new(&"Idle", &"Running").with_elapsed(Duration::from_millis(1500));
+/// assert_eq!(cfmt::format!("{0 :<>}, {0 :<ascii,elapsed>}", transition), "Idle → Running, Idle -> Running (1.5s)");
+/// ```
+#[derive(Debug)]
+pub struct Transition<'a, S> {
+    /// Previous state
+    pub from: &'a S,
+    /// Next state
+    pub to: &'a S,
+    /// Optional elapsed time of the transition
+    pub elapsed: Option<Duration>,
+}
+
+impl<S> Clone for Transition<'_, S> {
+    fn clone(&self) -> Self {
+        Self { from: self.from, to: self.to, elapsed: self.elapsed }
+    }
+}
+
+impl<'a, S> Transition<'a, S> {
+    /// Construct a new [`Transition`] value
+    pub fn new(from: &'a S, to: &'a S) -> Self {
+        Self { from, to, elapsed: None }
+    }
+
+    /// Set the elapsed time of the transition
+    pub fn with_elapsed(self, elapsed: Duration) -> Self {
+        Self { elapsed: Some(elapsed), ..self }
+    }
+}
+
+impl<S: fmt::Display> CustomFormat for Transition<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let mut arrow = "→";
+        let mut show_elapsed = false;
+
+        for option in options(spec) {
+            match option {
+                ("arrow", Some(value)) if !value.is_empty() => arrow = value,
+                ("ascii", None) => arrow = "->",
+                ("elapsed", None) => show_elapsed = true,
+                _ => return Err(fmt::Error),
+            }
+        }
+
+        write!(f, "{} {} {}", self.from, arrow, self.to)?;
+
+        match self.elapsed {
+            Some(elapsed) if show_elapsed => write!(f, " ({:?})", elapsed),
+            _ => Ok(()),
+        }
+    }
+}
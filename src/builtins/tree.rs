@@ -0,0 +1,114 @@
+use crate::runtime::CustomFormat;
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Node of a tree which can be displayed with [`Tree`]
+pub trait TreeNode {
+    /// Write the label of the node
+    fn label(&self, f: &mut fmt::Formatter) -> fmt::Result;
+
+    /// Returns the children of the node
+    fn children(&self) -> &[Self]
+    where
+        Self: Sized;
+}
+
+/// Tree displayed with indentation and branch glyphs, like the `tree` command.
+///
+/// The following format specifiers are supported:
+///
+/// - `"ascii"`: draw branches with ASCII glyphs.
+/// - `"uni"`: draw branches with Unicode box-drawing glyphs.
+///
+/// Lines are separated by a `\n` char, without a trailing newline.
+/// The tree is traversed with an explicit stack, so deep trees don't overflow the call stack.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::{Tree, TreeNode};
+///
+/// use core::fmt;
+///
+/// struct Node(&'static str, Vec<Node>);
+///
+/// impl TreeNode for Node {
+///     fn label(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         f.write_str(self.0)
+///     }
+///
+///     fn children(&self) -> &[Self] {
+///         &self.1
+///     }
+/// }
+///
+/// let root = Node("src", vec![Node("builtins", vec![Node("tree.rs", vec![])]), Node("lib.rs", vec![])]);
+///
+/// assert_eq!(cfmt::format!("{0 :<uni>}", Tree(&root)), "src\n├── builtins\n│   └── tree.rs\n└── lib.rs");
+/// assert_eq!(cfmt::format!("{0 :<ascii>}", Tree(&root)), "src\n|-- builtins\n|   `-- tree.rs\n`-- lib.rs");
+/// ```
+#[derive(Debug)]
+pub struct Tree<'a, T>(pub &'a T);
+
+impl<T> Clone for Tree<'_, T> {
+    fn clone(&self) -> Self {
+        Self(self.0)
+    }
+}
+
+/// Branch glyphs of a tree
+struct Glyphs {
+    /// Glyph for a child with following siblings
+    branch: &'static str,
+    /// Glyph for the last child
+    last_branch: &'static str,
+    /// Indentation below a child with following siblings
+    vertical: &'static str,
+    /// Indentation below the last child
+    empty: &'static str,
+}
+
+impl<T: TreeNode> CustomFormat for Tree<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        let glyphs = match spec {
+            "ascii" => Glyphs { branch: "|-- ", last_branch: "`-- ", vertical: "|   ", empty: "    " },
+            "uni" => Glyphs { branch: "├── ", last_branch: "└── ", vertical: "│   ", empty: "    " },
+            _ => return Err(fmt::Error),
+        };
+
+        self.0.label(f)?;
+
+        let mut stack = Vec::new();
+        stack.push(self.0.children().iter().peekable());
+
+        while let Some(siblings) = stack.last_mut() {
+            let node = match siblings.next() {
+                Some(node) => node,
+                None => {
+                    stack.pop();
+                    continue;
+                }
+            };
+
+            f.write_str("\n")?;
+
+            let depth = stack.len();
+            for ancestors in &mut stack[..depth - 1] {
+                f.write_str(if ancestors.peek().is_some() { glyphs.vertical } else { glyphs.empty })?;
+            }
+
+            let is_last = stack[depth - 1].peek().is_none();
+            f.write_str(if is_last { glyphs.last_branch } else { glyphs.branch })?;
+            node.label(f)?;
+
+            let children = node.children();
+            if !children.is_empty() {
+                stack.push(children.iter().peekable());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+
+/// UUID displayed from its 16 bytes, without validating its version or variant.
+///
+/// The following format specifiers are supported:
+///
+/// - `""`: hyphenated form, like `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+/// - `"simple"`: form without hyphens, like `67e5504410b1426f9247bb680e5fe0c8`.
+/// - `"urn"`: URN form, like `urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8`.
+/// - `"braced"`: braced form, like `{67e55044-10b1-426f-9247-bb680e5fe0c8}`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Uuid;
+///
+/// let uuid = Uuid([0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f, 0x92, 0x47, 0xbb, 0x68, 0x0e, 0x5f, 0xe0, 0xc8]);
+/// assert_eq!(cfmt::format!("{0 :<urn>}", uuid), "urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uuid(pub [u8; 16]);
+
+impl Uuid {
+    /// Write the bytes in lowercase hexadecimal, with or without hyphens between the groups
+    fn fmt_hex(&self, f: &mut fmt::Formatter, hyphenated: bool) -> fmt::Result {
+        for (index, byte) in self.0.iter().enumerate() {
+            if hyphenated && matches!(index, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl CustomFormat for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        match spec {
+            "" => self.fmt_hex(f, true),
+            "simple" => self.fmt_hex(f, false),
+            "urn" => {
+                f.write_str("urn:uuid:")?;
+                self.fmt_hex(f, true)
+            }
+            "braced" => {
+                f.write_str("{")?;
+                self.fmt_hex(f, true)?;
+                f.write_str("}")
+            }
+            _ => Err(fmt::Error),
+        }
+    }
+}
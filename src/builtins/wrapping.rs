@@ -0,0 +1,87 @@
+use crate::runtime::CustomFormat;
+
+use core::fmt;
+use core::num::Wrapping;
+
+#[cfg(has_num_saturating)]
+use core::num::Saturating;
+
+/// Format an integer with the standard formatting trait selected by the format specifier
+fn fmt_integer<T>(value: &T, f: &mut fmt::Formatter, spec: &str) -> fmt::Result
+where
+    T: fmt::Display + fmt::Debug + fmt::LowerHex + fmt::UpperHex + fmt::Octal + fmt::Binary + fmt::LowerExp + fmt::UpperExp,
+{
+    match spec {
+        "" => fmt::Display::fmt(value, f),
+        "?" => fmt::Debug::fmt(value, f),
+        "x" => fmt::LowerHex::fmt(value, f),
+        "X" => fmt::UpperHex::fmt(value, f),
+        "o" => fmt::Octal::fmt(value, f),
+        "b" => fmt::Binary::fmt(value, f),
+        "e" => fmt::LowerExp::fmt(value, f),
+        "E" => fmt::UpperExp::fmt(value, f),
+        "#x" => write!(f, "{:#x}", value),
+        "#X" => write!(f, "{:#X}", value),
+        "#o" => write!(f, "{:#o}", value),
+        "#b" => write!(f, "{:#b}", value),
+        _ => Err(fmt::Error),
+    }
+}
+
+/// Custom formatter for the inner value of a [`Wrapping`] integer.
+///
+/// The format specifier selects the standard formatting trait used for the inner value:
+/// `""` (`Display`), `"?"`, `"x"`, `"X"`, `"o"`, `"b"`, `"e"` and `"E"`, optionally prefixed by `#` for the alternate form of `"x"`, `"X"`, `"o"` and `"b"`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Wrap;
+///
+/// use core::num::Wrapping;
+///
+/// let value = Wrap(Wrapping(250u8) + Wrapping(10));
+/// assert_eq!(cfmt::format!("{0 :<>}, {0 :<#x>}, {0 :<b>}", value), "4, 0x4, 100");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Wrap<T>(pub Wrapping<T>);
+
+impl<T> CustomFormat for Wrap<T>
+where
+    T: fmt::Display + fmt::Debug + fmt::LowerHex + fmt::UpperHex + fmt::Octal + fmt::Binary + fmt::LowerExp + fmt::UpperExp,
+{
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        fmt_integer(&self.0 .0, f, spec)
+    }
+}
+
+/// Custom formatter for the inner value of a [`Saturating`] integer.
+///
+/// The format specifier is interpreted as for [`Wrap`]. This type is only available on Rust 1.74+.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::builtins::Sat;
+///
+/// use core::num::Saturating;
+///
+/// let value = Sat(Saturating(250u8) + Saturating(10));
+/// assert_eq!(cfmt::format!("{0 :<>}, {0 :<X>}", value), "255, FF");
+/// ```
+#[cfg(has_num_saturating)]
+#[clippy::msrv = "1.74"]
+#[derive(Debug, Clone)]
+pub struct Sat<T>(pub Saturating<T>);
+
+#[cfg(has_num_saturating)]
+impl<T> CustomFormat for Sat<T>
+where
+    T: fmt::Display + fmt::Debug + fmt::LowerHex + fmt::UpperHex + fmt::Octal + fmt::Binary + fmt::LowerExp + fmt::UpperExp,
+{
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        fmt_integer(&self.0 .0, f, spec)
+    }
+}
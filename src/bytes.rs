@@ -0,0 +1,158 @@
+//! Provides types associated to byte-oriented formatting, used by the [`write_bytes!`](crate::write_bytes) macro.
+
+use core::cell::{Cell, RefCell};
+use core::fmt;
+use std::io;
+use std::vec::Vec;
+
+/// Trait for custom formatting to a byte sink, which may produce non-UTF8 output
+pub trait CustomFormatBytes {
+    /// Writes the value to the byte sink using the given format specifier.
+    ///
+    /// An invalid format specifier should be reported with an error of kind [`io::ErrorKind::InvalidInput`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use custom_format as cfmt;
+    /// use custom_format::bytes::CustomFormatBytes;
+    ///
+    /// use std::io;
+    ///
+    /// struct Frame(u16);
+    ///
+    /// impl CustomFormatBytes for Frame {
+    ///     fn fmt_bytes(&self, w: &mut dyn io::Write, spec: &str) -> io::Result<()> {
+    ///         match spec {
+    ///             "be" => w.write_all(&self.0.to_be_bytes()),
+    ///             "le" => w.write_all(&self.0.to_le_bytes()),
+    ///             _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid format specifier")),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut buffer = Vec::new();
+    /// cfmt::write_bytes!(buffer, "[{0 :<be>}|{0 :<le>}]", Frame(0xFF01)).unwrap();
+    ///
+    /// assert_eq!(buffer, b"[\xFF\x01|\x01\xFF]");
+    /// ```
+    fn fmt_bytes(&self, w: &mut dyn io::Write, spec: &str) -> io::Result<()>;
+}
+
+/// Write the bytes of a slice
+///
+/// | Specifier | Output                           |
+/// |-----------|----------------------------------|
+/// | `raw`     | Unmodified bytes                 |
+/// | `hex`     | Lowercase hexadecimal digits     |
+/// | `HEX`     | Uppercase hexadecimal digits     |
+impl CustomFormatBytes for [u8] {
+    fn fmt_bytes(&self, w: &mut dyn io::Write, spec: &str) -> io::Result<()> {
+        let digits: &[u8; 16] = match spec {
+            "raw" => return w.write_all(self),
+            "hex" => b"0123456789abcdef",
+            "HEX" => b"0123456789ABCDEF",
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid format specifier")),
+        };
+
+        for &byte in self {
+            w.write_all(&[digits[(byte >> 4) as usize], digits[(byte & 0xF) as usize]])?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> CustomFormatBytes for [u8; N] {
+    fn fmt_bytes(&self, w: &mut dyn io::Write, spec: &str) -> io::Result<()> {
+        self[..].fmt_bytes(w, spec)
+    }
+}
+
+impl CustomFormatBytes for Vec<u8> {
+    fn fmt_bytes(&self, w: &mut dyn io::Write, spec: &str) -> io::Result<()> {
+        self[..].fmt_bytes(w, spec)
+    }
+}
+
+impl<T: CustomFormatBytes + ?Sized> CustomFormatBytes for &T {
+    fn fmt_bytes(&self, w: &mut dyn io::Write, spec: &str) -> io::Result<()> {
+        (**self).fmt_bytes(w, spec)
+    }
+}
+
+/// Byte sink shared between the formatted text and the [`BytesFormatter`] values of a [`write_bytes!`](crate::write_bytes) call
+#[doc(hidden)]
+pub struct ByteSink<'a, W: io::Write> {
+    /// Inner writer
+    inner: RefCell<&'a mut W>,
+    /// First error returned by the inner writer
+    error: Cell<Option<io::Error>>,
+}
+
+impl<'a, W: io::Write> ByteSink<'a, W> {
+    /// Construct a new [`ByteSink`] value
+    pub fn new(inner: &'a mut W) -> Self {
+        Self { inner: RefCell::new(inner), error: Cell::new(None) }
+    }
+
+    /// Write formatted data, where [`BytesFormatter`] values write their bytes directly to the inner writer
+    pub fn write_fmt(&self, args: fmt::Arguments) -> io::Result<()> {
+        match fmt::write(&mut TextWriter { sink: self }, args) {
+            Ok(()) => Ok(()),
+            Err(fmt::Error) => Err(self.error.take().unwrap_or_else(|| io::Error::new(io::ErrorKind::Other, "formatter error"))),
+        }
+    }
+
+    /// Store the first error of the inner writer
+    fn check<T>(&self, result: io::Result<T>) -> fmt::Result {
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                let error = self.error.take();
+                self.error.set(error.or(Some(e)));
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+/// Writer for the formatted text of a [`ByteSink`]
+struct TextWriter<'a, 'b, W: io::Write> {
+    /// Byte sink
+    sink: &'a ByteSink<'b, W>,
+}
+
+impl<W: io::Write> fmt::Write for TextWriter<'_, '_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.sink.check(self.sink.inner.borrow_mut().write_all(s.as_bytes()))
+    }
+}
+
+/// Wrapper for custom formatting to a byte sink via its [`Display`](core::fmt::Display) trait.
+///
+/// The bytes are written directly to the inner writer of the byte sink, and the standard format flags are ignored.
+#[doc(hidden)]
+pub struct BytesFormatter<'a, 'b, 'c, T: ?Sized, W: io::Write> {
+    /// Format specifier
+    spec: &'static str,
+    /// Value to format
+    value: &'a T,
+    /// Byte sink
+    sink: &'b ByteSink<'c, W>,
+}
+
+impl<'a, 'b, 'c, T: CustomFormatBytes + ?Sized, W: io::Write> BytesFormatter<'a, 'b, 'c, T, W> {
+    /// Construct a new [`BytesFormatter`] value
+    pub fn new(spec: &'static str, value: &'a T, sink: &'b ByteSink<'c, W>) -> Self {
+        Self { spec, value, sink }
+    }
+}
+
+impl<T: CustomFormatBytes + ?Sized, W: io::Write> fmt::Display for BytesFormatter<'_, '_, '_, T, W> {
+    fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+        let mut inner = self.sink.inner.borrow_mut();
+        let result = self.value.fmt_bytes(&mut **inner, self.spec);
+        self.sink.check(result)
+    }
+}
@@ -2,8 +2,20 @@
 
 use core::fmt;
 
-/// Trait for custom formatting with compile-time format checking
-pub trait CustomFormat<const SPEC: u128> {
+/// Trait for custom formatting with compile-time format checking.
+///
+/// Standard flags written before the custom separator, like in `{x:+#08.3 :%a}`, are set on the formatter passed to the implementation,
+/// which can read them with methods like [`fmt::Formatter::width`] or [`fmt::Formatter::sign_plus`].
+#[cfg_attr(
+    has_diagnostic_namespace,
+    diagnostic::on_unimplemented(
+        message = "type `{Self}` does not support the custom format specifier `{SPEC}`",
+        label = "custom format specifier not implemented for `{Self}`",
+        note = "the custom format specifier is converted to a const-generic parameter with `custom_format::compile_time::spec`",
+        note = "write `<spec>` instead of `spec` after the ` :` separator to use a runtime format specifier"
+    )
+)]
+pub trait CustomFormat<const SPEC: Spec> {
     /// Formats the value using the given formatter.
     ///
     /// # Examples
@@ -53,48 +65,995 @@ pub trait CustomFormat<const SPEC: u128> {
     /// ```
     ///
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result;
+
+    /// Formats the value using the given formatter and the text of the format specifier.
+    ///
+    /// This method is called by the formatting macros, which know the text of the format specifier.
+    /// The default implementation calls [`CustomFormat::fmt`], and can be overridden by implementations which
+    /// need the text of the format specifier, like a generic implementation for all format specifiers.
+    ///
+    /// With the `nightly-spec-str` feature, a generic implementation requires enabling the `adt_const_params` and `unsized_const_params` features in the implementing crate.
+    ///
+    /// # Examples
+    ///
+    #[cfg_attr(not(feature = "nightly-spec-str"), doc = "```rust")]
+    #[cfg_attr(feature = "nightly-spec-str", doc = "```rust,ignore")]
+    /// use custom_format as cfmt;
+    /// use custom_format::compile_time::{CustomFormat, Spec};
+    ///
+    /// use core::fmt;
+    ///
+    /// struct Tagged(u8);
+    ///
+    /// impl<const SPEC: Spec> CustomFormat<SPEC> for Tagged {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "{}", self.0)
+    ///     }
+    ///
+    ///     fn fmt_with_spec(&self, f: &mut fmt::Formatter, spec: &'static str) -> fmt::Result {
+    ///         write!(f, "<{}>{}</{}>", spec, self.0, spec)
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(cfmt::format!("{0 :b}, {0 :i}", Tagged(1)), "<b>1</b>, <i>1</i>");
+    /// ```
+    fn fmt_with_spec(&self, f: &mut fmt::Formatter, spec: &'static str) -> fmt::Result {
+        let _ = spec;
+        self.fmt(f)
+    }
+}
+
+/// Forward custom formatting through a reference, so that borrowed values can be passed to the formatting macros.
+///
+/// The same forwarding is implemented for `&mut T`, and for `Box<T>`, `Rc<T>` and `Arc<T>` with the `alloc` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::compile_time::{spec, CustomFormat};
+///
+/// use core::fmt;
+/// use std::sync::Arc;
+///
+/// struct Hex(u8);
+///
+/// impl CustomFormat<{ spec("x") }> for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "{:#x}", self.0)
+///     }
+/// }
+///
+/// let (value, boxed, shared) = (Hex(0xAB), Box::new(Hex(0xCD)), Arc::new(Hex(0xEF)));
+/// let borrowed = &&value;
+///
+/// assert_eq!(cfmt::format!("{borrowed :x}, {boxed :x}, {shared :x}"), "0xab, 0xcd, 0xef");
+/// ```
+impl<T: CustomFormat<SPEC> + ?Sized, const SPEC: Spec> CustomFormat<SPEC> for &T {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+
+    fn fmt_with_spec(&self, f: &mut fmt::Formatter, spec: &'static str) -> fmt::Result {
+        (**self).fmt_with_spec(f, spec)
+    }
+}
+
+impl<T: CustomFormat<SPEC> + ?Sized, const SPEC: Spec> CustomFormat<SPEC> for &mut T {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+
+    fn fmt_with_spec(&self, f: &mut fmt::Formatter, spec: &'static str) -> fmt::Result {
+        (**self).fmt_with_spec(f, spec)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: CustomFormat<SPEC> + ?Sized, const SPEC: Spec> CustomFormat<SPEC> for alloc::boxed::Box<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+
+    fn fmt_with_spec(&self, f: &mut fmt::Formatter, spec: &'static str) -> fmt::Result {
+        (**self).fmt_with_spec(f, spec)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: CustomFormat<SPEC> + ?Sized, const SPEC: Spec> CustomFormat<SPEC> for alloc::rc::Rc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+
+    fn fmt_with_spec(&self, f: &mut fmt::Formatter, spec: &'static str) -> fmt::Result {
+        (**self).fmt_with_spec(f, spec)
+    }
 }
 
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: CustomFormat<SPEC> + ?Sized, const SPEC: Spec> CustomFormat<SPEC> for alloc::sync::Arc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+
+    fn fmt_with_spec(&self, f: &mut fmt::Formatter, spec: &'static str) -> fmt::Result {
+        (**self).fmt_with_spec(f, spec)
+    }
+}
+
+/// Derives [`CustomFormat`] from `#[custom_format(...)]` attributes on the type or on the fields of a struct.
+///
+/// An attribute on the type must provide the path of a formatting function, like `#[custom_format(spec = "%D", with = "fmt_date")]`,
+/// which is called with `self` and the formatter.
+///
+/// An attribute on a field maps a format specifier to this field, which is formatted with its [`Display`](core::fmt::Display) implementation,
+/// or with the formatting function provided with the `with` argument, called with a reference to the field and the formatter.
+///
+/// The format specifier defaults to the name of the formatting function for an attribute on the type, and to the name of the field for an attribute on a field.
+/// It can be provided with the `spec` argument, or equivalently with the `rename` argument, like `#[custom_format(rename = "%Y")]`.
+///
+/// Each format specifier can only be used once, and two format specifiers cannot have the same [`spec`] value.
+/// If the crate is renamed, its path can be provided with `#[custom_format(crate = path)]` on the type.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::compile_time::CustomFormat;
+///
+/// use core::fmt;
+///
+/// fn fmt_date(date: &Date, f: &mut fmt::Formatter) -> fmt::Result {
+///     write!(f, "{:04}-{:02}", date.year, date.month)
+/// }
+///
+/// fn fmt_padded(month: &u8, f: &mut fmt::Formatter) -> fmt::Result {
+///     write!(f, "{:02}", month)
+/// }
+///
+/// #[derive(CustomFormat)]
+/// #[custom_format(crate = cfmt, spec = "%D", with = "fmt_date")]
+/// struct Date {
+///     #[custom_format(spec = "%Y")]
+///     year: i32,
+///     #[custom_format(rename = "%m", with = "fmt_padded")]
+///     month: u8,
+///     #[custom_format()]
+///     day: u8,
+/// }
+///
+/// let date = Date { year: 2023, month: 7, day: 14 };
+/// assert_eq!(cfmt::format!("{date :%D}, {date :%Y}, {date :%m}, {date :day}"), "2023-07, 2023, 07, 14");
+/// ```
+pub use custom_format_macros::CustomFormat;
+
 /// Wrapper for custom formatting via its [`Display`](core::fmt::Display) trait.
 ///
 /// The format specifier is a const-generic parameter and is part of the type.
 ///
-#[derive(Debug, Clone)]
-pub struct CustomFormatter<'a, T, const SPEC: u128> {
+/// The other formatting traits, like [`Debug`](core::fmt::Debug) or [`LowerHex`](core::fmt::LowerHex), are forwarded to the wrapped value,
+/// so that the wrapper can be used in any formatting context.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::compile_time::{spec, CustomFormat};
+///
+/// use core::fmt;
+///
+/// #[derive(Debug)]
+/// struct Hex(u8);
+///
+/// impl CustomFormat<{ spec("x") }> for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "{:#x}", self.0)
+///     }
+/// }
+///
+/// let formatter = cfmt::custom_formatter!("x", &Hex(0xAB));
+/// assert_eq!(format!("{}, {:?}, {:#x}", formatter, formatter, cfmt::custom_formatter!("x", &0xCDu8)), "0xab, Hex(171), 0xcd");
+/// ```
+#[derive(Clone)]
+pub struct CustomFormatter<'a, T, const SPEC: Spec> {
+    /// Text of the format specifier, if known
+    spec: Option<&'static str>,
     /// Value to format
     value: &'a T,
 }
 
-impl<'a, T, const SPEC: u128> CustomFormatter<'a, T, SPEC> {
+impl<'a, T, const SPEC: Spec> CustomFormatter<'a, T, SPEC> {
     /// Construct a new [`CustomFormatter`] value
     pub fn new(value: &'a T) -> Self {
-        Self { value }
+        Self { spec: None, value }
+    }
+
+    /// Construct a new [`CustomFormatter`] value with the text of the format specifier,
+    /// which is passed to [`CustomFormat::fmt_with_spec`] when formatting.
+    ///
+    /// The text must be the format specifier used for computing the `SPEC` parameter with the [`spec`] function.
+    pub fn with_spec(spec: &'static str, value: &'a T) -> Self {
+        debug_assert!(self::spec(spec) == SPEC, "format specifier text doesn't match the const-generic parameter");
+        Self { spec: Some(spec), value }
     }
 }
 
-/// Helper macro for constructing a new [`compile_time::CustomFormatter`](CustomFormatter) value from a format specifier
+/// Helper macro for constructing a new [`compile_time::CustomFormatter`](CustomFormatter) value from a format specifier.
+///
+/// A deprecation warning is emitted if the format specifier is marked as deprecated with the [`deprecate_spec!`] macro.
 #[macro_export]
 macro_rules! custom_formatter {
     ($spec:literal, $value:expr) => {{
-        $crate::compile_time::CustomFormatter::<_, { $crate::compile_time::spec($spec) }>::new($value)
+        $crate::compile_time::SpecCheck::<_, { $crate::compile_time::spec($spec) }>::new($spec, $value).check()
     }};
 }
 pub use custom_formatter;
 
-impl<T: CustomFormat<SPEC>, const SPEC: u128> fmt::Display for CustomFormatter<'_, T, SPEC> {
+/// Implements [`CustomFormat`] for a type, with one implementation for each format specifier of a `match spec` block.
+///
+/// Each arm maps a format specifier to a closure or a function with the signature `fn(&Self, &mut fmt::Formatter) -> fmt::Result`.
+/// Generic parameters of the implementation can be provided in brackets after the `impl` keyword, like in `impl[T: fmt::Display] Wrapper<T>`.
+///
+/// See [`impl_runtime_custom_format!`](crate::impl_runtime_custom_format) for implementing the [`runtime::CustomFormat`](crate::runtime::CustomFormat) trait.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::fmt;
+///
+/// struct Date {
+///     year: i32,
+///     month: u8,
+/// }
+///
+/// fn month(date: &Date, f: &mut fmt::Formatter) -> fmt::Result {
+///     write!(f, "{:02}", date.month)
+/// }
+///
+/// cfmt::impl_custom_format!(Date, match spec {
+///     "%Y" => |this, f| write!(f, "{:04}", this.year),
+///     "%m" => month,
+/// });
+///
+/// struct Wrapper<T>(T);
+///
+/// cfmt::impl_custom_format!(impl[T: fmt::Display] Wrapper<T>, match spec {
+///     "[]" => |this, f| write!(f, "[{}]", this.0),
+/// });
+///
+/// let date = Date { year: 2023, month: 7 };
+/// assert_eq!(cfmt::format!("{date :%Y}-{date :%m}, {0 :[]}", Wrapper(1)), "2023-07, [1]");
+/// ```
+#[macro_export]
+macro_rules! impl_custom_format {
+    (@impl [$($generics:tt)*] $ty:ty, $spec:literal, $func:expr) => {
+        impl<$($generics)*> $crate::compile_time::CustomFormat<{ $crate::compile_time::spec($spec) }> for $ty {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                ($func as fn(&Self, &mut ::core::fmt::Formatter) -> ::core::fmt::Result)(self, f)
+            }
+        }
+    };
+    (impl $generics:tt $ty:ty, match spec { $($spec:literal => $func:expr),* $(,)? }) => {
+        $($crate::impl_custom_format!(@impl $generics $ty, $spec, $func);)*
+    };
+    ($ty:ty, match spec { $($spec:literal => $func:expr),* $(,)? }) => {
+        $crate::impl_custom_format!(impl[] $ty, match spec { $($spec => $func),* });
+    };
+}
+pub use impl_custom_format;
+
+/// Marker trait for a deprecated format specifier of a type, implemented with the [`deprecate_spec!`] macro
+pub trait DeprecatedSpec<const SPEC: Spec> {}
+
+/// Mark format specifiers of a type as deprecated, so that using them in a format string emits a deprecation warning.
+///
+/// The format specifiers are still valid, and must be implemented with the [`CustomFormat`] trait.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::compile_time::{spec, CustomFormat};
+///
+/// use core::fmt;
+///
+/// struct Hex(u8);
+///
+/// impl CustomFormat<{ spec("x") }> for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "{:#x}", self.0)
+///     }
+/// }
+///
+/// impl CustomFormat<{ spec("hex") }> for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "{:#x}", self.0)
+///     }
+/// }
+///
+/// cfmt::deprecate_spec!(Hex, "hex");
+///
+/// assert_eq!(cfmt::format!("{0 :x}", Hex(0xAB)), "0xab");
+/// ```
+///
+/// The following statement doesn't compile when denying warnings, since `"hex"` is deprecated:
+///
+/// ```rust,compile_fail
+/// # #![deny(deprecated)]
+/// # use custom_format as cfmt;
+/// # use custom_format::compile_time::{spec, CustomFormat};
+/// # use core::fmt;
+/// # struct Hex(u8);
+/// # impl CustomFormat<{ spec("hex") }> for Hex {
+/// #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+/// #         write!(f, "{:#x}", self.0)
+/// #     }
+/// # }
+/// # cfmt::deprecate_spec!(Hex, "hex");
+/// cfmt::println!("{0 :hex}", Hex(0xAB));
+/// ```
+#[macro_export]
+macro_rules! deprecate_spec {
+    ($ty:ty, $($spec:literal),+ $(,)?) => {
+        $(impl $crate::compile_time::DeprecatedSpec<{ $crate::compile_time::spec($spec) }> for $ty {})+
+    };
+}
+pub use deprecate_spec;
+
+/// Wrapper checking if a format specifier is deprecated when constructing a [`CustomFormatter`] value.
+///
+/// The deprecated `check` method is selected when the format specifier is deprecated,
+/// and the `check` method of [`CustomFormatter`] is selected otherwise via auto-deref.
+#[doc(hidden)]
+pub struct SpecCheck<'a, T, const SPEC: Spec>(CustomFormatter<'a, T, SPEC>);
+
+impl<'a, T, const SPEC: Spec> SpecCheck<'a, T, SPEC> {
+    /// Construct a new [`SpecCheck`] value
+    pub fn new(spec: &'static str, value: &'a T) -> Self {
+        Self(CustomFormatter::with_spec(spec, value))
+    }
+
+    /// Return the value to format
+    pub fn value(&self) -> &'a T {
+        self.0.value
+    }
+}
+
+impl<'a, T: DeprecatedSpec<SPEC>, const SPEC: Spec> SpecCheck<'a, T, SPEC> {
+    /// Return the wrapped [`CustomFormatter`] value, emitting a deprecation warning
+    #[deprecated(note = "this format specifier is deprecated for the formatted type")]
+    pub fn check(&self) -> CustomFormatter<'a, T, SPEC> {
+        self.0.check()
+    }
+}
+
+impl<'a, T, const SPEC: Spec> core::ops::Deref for SpecCheck<'a, T, SPEC> {
+    type Target = CustomFormatter<'a, T, SPEC>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Select the [`SpecCheck`] value when the value implements [`CustomFormat`] for the format specifier.
+///
+/// The [`checked_custom_formatter!`] macro implements a local trait for a reference to [`SpecCheck`], which is selected otherwise via auto-ref,
+/// so that the compilation error is reported for a trait whose diagnostic contains the text of the format specifier.
+#[cfg(has_diagnostic_namespace)]
+#[doc(hidden)]
+pub trait SpecCheckCustom<'a, T, const SPEC: Spec> {
+    /// Return a copy of the [`SpecCheck`] value
+    fn select(&self) -> SpecCheck<'a, T, SPEC>;
+}
+
+#[cfg(has_diagnostic_namespace)]
+impl<'a, T: CustomFormat<SPEC>, const SPEC: Spec> SpecCheckCustom<'a, T, SPEC> for SpecCheck<'a, T, SPEC> {
+    fn select(&self) -> SpecCheck<'a, T, SPEC> {
+        SpecCheck(self.0.check())
+    }
+}
+
+/// Helper macro for constructing a new [`compile_time::CustomFormatter`](CustomFormatter) value from a format specifier,
+/// reporting a compilation error containing the text of the format specifier if it is not implemented.
+///
+/// The message of the compilation error is computed by the formatting macros.
+#[cfg(has_diagnostic_namespace)]
+#[doc(hidden)]
+#[macro_export]
+#[rustfmt::skip]
+macro_rules! checked_custom_formatter {
+    ($spec:literal, $message:literal, $value:expr) => {{
+        #[diagnostic::on_unimplemented(
+            message = $message,
+            label = "custom format specifier not implemented for `{Self}`",
+            note = "compile-time format specifiers are implemented with the `custom_format::compile_time::CustomFormat` trait",
+            note = "write `<spec>` instead of `spec` after the ` :` separator to use a runtime format specifier"
+        )]
+        trait MissingCustomFormat {}
+
+        struct MissingFormatter<'a, T>(&'a T);
+
+        impl<T> MissingFormatter<'_, T> {
+            fn check(self) -> Self {
+                self
+            }
+        }
+
+        impl<T: MissingCustomFormat> ::core::fmt::Display for MissingFormatter<'_, T> {
+            fn fmt(&self, _: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        trait SpecCheckMissing<'a, T> {
+            fn select(&self) -> MissingFormatter<'a, T>;
+        }
+
+        impl<'a, T> SpecCheckMissing<'a, T> for &$crate::compile_time::SpecCheck<'a, T, { $crate::compile_time::spec($spec) }> {
+            fn select(&self) -> MissingFormatter<'a, T> {
+                MissingFormatter(self.value())
+            }
+        }
+
+        #[allow(unused_imports)]
+        use $crate::compile_time::SpecCheckCustom as _;
+        (&$crate::compile_time::SpecCheck::<_, { $crate::compile_time::spec($spec) }>::new($spec, $value)).select().check()
+    }};
+}
+
+/// Helper macro for constructing a new [`compile_time::CustomFormatter`](CustomFormatter) value from a format specifier.
+///
+/// The `#[diagnostic]` attribute namespace is not supported, so the message of the compilation error is not used.
+#[cfg(not(has_diagnostic_namespace))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! checked_custom_formatter {
+    ($spec:literal, $message:literal, $value:expr) => {
+        $crate::custom_formatter!($spec, $value)
+    };
+}
+
+impl<'a, T, const SPEC: Spec> CustomFormatter<'a, T, SPEC> {
+    /// Return a copy of the [`CustomFormatter`] value, used by the [`custom_formatter!`] macro for non-deprecated format specifiers
+    #[doc(hidden)]
+    pub fn check(&self) -> Self {
+        Self { spec: self.spec, value: self.value }
+    }
+}
+
+impl<T: CustomFormat<SPEC>, const SPEC: Spec> fmt::Display for CustomFormatter<'_, T, SPEC> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.spec {
+            Some(spec) => CustomFormat::fmt_with_spec(self.value, f, spec),
+            None => CustomFormat::fmt(self.value, f),
+        }
+    }
+}
+
+/// Forward formatting traits other than [`Display`](fmt::Display) to the wrapped value
+macro_rules! forward_fmt_traits {
+    ($($fmt_trait:ident),+) => {
+        $(
+            impl<T: fmt::$fmt_trait, const SPEC: Spec> fmt::$fmt_trait for CustomFormatter<'_, T, SPEC> {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    fmt::$fmt_trait::fmt(self.value, f)
+                }
+            }
+        )+
+    };
+}
+
+forward_fmt_traits!(Debug, Binary, Octal, LowerHex, UpperHex, LowerExp, UpperExp);
+
+/// Wrapper selecting the formatter of the empty compile-time format specifier `{x :}`.
+///
+/// The [`EmptySpecCustom`] trait is implemented for this type and is selected when the value implements [`CustomFormat`] for the empty format specifier,
+/// and the [`EmptySpecDisplay`] trait is implemented for a reference to this type and is selected otherwise via auto-ref.
+#[doc(hidden)]
+pub struct EmptySpec<'a, T>(&'a T);
+
+impl<'a, T> EmptySpec<'a, T> {
+    /// Construct a new [`EmptySpec`] value
+    pub fn new(value: &'a T) -> Self {
+        Self(value)
+    }
+}
+
+/// Select the custom formatter of an [`EmptySpec`] value
+#[doc(hidden)]
+pub trait EmptySpecCustom<'a, T> {
+    /// Return the custom formatter
+    fn formatter(&self) -> CustomFormatter<'a, T, { spec("") }>;
+}
+
+impl<'a, T: CustomFormat<{ spec("") }>> EmptySpecCustom<'a, T> for EmptySpec<'a, T> {
+    fn formatter(&self) -> CustomFormatter<'a, T, { spec("") }> {
+        CustomFormatter::with_spec("", self.0)
+    }
+}
+
+/// Select the value itself as formatter of an [`EmptySpec`] value, so that it is formatted with its [`Display`](core::fmt::Display) implementation
+#[doc(hidden)]
+pub trait EmptySpecDisplay<'a, T> {
+    /// Return the value
+    fn formatter(&self) -> &'a T;
+}
+
+impl<'a, T> EmptySpecDisplay<'a, T> for &EmptySpec<'a, T> {
+    fn formatter(&self) -> &'a T {
+        self.0
+    }
+}
+
+/// Helper macro for constructing the formatter of the empty compile-time format specifier, falling back to the [`Display`](core::fmt::Display) trait
+#[doc(hidden)]
+#[macro_export]
+macro_rules! empty_spec_formatter {
+    ("", $value:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::compile_time::{EmptySpecCustom as _, EmptySpecDisplay as _};
+        (&$crate::compile_time::EmptySpec::new($value)).formatter()
+    }};
+}
+
+/// Wrapper selecting a compile-time or a runtime formatter for a format specifier written like `{x :%Y|<>}`.
+///
+/// The [`FallbackCompileTime`] trait is implemented for this type and is selected when the value implements [`CustomFormat`] for the format specifier,
+/// and the [`FallbackRuntime`] trait is implemented for a reference to this type and is selected otherwise via auto-ref.
+#[cfg(feature = "runtime")]
+#[doc(hidden)]
+pub struct Fallback<'a, T, const SPEC: Spec> {
+    /// Format specifier
+    spec: &'static str,
+    /// Value to format
+    value: &'a T,
+}
+
+#[cfg(feature = "runtime")]
+impl<'a, T, const SPEC: Spec> Fallback<'a, T, SPEC> {
+    /// Construct a new [`Fallback`] value
+    pub fn new(spec: &'static str, value: &'a T) -> Self {
+        Self { spec, value }
+    }
+}
+
+/// Select the compile-time formatter of a [`Fallback`] value
+#[cfg(feature = "runtime")]
+#[doc(hidden)]
+pub trait FallbackCompileTime<'a, T, const SPEC: Spec> {
+    /// Return the compile-time formatter
+    fn formatter(&self) -> CustomFormatter<'a, T, SPEC>;
+}
+
+#[cfg(feature = "runtime")]
+impl<'a, T: CustomFormat<SPEC>, const SPEC: Spec> FallbackCompileTime<'a, T, SPEC> for Fallback<'a, T, SPEC> {
+    fn formatter(&self) -> CustomFormatter<'a, T, SPEC> {
+        CustomFormatter::with_spec(self.spec, self.value)
+    }
+}
+
+/// Select the runtime formatter of a [`Fallback`] value
+#[cfg(feature = "runtime")]
+#[doc(hidden)]
+pub trait FallbackRuntime<'a, T> {
+    /// Return the runtime formatter
+    fn formatter(&self) -> crate::runtime::CustomFormatter<'a, T>;
+}
+
+#[cfg(feature = "runtime")]
+impl<'a, T, const SPEC: Spec> FallbackRuntime<'a, T> for &Fallback<'a, T, SPEC> {
+    fn formatter(&self) -> crate::runtime::CustomFormatter<'a, T> {
+        crate::runtime::CustomFormatter::new(self.spec, self.value)
+    }
+}
+
+/// Helper macro for constructing a compile-time formatter if the format specifier is implemented, or a runtime formatter otherwise
+#[cfg(feature = "runtime")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fallback_formatter {
+    ($spec:literal, $value:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::compile_time::{FallbackCompileTime as _, FallbackRuntime as _};
+        (&$crate::compile_time::Fallback::<_, { $crate::compile_time::spec($spec) }>::new($spec, $value)).formatter()
+    }};
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: CustomFormat<SPEC>, const SPEC: Spec> CustomFormatter<'_, T, SPEC> {
+    /// Render the formatted value to a new `String`, which is equivalent to calling `.to_string()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use custom_format as cfmt;
+    /// use custom_format::compile_time::{spec, CustomFormat};
+    ///
+    /// use core::fmt;
+    ///
+    /// struct Hex(u8);
+    ///
+    /// impl CustomFormat<{ spec("x") }> for Hex {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         write!(f, "{:#x}", self.0)
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(cfmt::custom_formatter!("x", &Hex(0xAB)).render(), "0xab");
+    /// ```
+    pub fn render(&self) -> alloc::string::String {
+        alloc::string::ToString::to_string(self)
+    }
+}
+
+/// Value implementing [`CustomFormat`] for a single format specifier by calling a closure, returned by [`from_fn`]
+#[derive(Clone, Copy)]
+pub struct FromFn<F, const SPEC: Spec>(F);
+
+impl<F, const SPEC: Spec> fmt::Debug for FromFn<F, SPEC> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FromFn").finish_non_exhaustive()
+    }
+}
+
+impl<F: Fn(&mut fmt::Formatter) -> fmt::Result, const SPEC: Spec> CustomFormat<SPEC> for FromFn<F, SPEC> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (self.0)(f)
+    }
+}
+
+/// Create a value implementing [`CustomFormat`] for a single format specifier from a closure,
+/// for writing a one-off formatter inline without defining a named type.
+///
+/// The format specifier is provided at the call site with the [`spec`] function.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::compile_time::{from_fn, spec};
+///
+/// let answer = from_fn::<{ spec("x") }, _>(|f| write!(f, "{:#x}", 42));
+/// assert_eq!(cfmt::format!("{answer :x}"), "0x2a");
+/// assert_eq!(cfmt::custom_formatter!("x", &answer).to_string(), "0x2a");
+/// ```
+///
+/// The following statement doesn't compile since `"X"` is not the format specifier of the value:
+///
+/// ```rust,compile_fail
+/// # use custom_format as cfmt;
+/// # use custom_format::compile_time::{from_fn, spec};
+/// let answer = from_fn::<{ spec("x") }, _>(|f| write!(f, "{:#x}", 42));
+/// cfmt::println!("{answer :X}");
+/// ```
+pub fn from_fn<const SPEC: Spec, F: Fn(&mut fmt::Formatter) -> fmt::Result>(f: F) -> FromFn<F, SPEC> {
+    FromFn(f)
+}
+
+/// Trait for custom formatting of a family of format specifiers sharing a prefix, with compile-time checking of the prefix.
+///
+/// A format specifier written as `prefix(rest)`, like in `{x :%N(6)}`, is mapped to the implementation for the prefix,
+/// and the text between the parentheses is passed to the implementation, which can check it at runtime.
+/// An implementation of [`CustomFormat`] for the whole format specifier takes precedence over the implementation for the prefix.
+#[cfg_attr(
+    has_diagnostic_namespace,
+    diagnostic::on_unimplemented(
+        message = "type `{Self}` does not support the custom format specifier prefix `{PREFIX}`",
+        label = "custom format specifier prefix not implemented for `{Self}`",
+        note = "the prefix is converted to a const-generic parameter with `custom_format::compile_time::spec`"
+    )
+)]
+pub trait CustomFormatPrefix<const PREFIX: Spec> {
+    /// Formats the value using the given formatter and the remaining text of the format specifier after the prefix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use custom_format as cfmt;
+    /// use custom_format::compile_time::{spec, CustomFormatPrefix};
+    ///
+    /// use core::fmt;
+    ///
+    /// struct Nanoseconds(u32);
+    ///
+    /// impl CustomFormatPrefix<{ spec("%N") }> for Nanoseconds {
+    ///     fn fmt(&self, f: &mut fmt::Formatter, rest: &str) -> fmt::Result {
+    ///         match rest.parse::<u32>() {
+    ///             Ok(n @ 1..=9) => write!(f, "{:0width$}", self.0 / 10u32.pow(9 - n), width = n as usize),
+    ///             _ => Err(fmt::Error),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let ns = Nanoseconds(123456789);
+    /// assert_eq!(cfmt::format!("{ns :%N(3)}, {ns :%N(6)}"), "123, 123456");
+    /// ```
+    ///
+    /// The following statement doesn't compile since `"%Q"` is not a valid prefix:
+    ///
+    /// ```rust,compile_fail
+    /// # use custom_format as cfmt;
+    /// # use custom_format::compile_time::{spec, CustomFormatPrefix};
+    /// # use core::fmt;
+    /// # struct Nanoseconds(u32);
+    /// # impl CustomFormatPrefix<{ spec("%N") }> for Nanoseconds {
+    /// #     fn fmt(&self, f: &mut fmt::Formatter, rest: &str) -> fmt::Result {
+    /// #         write!(f, "{}", rest)
+    /// #     }
+    /// # }
+    /// cfmt::println!("{ :%Q(3)}", Nanoseconds(0));
+    /// ```
+    ///
+    fn fmt(&self, f: &mut fmt::Formatter, rest: &str) -> fmt::Result;
+}
+
+impl<T: CustomFormatPrefix<PREFIX> + ?Sized, const PREFIX: Spec> CustomFormatPrefix<PREFIX> for &T {
+    fn fmt(&self, f: &mut fmt::Formatter, rest: &str) -> fmt::Result {
+        (**self).fmt(f, rest)
+    }
+}
+
+impl<T: CustomFormatPrefix<PREFIX> + ?Sized, const PREFIX: Spec> CustomFormatPrefix<PREFIX> for &mut T {
+    fn fmt(&self, f: &mut fmt::Formatter, rest: &str) -> fmt::Result {
+        (**self).fmt(f, rest)
+    }
+}
+
+/// Wrapper for custom formatting of a family of format specifiers via its [`Display`](core::fmt::Display) trait.
+///
+/// The prefix of the format specifier is a const-generic parameter and is part of the type.
+///
+#[derive(Debug, Clone)]
+pub struct CustomPrefixFormatter<'a, T, const PREFIX: Spec> {
+    /// Remaining text of the format specifier after the prefix
+    rest: &'a str,
+    /// Value to format
+    value: &'a T,
+}
+
+impl<'a, T, const PREFIX: Spec> CustomPrefixFormatter<'a, T, PREFIX> {
+    /// Construct a new [`CustomPrefixFormatter`] value
+    pub fn new(rest: &'a str, value: &'a T) -> Self {
+        Self { rest, value }
+    }
+}
+
+impl<T: CustomFormatPrefix<PREFIX>, const PREFIX: Spec> fmt::Display for CustomPrefixFormatter<'_, T, PREFIX> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        CustomFormat::fmt(self.value, f)
+        CustomFormatPrefix::fmt(self.value, f, self.rest)
+    }
+}
+
+/// Helper macro for constructing a new [`compile_time::CustomPrefixFormatter`](CustomPrefixFormatter) value from a prefix and the remaining text of a format specifier.
+#[macro_export]
+macro_rules! custom_prefix_formatter {
+    ($prefix:literal, $rest:literal, $value:expr) => {{
+        $crate::compile_time::CustomPrefixFormatter::<_, { $crate::compile_time::spec($prefix) }>::new($rest, $value)
+    }};
+}
+pub use custom_prefix_formatter;
+
+impl<'a, T, const PREFIX: Spec> CustomPrefixFormatter<'a, T, PREFIX> {
+    /// Return a copy of the [`CustomPrefixFormatter`] value, used by the formatting macros for non-deprecated prefixes
+    #[doc(hidden)]
+    pub fn check(&self) -> Self {
+        Self { rest: self.rest, value: self.value }
+    }
+}
+
+/// Wrapper checking if a prefix is deprecated when constructing a [`CustomPrefixFormatter`] value.
+///
+/// The deprecated `check` method is selected when the prefix is deprecated,
+/// and the `check` method of [`CustomPrefixFormatter`] is selected otherwise via auto-deref.
+#[doc(hidden)]
+pub struct PrefixCheck<'a, T, const PREFIX: Spec>(CustomPrefixFormatter<'a, T, PREFIX>);
+
+impl<'a, T: DeprecatedSpec<PREFIX>, const PREFIX: Spec> PrefixCheck<'a, T, PREFIX> {
+    /// Return the wrapped [`CustomPrefixFormatter`] value, emitting a deprecation warning
+    #[deprecated(note = "this format specifier prefix is deprecated for the formatted type")]
+    pub fn check(&self) -> CustomPrefixFormatter<'a, T, PREFIX> {
+        self.0.check()
+    }
+}
+
+impl<'a, T, const PREFIX: Spec> core::ops::Deref for PrefixCheck<'a, T, PREFIX> {
+    type Target = CustomPrefixFormatter<'a, T, PREFIX>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
 
-/// Convert a format specifier to a [`u128`], used as a const-generic parameter
-pub const fn spec(s: &str) -> u128 {
+/// Wrapper selecting the formatter of a format specifier written like `{x :%N(6)}`.
+///
+/// The [`PrefixFull`] trait is selected when the value implements [`CustomFormat`] for the whole format specifier,
+/// and the [`PrefixFamily`] trait is selected otherwise via auto-deref, when the value implements [`CustomFormatPrefix`] for the prefix.
+/// The [`checked_prefix_formatter!`] macro implements a local trait selected when neither is implemented,
+/// so that the compilation error is reported for a trait whose diagnostic contains the text of the format specifier.
+#[doc(hidden)]
+pub struct Prefix<'a, T, const SPEC: Spec, const PREFIX: Spec> {
+    /// Format specifier
+    spec: &'static str,
+    /// Remaining text of the format specifier after the prefix
+    rest: &'static str,
+    /// Value to format
+    value: &'a T,
+}
+
+impl<'a, T, const SPEC: Spec, const PREFIX: Spec> Prefix<'a, T, SPEC, PREFIX> {
+    /// Construct a new [`Prefix`] value
+    pub fn new(spec: &'static str, rest: &'static str, value: &'a T) -> Self {
+        Self { spec, rest, value }
+    }
+
+    /// Return the value to format
+    pub fn value(&self) -> &'a T {
+        self.value
+    }
+}
+
+/// Select the formatter of the whole format specifier of a [`Prefix`] value
+#[doc(hidden)]
+pub trait PrefixFull<'a, T, const SPEC: Spec> {
+    /// Return the formatter of the whole format specifier
+    fn select(self) -> SpecCheck<'a, T, SPEC>;
+}
+
+impl<'a, T: CustomFormat<SPEC>, const SPEC: Spec, const PREFIX: Spec> PrefixFull<'a, T, SPEC> for &&&Prefix<'a, T, SPEC, PREFIX> {
+    fn select(self) -> SpecCheck<'a, T, SPEC> {
+        SpecCheck::new(self.spec, self.value)
+    }
+}
+
+/// Select the formatter of the prefix of a [`Prefix`] value
+#[doc(hidden)]
+pub trait PrefixFamily<'a, T, const PREFIX: Spec> {
+    /// Return the formatter of the prefix
+    fn select(self) -> PrefixCheck<'a, T, PREFIX>;
+}
+
+impl<'a, T: CustomFormatPrefix<PREFIX>, const SPEC: Spec, const PREFIX: Spec> PrefixFamily<'a, T, PREFIX> for &&Prefix<'a, T, SPEC, PREFIX> {
+    fn select(self) -> PrefixCheck<'a, T, PREFIX> {
+        PrefixCheck(CustomPrefixFormatter::new(self.rest, self.value))
+    }
+}
+
+/// Helper macro for constructing a formatter for a format specifier written like `{x :%N(6)}`.
+///
+/// The [`CustomFormat`] implementation for the whole format specifier is used if it exists,
+/// otherwise the [`CustomFormatPrefix`] implementation for the prefix is used.
+/// A compilation error containing the text of the format specifier is reported if neither is implemented.
+///
+/// The message of the compilation error is computed by the formatting macros.
+#[cfg(has_diagnostic_namespace)]
+#[doc(hidden)]
+#[macro_export]
+#[rustfmt::skip]
+macro_rules! checked_prefix_formatter {
+    ($spec:literal, $prefix:literal, $rest:literal, $message:literal, $value:expr) => {{
+        #[diagnostic::on_unimplemented(
+            message = $message,
+            label = "custom format specifier not implemented for `{Self}`",
+            note = "compile-time format specifiers are implemented with the `custom_format::compile_time::CustomFormat` trait",
+            note = "a format specifier written like `prefix(rest)` can also be implemented for its prefix with the `custom_format::compile_time::CustomFormatPrefix` trait"
+        )]
+        trait MissingCustomFormat {}
+
+        struct MissingFormatter<'a, T>(&'a T);
+
+        impl<T> MissingFormatter<'_, T> {
+            fn check(self) -> Self {
+                self
+            }
+        }
+
+        impl<T: MissingCustomFormat> ::core::fmt::Display for MissingFormatter<'_, T> {
+            fn fmt(&self, _: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        trait PrefixMissing<'a, T> {
+            fn select(self) -> MissingFormatter<'a, T>;
+        }
+
+        impl<'a, T> PrefixMissing<'a, T>
+            for &$crate::compile_time::Prefix<'a, T, { $crate::compile_time::spec($spec) }, { $crate::compile_time::spec($prefix) }>
+        {
+            fn select(self) -> MissingFormatter<'a, T> {
+                MissingFormatter(self.value())
+            }
+        }
+
+        #[allow(unused_imports)]
+        use $crate::compile_time::{PrefixFamily as _, PrefixFull as _};
+        (&&&$crate::compile_time::Prefix::<_, { $crate::compile_time::spec($spec) }, { $crate::compile_time::spec($prefix) }>::new($spec, $rest, $value))
+            .select()
+            .check()
+    }};
+}
+
+/// Helper macro for constructing a formatter for a format specifier written like `{x :%N(6)}`.
+///
+/// The `#[diagnostic]` attribute namespace is not supported, so the message of the compilation error is not used.
+#[cfg(not(has_diagnostic_namespace))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! checked_prefix_formatter {
+    ($spec:literal, $prefix:literal, $rest:literal, $message:literal, $value:expr) => {{
+        trait PrefixMissing<'a, T> {
+            fn select(self) -> $crate::compile_time::CustomPrefixFormatter<'a, T, { $crate::compile_time::spec($prefix) }>;
+        }
+
+        impl<'a, T> PrefixMissing<'a, T>
+            for &$crate::compile_time::Prefix<'a, T, { $crate::compile_time::spec($spec) }, { $crate::compile_time::spec($prefix) }>
+        {
+            fn select(self) -> $crate::compile_time::CustomPrefixFormatter<'a, T, { $crate::compile_time::spec($prefix) }> {
+                $crate::compile_time::CustomPrefixFormatter::new($rest, self.value())
+            }
+        }
+
+        #[allow(unused_imports)]
+        use $crate::compile_time::{PrefixFamily as _, PrefixFull as _};
+        (&&&$crate::compile_time::Prefix::<_, { $crate::compile_time::spec($spec) }, { $crate::compile_time::spec($prefix) }>::new($spec, $rest, $value))
+            .select()
+            .check()
+    }};
+}
+
+/// Type of the const-generic format specifier parameter of the [`CustomFormat`] trait.
+///
+/// It is a [`u128`] computed by the [`spec`] function, or the format specifier itself with the `nightly-spec-str` feature.
+#[cfg(not(feature = "nightly-spec-str"))]
+pub type Spec = u128;
+
+/// Type of the const-generic format specifier parameter of the [`CustomFormat`] trait.
+///
+/// With the `nightly-spec-str` feature, it is the format specifier itself, which can be written directly as a string literal in an implementation.
+#[cfg(feature = "nightly-spec-str")]
+pub type Spec = &'static str;
+
+/// Convert a format specifier to a [`u128`], used as a const-generic parameter.
+///
+/// Format specifiers of at most 16 bytes are stored directly in the [`u128`].
+/// Longer format specifiers are hashed, and the most significant byte of the hash is set to `0xFF`,
+/// which never appears in UTF-8 text, so that a hashed format specifier is always distinct from a stored one.
+///
+/// Two distinct long format specifiers could produce the same hash: the formatting macros report an error
+/// if this happens for format specifiers used in the same format string.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::compile_time::{spec, CustomFormat};
+///
+/// use core::fmt;
+///
+/// struct Timestamp(u64);
+///
+/// impl CustomFormat<{ spec("%Y-%m-%dT%H:%M:%S%.3fZ") }> for Timestamp {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "1970-01-01T00:00:{:02}.000Z", self.0)
+///     }
+/// }
+///
+/// assert_eq!(cfmt::format!("{0 :%Y-%m-%dT%H:%M:%S%.3fZ}", Timestamp(42)), "1970-01-01T00:00:42.000Z");
+/// ```
+#[cfg(not(feature = "nightly-spec-str"))]
+pub const fn spec(s: &str) -> Spec {
     let bytes = s.as_bytes();
     let len = s.len();
 
     if len > 16 {
-        #[allow(unconditional_panic, clippy::out_of_bounds_indexing)]
-        let _ = ["format specifier is limited to 16 bytes"][usize::MAX];
+        return hash_spec(bytes);
     }
 
     let mut result = [0u8; 16];
@@ -107,3 +1066,149 @@ pub const fn spec(s: &str) -> u128 {
 
     u128::from_le_bytes(result)
 }
+
+/// Convert a format specifier to a const-generic parameter, which is the format specifier itself with the `nightly-spec-str` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::compile_time::CustomFormat;
+///
+/// use core::fmt;
+///
+/// struct Timestamp(u64);
+///
+/// impl CustomFormat<"%Y-%m-%dT%H:%M:%S%.3fZ"> for Timestamp {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "1970-01-01T00:00:{:02}.000Z", self.0)
+///     }
+/// }
+///
+/// assert_eq!(cfmt::format!("{0 :%Y-%m-%dT%H:%M:%S%.3fZ}", Timestamp(42)), "1970-01-01T00:00:42.000Z");
+/// ```
+#[cfg(feature = "nightly-spec-str")]
+pub const fn spec(s: &'static str) -> Spec {
+    s
+}
+
+/// Text of a format specifier recovered from its const-generic parameter by the [`spec_str`] function, for debugging purposes.
+///
+/// It is displayed as the format specifier itself, or as the hexadecimal value of the parameter if the text cannot be recovered.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SpecStr {
+    /// Representation of the format specifier
+    repr: SpecStrRepr,
+}
+
+/// Representation of a [`SpecStr`] value
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SpecStrRepr {
+    /// Format specifier of at most 16 bytes, stored in the const-generic parameter
+    #[cfg(not(feature = "nightly-spec-str"))]
+    Stored([u8; 16], usize),
+    /// Hashed or invalid const-generic parameter
+    #[cfg(not(feature = "nightly-spec-str"))]
+    Value(u128),
+    /// Format specifier used as the const-generic parameter with the `nightly-spec-str` feature
+    #[cfg(feature = "nightly-spec-str")]
+    Str(&'static str),
+}
+
+impl SpecStr {
+    /// Returns the text of the format specifier, or `None` if it was hashed or if the const-generic parameter is not a valid format specifier.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.repr {
+            #[cfg(not(feature = "nightly-spec-str"))]
+            SpecStrRepr::Stored(bytes, len) => core::str::from_utf8(&bytes[..*len]).ok(),
+            #[cfg(not(feature = "nightly-spec-str"))]
+            SpecStrRepr::Value(_) => None,
+            #[cfg(feature = "nightly-spec-str")]
+            SpecStrRepr::Str(s) => Some(s),
+        }
+    }
+}
+
+impl fmt::Display for SpecStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.repr {
+            #[cfg(not(feature = "nightly-spec-str"))]
+            SpecStrRepr::Value(value) => write!(f, "{:#034x}", value),
+            _ => f.pad(self.as_str().unwrap_or_default()),
+        }
+    }
+}
+
+impl fmt::Debug for SpecStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.as_str() {
+            Some(s) => fmt::Debug::fmt(s, f),
+            None => write!(f, "SpecStr({})", self),
+        }
+    }
+}
+
+/// Recover the text of a format specifier from its const-generic parameter, for debugging purposes.
+///
+/// Format specifiers of at most 16 bytes are recovered from the [`u128`], without their trailing NUL bytes.
+/// Longer format specifiers are hashed by the [`spec`] function and cannot be recovered.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format::compile_time::{spec, spec_str};
+///
+/// assert_eq!(spec_str(spec("%Y")).as_str(), Some("%Y"));
+/// assert_eq!(spec_str(spec("%Y")).to_string(), "%Y");
+///
+/// let hashed = spec_str(spec("%Y-%m-%dT%H:%M:%S%.3fZ"));
+/// assert_eq!(hashed.as_str(), None);
+/// assert_eq!(hashed.to_string(), "0xff99034ceca454be14a8dcfdfee7bbe3");
+/// ```
+#[cfg(not(feature = "nightly-spec-str"))]
+pub fn spec_str(spec: Spec) -> SpecStr {
+    let bytes = spec.to_le_bytes();
+    let len = bytes.iter().rposition(|&x| x != 0).map_or(0, |index| index + 1);
+
+    let repr = match core::str::from_utf8(&bytes[..len]) {
+        Ok(_) => SpecStrRepr::Stored(bytes, len),
+        Err(_) => SpecStrRepr::Value(spec),
+    };
+
+    SpecStr { repr }
+}
+
+/// Recover the text of a format specifier from its const-generic parameter, for debugging purposes.
+///
+/// With the `nightly-spec-str` feature, the const-generic parameter is the format specifier itself.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format::compile_time::{spec, spec_str};
+///
+/// assert_eq!(spec_str(spec("%Y")).as_str(), Some("%Y"));
+/// assert_eq!(spec_str(spec("%Y-%m-%dT%H:%M:%S%.3fZ")).to_string(), "%Y-%m-%dT%H:%M:%S%.3fZ");
+/// ```
+#[cfg(feature = "nightly-spec-str")]
+pub fn spec_str(spec: Spec) -> SpecStr {
+    SpecStr { repr: SpecStrRepr::Str(spec) }
+}
+
+/// Hash a long format specifier with the 128-bit FNV-1a algorithm, setting the most significant byte to `0xFF`
+#[cfg(not(feature = "nightly-spec-str"))]
+const fn hash_spec(bytes: &[u8]) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    let mut hash = OFFSET_BASIS;
+
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u128;
+        hash = hash.wrapping_mul(PRIME);
+        i += 1;
+    }
+
+    hash | (0xFF << 120)
+}
@@ -0,0 +1,110 @@
+//! Macros for the [`defmt`](https://docs.rs/defmt) crate, with custom format specifiers in the message.
+//!
+//! Since custom format specifiers are implemented with [`core::fmt`], the message is rendered on the target
+//! into a stack buffer of [`MESSAGE_CAPACITY`] bytes, and is transmitted as a `{=str}` parameter of the `defmt` wire format.
+//! Longer messages are truncated. The message is only rendered if the log level is enabled.
+//!
+//! With the `runtime` feature, the [`runtime::CustomFormatter`](crate::runtime::CustomFormatter) type also implements [`defmt::Format`](defmt_crate::Format),
+//! so that values implementing [`runtime::CustomFormat`](crate::runtime::CustomFormat) can be passed to the `defmt` macros directly.
+//!
+//! The `defmt` crate must also be a dependency of the calling crate, since its macros reference it by name.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use custom_format as cfmt;
+//! use custom_format::runtime::CustomFormatter;
+//!
+//! let sensor = format_args!("temp0");
+//! cfmt::defmt::info!("reading {sensor :<upper>}: {}", 21);
+//! cfmt::defmt::println!("{0 :<upper>}", format_args!("ready"));
+//!
+//! defmt::info!("reading {}", CustomFormatter::new("upper", &sensor));
+//! ```
+
+use crate::buffer::FixedString;
+
+use core::fmt;
+
+#[doc(inline)]
+pub use crate::{defmt_debug as debug, defmt_error as error, defmt_info as info, defmt_println as println, defmt_trace as trace, defmt_warn as warn};
+
+/// Capacity in bytes of the buffer of a rendered message
+pub const MESSAGE_CAPACITY: usize = 256;
+
+/// Render formatted data into a stack buffer, truncating the output if the buffer is full
+#[doc(hidden)]
+pub fn render(args: fmt::Arguments) -> FixedString<MESSAGE_CAPACITY> {
+    let mut message = FixedString::new();
+    let _ = fmt::write(&mut message, args);
+    message
+}
+
+#[cfg(feature = "runtime")]
+impl<T: crate::runtime::CustomFormat> crate::defmt_crate::Format for crate::runtime::CustomFormatter<'_, T> {
+    fn format(&self, f: crate::defmt_crate::Formatter) {
+        crate::defmt_crate::write!(f, "{=str}", render(format_args!("{}", self)).as_str())
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! defmt_inner {
+    ([$lvl:ident], $($arg:tt)+) => {
+        $crate::defmt_crate::$lvl!("{=str}", $crate::defmt::render($crate::format_args!($($arg)+)).as_str())
+    };
+}
+
+/// Prints a message unconditionally
+#[doc(hidden)]
+#[macro_export]
+macro_rules! defmt_println {
+    ($($arg:tt)+) => {
+        $crate::defmt_inner!([println], $($arg)+)
+    };
+}
+
+/// Logs a message at the error level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! defmt_error {
+    ($($arg:tt)+) => {
+        $crate::defmt_inner!([error], $($arg)+)
+    };
+}
+
+/// Logs a message at the warn level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! defmt_warn {
+    ($($arg:tt)+) => {
+        $crate::defmt_inner!([warn], $($arg)+)
+    };
+}
+
+/// Logs a message at the info level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! defmt_info {
+    ($($arg:tt)+) => {
+        $crate::defmt_inner!([info], $($arg)+)
+    };
+}
+
+/// Logs a message at the debug level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! defmt_debug {
+    ($($arg:tt)+) => {
+        $crate::defmt_inner!([debug], $($arg)+)
+    };
+}
+
+/// Logs a message at the trace level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! defmt_trace {
+    ($($arg:tt)+) => {
+        $crate::defmt_inner!([trace], $($arg)+)
+    };
+}
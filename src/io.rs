@@ -0,0 +1,107 @@
+//! Provides a global writer for the [`print!`](crate::print) and [`println!`](crate::println) macros on `no_std` targets.
+//!
+//! With the `no-std-print` feature, the printing macros write to the global writer instead of the standard output,
+//! so that formatted output with custom format specifiers can be directed to a serial port or a debug probe.
+//!
+//! The output is discarded if no global writer is registered.
+//! Like with the standard output, the printing macros panic if a formatting trait implementation or the global writer returns an error.
+
+use core::fmt;
+
+/// Trait for a writer which can be shared between threads, registered with [`set_global_writer`]
+///
+/// Unlike [`fmt::Write`], writing only requires a shared reference, so the implementation is responsible for synchronization.
+pub trait GlobalWriter: Sync {
+    /// Writes a string slice into this writer, returning whether the write succeeded.
+    fn write_str(&self, s: &str) -> fmt::Result;
+}
+
+/// Error returned by [`set_global_writer`] if a global writer is already registered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetGlobalWriterError(());
+
+impl fmt::Display for SetGlobalWriterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a global writer is already registered")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SetGlobalWriterError {}
+
+/// Registered global writer
+static GLOBAL_WRITER: spin::Once<&'static dyn GlobalWriter> = spin::Once::new();
+
+/// Register the global writer used by the [`print!`](crate::print) and [`println!`](crate::println) macros.
+///
+/// The global writer can only be registered once.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::io::GlobalWriter;
+///
+/// use core::fmt;
+/// use core::sync::atomic::{AtomicUsize, Ordering};
+///
+/// struct Uart {
+///     written: AtomicUsize,
+/// }
+///
+/// impl GlobalWriter for Uart {
+///     fn write_str(&self, s: &str) -> fmt::Result {
+///         // Write to the UART registers
+///         self.written.fetch_add(s.len(), Ordering::Relaxed);
+///         Ok(())
+///     }
+/// }
+///
+/// static UART: Uart = Uart { written: AtomicUsize::new(0) };
+///
+/// cfmt::io::set_global_writer(&UART).unwrap();
+/// assert!(cfmt::io::set_global_writer(&UART).is_err());
+///
+/// cfmt::println!("{0 :<upper>}", format_args!("ready"));
+/// assert_eq!(UART.written.load(Ordering::Relaxed), 6);
+/// ```
+pub fn set_global_writer(writer: &'static dyn GlobalWriter) -> Result<(), SetGlobalWriterError> {
+    let mut registered = false;
+
+    GLOBAL_WRITER.call_once(|| {
+        registered = true;
+        writer
+    });
+
+    if registered {
+        Ok(())
+    } else {
+        Err(SetGlobalWriterError(()))
+    }
+}
+
+/// Returns the registered global writer, if any
+pub fn global_writer() -> Option<&'static dyn GlobalWriter> {
+    GLOBAL_WRITER.get().copied()
+}
+
+/// Adapter implementing [`fmt::Write`] for the global writer, discarding the output if no global writer is registered
+struct Adapter(Option<&'static dyn GlobalWriter>);
+
+impl fmt::Write for Adapter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.0 {
+            Some(writer) => writer.write_str(s),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Write formatted data to the global writer, used by the [`print!`](crate::print) and [`println!`](crate::println) macros
+#[doc(hidden)]
+pub fn print_fmt(args: fmt::Arguments) {
+    // Format the arguments even without a global writer, so that formatting errors are always reported
+    if fmt::write(&mut Adapter(global_writer()), args).is_err() {
+        panic!("failed printing to the global writer")
+    }
+}
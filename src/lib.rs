@@ -2,6 +2,8 @@
 #![forbid(unsafe_code)]
 #![deny(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(feature = "nightly-spec-str", feature(adt_const_params, unsized_const_params))]
+#![cfg_attr(feature = "nightly-spec-str", allow(incomplete_features))]
 
 //! This crate extends the standard formatting syntax with custom format specifiers, by providing custom formatting macros.
 //!
@@ -9,56 +11,343 @@
 //! It also supports [format args capture](https://blog.rust-lang.org/2022/01/13/Rust-1.58.0.html#captured-identifiers-in-format-strings)
 //! even on older versions of Rust, since it manually adds the named parameter if missing.
 //!
+//! Standard formatting flags (fill, alignment, sign, `#`, `0`, width and precision) can be written before the separator, like in `{x:★>10.3 :%Y}`.
+//! They are applied to the [`Formatter`](core::fmt::Formatter) passed to the custom formatting implementation, which can use them or ignore them.
+//!
+//! The text after the separator always belongs to the custom format specifier, so `{x :>10:%Y}` uses the format specifier `>10:%Y`.
+//! Ending the standard flags with `!`, like in `{x:*>10! :%Y}`, pads the whole custom output with the fill, alignment and width flags,
+//! by wrapping it in [`Padded`]. The custom formatting implementation then receives a formatter without flags.
+//! Only the fill, alignment and width flags can be used in this case.
+//!
 //! This library comes in two flavors, corresponding to the following features:
 //!
 //! - `compile-time` (*enabled by default*)
 //!
-//!     The set of possible custom format specifiers is defined at compilation, so invalid specifiers can be checked at compile-time.
-//!     This allows the library to have the same performance as when using the standard library formatting traits.
-//!     See the [`compile_time::CustomFormat`] trait.
+//!   The set of possible custom format specifiers is defined at compilation, so invalid specifiers can be checked at compile-time.
+//!   This allows the library to have the same performance as when using the standard library formatting traits.
+//!   See the [`compile_time::CustomFormat`] trait.
 //!
 //! - `runtime` (*enabled by default*)
 //!
-//!     The formatting method dynamically checks the format specifier at runtime for each invocation.
-//!     This is a slower version, but has a lower MSRV for greater compatibility.
-//!     See the [`runtime::CustomFormat`] trait.
+//!   The formatting method dynamically checks the format specifier at runtime for each invocation.
+//!   This is a slower version, but has a lower MSRV for greater compatibility.
+//!   See the [`runtime::CustomFormat`] trait.
+//!
+//! With the `nightly-spec-str` feature, which requires a nightly compiler, the const-generic parameter of the [`compile_time::CustomFormat`] trait
+//! is the format specifier itself, like in `impl CustomFormat<"%Y"> for Date`, instead of a [`u128`] computed with [`compile_time::spec`].
+//!
+//! A compile-time specifier written as `prefix(rest)`, like in `{x :%N(6)}`, uses the implementation for the whole format specifier if it exists.
+//! Otherwise it is checked at compile-time for its prefix only, and the text between the parentheses is passed to the formatting implementation.
+//! See the [`compile_time::CustomFormatPrefix`] trait.
+//!
+//! A compile-time specifier can be followed by `|<>`, like in `{x :%Y|<>}`, so that the value is formatted with its [`runtime::CustomFormat`] implementation
+//! if the format specifier is not implemented with the [`compile_time::CustomFormat`] trait, instead of failing to compile.
+//! This is useful for migrating a type from runtime to compile-time format specifiers incrementally.
+//!
+//! The empty format specifiers `{x :}` and `{x :<>}` fall back to the [`Display`](core::fmt::Display) implementation of the value
+//! if it doesn't implement the corresponding custom formatting trait, so that types don't need a boilerplate implementation for them.
+//!
+//! A runtime specifier can be followed by `|?`, like in `{x :<%Y>|?}`, so that the value is formatted with its [`Debug`](core::fmt::Debug) implementation
+//! if the custom formatting fails. See [`runtime::DebugFallback`].
+//!
+//! A runtime specifier ending with `?`, like in `{x :<%Y?>}`, quotes and escapes the custom output like a [`Debug`](core::fmt::Debug) string.
+//! The `{x :<?>}` specifier is passed unchanged to the formatting implementation. See [`runtime::DebugQuoted`].
+//!
+//! A runtime specifier can reference a pair of arguments separated by a comma, like in `{a, b :<ratio>}`,
+//! so that the two values are formatted together. See the [`runtime::CustomFormat2`] trait.
+//!
+//! The format string can be loaded from a file with `include_str!("path")`, which is read by the procedural macro.
+//! Like with the standard macro, a relative path is resolved from the directory of the invoking source file,
+//! or from the `CARGO_MANIFEST_DIR` directory before Rust 1.88.
+//!
+//! Identifiers in the format string must be normalized in Unicode NFC, like with the standard formatting macros.
+//! Custom format specifiers are not normalized: they are passed byte-for-byte to the formatting implementation.
+//!
+//! The [`builtins`] module, available with the `runtime` feature, provides ready-made custom formatters for common use cases.
+//! Some of them require the `alloc` feature.
+//!
+//! The [`style`] module, available with the `compile-time` feature, provides ANSI styling with format specifiers like `{msg :red,bold}`,
+//! which are used by the [`cprint!`] and [`cprintln!`] macros.
+//!
+//! With the `alloc` feature, a [`runtime::Template`] parses a format string at runtime, for rendering dynamic templates without macros.
+//!
+//! The kind of custom format specifiers accepted by a macro invocation can be restricted with an `only` option before the format string,
+//! like in `format!(only = "compile-time", "{x :%Y}")`, which is a compilation error if the format string contains a runtime specifier.
+//! Symmetrically, `only = "runtime"` rejects compile-time specifiers. This can be used for enforcing a policy in a wrapper macro.
+//!
+//! With the `log`, `tracing` and `defmt` features, the [`log`], [`tracing`] and [`defmt`] modules provide macros for the corresponding crates,
+//! with custom format specifiers in the message.
+//!
+//! With the `no-std-print` feature, the [`print!`] and [`println!`] macros write to a global writer registered with
+//! [`io::set_global_writer`] instead of the standard output, which is useful on `no_std` targets.
+//!
+//! With the `wasm` feature, the [`console_log!`] and [`console_error!`] macros log to the web console on WebAssembly targets.
+//!
+//! With the `std` feature, the [`write_bytes!`] macro formats runtime specifiers with the [`bytes::CustomFormatBytes`] trait,
+//! which can write non-UTF8 output like raw binary frames to a [`std::io::Write`] writer.
+//!
+//! With the `std` feature, an ambient specifier like `{:<now>}` doesn't reference any argument,
+//! and is formatted by a provider registered for the current thread with [`runtime::set_provider`].
+
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+pub extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "log")]
+#[doc(hidden)]
+pub extern crate log as log_crate;
+
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub extern crate tracing as tracing_crate;
+
+#[cfg(feature = "defmt")]
+#[doc(hidden)]
+pub extern crate defmt as defmt_crate;
+
+pub mod buffer;
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod bytes;
 
 #[cfg(feature = "compile-time")]
 #[cfg_attr(docsrs, doc(cfg(feature = "compile-time")))]
 pub mod compile_time;
 
+#[cfg(feature = "compile-time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compile-time")))]
+pub mod style;
+
 #[cfg(feature = "runtime")]
 #[cfg_attr(docsrs, doc(cfg(feature = "runtime")))]
 pub mod runtime;
 
+#[cfg(feature = "runtime")]
+#[cfg_attr(docsrs, doc(cfg(feature = "runtime")))]
+pub mod builtins;
+
+#[cfg(feature = "log")]
+#[cfg_attr(docsrs, doc(cfg(feature = "log")))]
+pub mod log;
+
+#[cfg(feature = "tracing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+pub mod tracing;
+
+#[cfg(feature = "defmt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "defmt")))]
+pub mod defmt;
+
+#[cfg(feature = "no-std-print")]
+#[cfg_attr(docsrs, doc(cfg(feature = "no-std-print")))]
+pub mod io;
+
+#[cfg(feature = "wasm")]
+#[doc(hidden)]
+pub mod wasm;
+
 #[doc(hidden)]
 pub use custom_format_macros;
 
+/// Implements [`compile_time::CustomFormat`] for the methods of an impl block mapped to a format specifier.
+///
+/// A method named `spec_X` is mapped to the `"%X"` format specifier by naming convention,
+/// and a `#[spec("...")]` attribute maps a method to any format specifier.
+/// Mapped methods must have the signature `fn(&self, f: &mut fmt::Formatter) -> fmt::Result`.
+///
+/// If the crate is renamed, its path can be provided with `#[specs(crate = path)]`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::fmt;
+///
+/// struct Date {
+///     year: i32,
+///     month: u8,
+/// }
+///
+/// #[cfmt::specs(crate = cfmt)]
+/// impl Date {
+///     fn spec_Y(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "{:04}", self.year)
+///     }
+///
+///     #[spec("month")]
+///     fn month(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "{:02}", self.month)
+///     }
+/// }
+///
+/// let date = Date { year: 2023, month: 7 };
+/// assert_eq!(cfmt::format!("{date :%Y}-{date :month}"), "2023-07");
+/// ```
+#[cfg(feature = "compile-time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compile-time")))]
+pub use custom_format_macros::specs;
+
+/// Declares the closed set of format specifiers supported by a type, with one formatting function for each format specifier.
+///
+/// Each arm maps a format specifier to the name of an associated constant and to a closure or a function
+/// with the signature `fn(&Self, &mut fmt::Formatter) -> fmt::Result`. The macro generates:
+///
+/// - a `SUPPORTED_SPECS: &[&str]` associated constant, listing the format specifiers in declaration order,
+/// - with the `compile-time` feature, an associated constant of type [`compile_time::Spec`] for each format specifier,
+///   and the corresponding [`compile_time::CustomFormat`] implementations,
+/// - with the `runtime` feature, a [`runtime::CustomFormat`] implementation dispatching on the same format specifiers,
+///   and a `check_spec` associated function returning a [`runtime::UnsupportedSpec`] error which lists the supported format specifiers.
+///
+/// Generic parameters of the implementation can be provided in brackets after the `impl` keyword, like in `impl[T: fmt::Display] Wrapper<T>`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::fmt;
+///
+/// struct Date {
+///     year: i32,
+///     month: u8,
+/// }
+///
+/// cfmt::define_specs!(Date, match spec {
+///     "%Y" as YEAR => |this, f| write!(f, "{:04}", this.year),
+///     "%m" as MONTH => |this, f| write!(f, "{:02}", this.month),
+/// });
+///
+/// let date = Date { year: 2023, month: 7 };
+///
+/// assert_eq!(Date::SUPPORTED_SPECS, ["%Y", "%m"]);
+/// assert_eq!(Date::YEAR, cfmt::compile_time::spec("%Y"));
+/// assert_eq!(cfmt::format!("{date :%Y}-{date :%m}, {date :<%m>}"), "2023-07, 07");
+///
+/// let error = Date::check_spec("%q").unwrap_err();
+/// assert_eq!(error.to_string(), "unsupported format specifier `%q`, expected one of: `%Y`, `%m`");
+/// ```
+#[macro_export]
+macro_rules! define_specs {
+    (@supported [$($generics:tt)*] $ty:ty, $($spec:literal),*) => {
+        impl<$($generics)*> $ty {
+            /// Supported format specifiers
+            pub const SUPPORTED_SPECS: &'static [&'static str] = &[$($spec),*];
+        }
+    };
+    (impl $generics:tt $ty:ty, match spec { $($spec:literal as $name:ident => $func:expr),* $(,)? }) => {
+        $crate::define_specs!(@supported $generics $ty, $($spec),*);
+        $crate::define_specs_compile_time!($generics $ty, $($spec, $name, $func;)*);
+        $crate::define_specs_runtime!($generics $ty, $($spec, $func;)*);
+    };
+    ($ty:ty, match spec { $($spec:literal as $name:ident => $func:expr),* $(,)? }) => {
+        $crate::define_specs!(impl[] $ty, match spec { $($spec as $name => $func),* });
+    };
+}
+
+#[cfg(feature = "compile-time")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! define_specs_compile_time {
+    (@consts [$($generics:tt)*] $ty:ty, $($spec:literal, $name:ident;)*) => {
+        impl<$($generics)*> $ty {
+            $(
+                #[doc = ::core::concat!("Compile-time value of the `", $spec, "` format specifier")]
+                pub const $name: $crate::compile_time::Spec = $crate::compile_time::spec($spec);
+            )*
+        }
+    };
+    ($generics:tt $ty:ty, $($spec:literal, $name:ident, $func:expr;)*) => {
+        $crate::define_specs_compile_time!(@consts $generics $ty, $($spec, $name;)*);
+        $($crate::impl_custom_format!(@impl $generics $ty, $spec, $func);)*
+    };
+}
+
+#[cfg(not(feature = "compile-time"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! define_specs_compile_time {
+    ($($tt:tt)*) => {};
+}
+
+#[cfg(feature = "runtime")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! define_specs_runtime {
+    ([$($generics:tt)*] $ty:ty, $($spec:literal, $func:expr;)*) => {
+        impl<$($generics)*> $ty {
+            /// Check if a runtime format specifier is supported, returning an error listing the supported format specifiers otherwise
+            pub fn check_spec(spec: &str) -> ::core::result::Result<(), $crate::runtime::UnsupportedSpec<'_>> {
+                $crate::runtime::UnsupportedSpec::check(spec, Self::SUPPORTED_SPECS)
+            }
+        }
+
+        impl<$($generics)*> $crate::runtime::CustomFormat for $ty {
+            fn empty_is_valid(&self) -> bool {
+                false $(|| $spec.is_empty())*
+            }
+
+            fn fmt(&self, f: &mut ::core::fmt::Formatter, spec: &str) -> ::core::fmt::Result {
+                match spec {
+                    $($spec => ($func as fn(&Self, &mut ::core::fmt::Formatter) -> ::core::fmt::Result)(self, f),)*
+                    _ => ::core::result::Result::Err(::core::fmt::Error),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "runtime"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! define_specs_runtime {
+    ($($tt:tt)*) => {};
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! parse_args {
-    ([$($macro:tt)*], [$($first_arg:expr)?], [$($result:expr),*], $id:ident = $expr:expr, $($arg:tt)*) => {{
-        $crate::parse_args!([$($macro)*], [$($first_arg)?], [$($result,)* ($id) = $expr], $($arg)*)
+    ([$($macro:tt)*], [$($first_arg:expr)?], [$($option:tt)*], [$($result:expr),*], $id:ident = $expr:expr, $($arg:tt)*) => {{
+        $crate::parse_args!([$($macro)*], [$($first_arg)?], [$($option)*], [$($result,)* ($id) = $expr], $($arg)*)
     }};
-    ([$($macro:tt)*], [$($first_arg:expr)?], [$($result:expr),*], $expr:expr, $($arg:tt)*) => {{
-        $crate::parse_args!([$($macro)*], [$($first_arg)?], [$($result,)* $expr], $($arg)*)
+    ([$($macro:tt)*], [$($first_arg:expr)?], [$($option:tt)*], [$($result:expr),*], $expr:expr, $($arg:tt)*) => {{
+        $crate::parse_args!([$($macro)*], [$($first_arg)?], [$($option)*], [$($result,)* $expr], $($arg)*)
     }};
-    ([$($macro:tt)*], [$($first_arg:expr)?], [$($result:expr),*], $(,)?) => {{
-        $crate::custom_format_macros::fmt!($crate, [$($macro)*], [$($first_arg)?], [$($result),*])
+    ([$($macro:tt)*], [$($first_arg:expr)?], [$($option:tt)*], [$($result:expr),*], $(,)?) => {{
+        $crate::custom_format_macros::fmt!($crate, [$($macro)*], [$($first_arg)?], [$($result),*], [$($option)*])
     }};
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! fmt_inner {
-    ([$($macro:tt)*], [$($first_arg:expr)?], ) => {{
+    ([$($macro:tt)*], [$($first_arg:expr)?], $(only = $only:literal,)? ) => {{
         compile_error!("requires at least a format string argument")
     }};
-    ([$($macro:tt)*], [$($first_arg:expr)?], $fmt:literal) => {{
-        $crate::custom_format_macros::fmt!($crate, [$($macro)*], [$($first_arg)?], [$fmt])
+    ([$($macro:tt)*], [$($first_arg:expr)?], only = $only:literal, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$($macro)*], [$($first_arg)?], [only = $only], $($arg)*)
+    }};
+    ([$($macro:tt)*], [$($first_arg:expr)?], $fmt:literal $($arg:tt)*) => {{
+        $crate::fmt_inner!([$($macro)*], [$($first_arg)?], [], $fmt $($arg)*)
+    }};
+    ([$($macro:tt)*], [$($first_arg:expr)?], include_str!($path:literal) $($arg:tt)*) => {{
+        $crate::fmt_inner!([$($macro)*], [$($first_arg)?], [], include_str!($path) $($arg)*)
     }};
-    ([$($macro:tt)*], [$($first_arg:expr)?], $fmt:literal, $($arg:tt)*) => {{
-        $crate::parse_args!([$($macro)*], [$($first_arg)?], [$fmt], $($arg)*,)
+    ([$($macro:tt)*], [$($first_arg:expr)?], [$($option:tt)*], $fmt:literal) => {{
+        $crate::custom_format_macros::fmt!($crate, [$($macro)*], [$($first_arg)?], [$fmt], [$($option)*])
+    }};
+    ([$($macro:tt)*], [$($first_arg:expr)?], [$($option:tt)*], $fmt:literal, $($arg:tt)*) => {{
+        $crate::parse_args!([$($macro)*], [$($first_arg)?], [$($option)*], [$fmt], $($arg)*,)
+    }};
+    ([$($macro:tt)*], [$($first_arg:expr)?], [$($option:tt)*], include_str!($path:literal)) => {{
+        $crate::custom_format_macros::fmt!($crate, [$($macro)*], [$($first_arg)?], [(include_str!($path))], [$($option)*])
+    }};
+    ([$($macro:tt)*], [$($first_arg:expr)?], [$($option:tt)*], include_str!($path:literal), $($arg:tt)*) => {{
+        $crate::parse_args!([$($macro)*], [$($first_arg)?], [$($option)*], [(include_str!($path))], $($arg)*,)
     }};
 }
 
@@ -146,7 +435,9 @@ macro_rules! fmt_inner {
 ///
 /// which reevaluates the input arguments if they are used several times in the format string.
 ///
-/// To avoid unnecessary reevaluations, we can store the expression result in a variable beforehand:
+/// The [`owned_format_args!`] macro can be used instead, which evaluates each argument once and owns the resulting values.
+///
+/// To avoid unnecessary reevaluations, we can also store the expression result in a variable beforehand:
 ///
 /// ```rust
 /// # use custom_format as cfmt;
@@ -186,78 +477,1126 @@ macro_rules! format_args {
     }};
 }
 
-/// Creates a `String` using interpolation of runtime expressions
+/// Constructs parameters for the other string-formatting macros, with a newline appended to the format string
+///
+/// This is the equivalent of the `format_args!` macro for newline-terminated output, like with the `println!` macro.
+/// It can be used for implementing custom print-like macros.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let value = "value";
+/// assert_eq!(format!("{}", cfmt::format_args_nl!("{value :<upper>}", value = format_args!("{}", value))), "VALUE\n");
+/// assert_eq!(format!("{}", cfmt::format_args_nl!()), "\n");
+/// ```
 #[macro_export]
-macro_rules! format {
+macro_rules! format_args_nl {
+    () => {{
+        ::core::format_args!("\n")
+    }};
+    (only = $only:literal, $($arg:tt)*) => {{
+        $crate::fmt_inner!([::core::format_args!], [], [only = $only, newline], $($arg)*)
+    }};
     ($($arg:tt)*) => {{
-        $crate::fmt_inner!([::std::format!], [], $($arg)*)
+        $crate::fmt_inner!([::core::format_args!], [], [newline], $($arg)*)
     }};
 }
 
-/// Prints to the standard output
+/// Creates a value implementing [`Display`](core::fmt::Display), which formats its arguments only when it is written
+///
+/// The arguments are moved into a closure, and are evaluated each time the value is written.
+/// Unlike the [`format_args!`] macro, the returned value doesn't borrow temporary values, so it can be stored or returned from a function.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::fmt;
+///
+/// struct Hex(u8);
+///
+/// impl cfmt::runtime::CustomFormat for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter, _: &str) -> fmt::Result {
+///         write!(f, "{:#04x}", self.0)
+///     }
+/// }
+///
+/// fn message(id: u8) -> impl fmt::Display {
+///     let value = Hex(id);
+///     cfmt::lazy_format!("id: {value :<x>}")
+/// }
+///
+/// assert_eq!(message(10).to_string(), "id: 0x0a");
+/// ```
 #[macro_export]
-macro_rules! print {
+macro_rules! lazy_format {
     ($($arg:tt)*) => {{
-        $crate::fmt_inner!([::std::print!], [], $($arg)*)
+        $crate::LazyFormat::new(move |f: &mut ::core::fmt::Formatter| $crate::fmt_inner!([::core::write!], [f], $($arg)*))
     }};
 }
 
-/// Prints to the standard output, with a newline
+/// Creates a value implementing [`Display`](core::fmt::Display), which owns its evaluated arguments
+///
+/// Each argument expression is evaluated exactly once when calling the macro, and its value is moved into the returned value,
+/// like the implicitly captured identifiers. Unlike the [`format_args!`] macro, arguments used several times in the format string
+/// are not reevaluated, and the returned value doesn't borrow temporary values, so it can be stored or returned from a function.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::cell::Cell;
+/// use core::fmt;
+///
+/// struct Hex(u8);
+///
+/// impl cfmt::runtime::CustomFormat for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter, _: &str) -> fmt::Result {
+///         write!(f, "{:#04x}", self.0)
+///     }
+/// }
+///
+/// let calls = Cell::new(0);
+/// let call = || {
+///     calls.set(calls.get() + 1);
+///     Hex(42)
+/// };
+///
+/// let args = cfmt::owned_format_args!("{0 :<x>}, {0 :<x>}", call());
+///
+/// assert_eq!(args.to_string(), "0x2a, 0x2a");
+/// assert_eq!(args.to_string(), "0x2a, 0x2a");
+/// assert_eq!(calls.get(), 1);
+/// ```
 #[macro_export]
-macro_rules! println {
-    () => {{
-        ::std::println!()
+macro_rules! owned_format_args {
+    (only = $only:literal, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::owned_format_args_fmt!], [], [only = $only, owned], $($arg)*)
     }};
     ($($arg:tt)*) => {{
-        $crate::fmt_inner!([::std::println!], [], $($arg)*)
+        $crate::fmt_inner!([$crate::owned_format_args_fmt!], [], [owned], $($arg)*)
     }};
 }
 
-/// Prints to the standard error
+#[doc(hidden)]
 #[macro_export]
-macro_rules! eprint {
-    ($($arg:tt)*) => {{
-        $crate::fmt_inner!([::std::eprint!], [], $($arg)*)
-    }};
+macro_rules! owned_format_args_fmt {
+    ($($arg:tt)*) => {
+        $crate::LazyFormat::new(move |f: &mut ::core::fmt::Formatter| ::core::write!(f, $($arg)*))
+    };
 }
 
-/// Prints to the standard error, with a newline
+/// Value formatting its arguments only when it is written, returned by the [`lazy_format!`] and [`owned_format_args!`] macros
+#[derive(Clone, Copy)]
+pub struct LazyFormat<F>(F);
+
+impl<F: Fn(&mut core::fmt::Formatter) -> core::fmt::Result> LazyFormat<F> {
+    /// Construct a new [`LazyFormat`] value from a closure writing to the formatter
+    pub fn new(f: F) -> Self {
+        Self(f)
+    }
+}
+
+impl<F> core::fmt::Debug for LazyFormat<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("LazyFormat").finish_non_exhaustive()
+    }
+}
+
+impl<F: Fn(&mut core::fmt::Formatter) -> core::fmt::Result> core::fmt::Display for LazyFormat<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        (self.0)(f)
+    }
+}
+
+/// Wrapper padding the output of a value with the fill, alignment and width flags of the formatter.
+///
+/// The formatting macros use it for standard flags ending with `!`, like in `{x:>10! :%Y}`,
+/// and a custom formatting implementation can use it for handling the standard flags written before the custom separator, like in `{x:>10 :%Y}`.
+///
+/// The wrapped value is formatted without flags, and the output is left-aligned by default, like a string.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::runtime::{self, CustomFormat};
+///
+/// use core::fmt;
+///
+/// struct Hex(u8);
+///
+/// impl CustomFormat for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+///         match spec {
+///             "x" => write!(f, "{:#x}", self.0),
+///             "X" => fmt::Display::fmt(&cfmt::Padded(format_args!("{:#X}", self.0)), f),
+///             _ => Err(fmt::Error),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(cfmt::format!("[{0:>6 :<x>}] [{0:>6 :<X>}] [{0:*<6 :<X>}]", Hex(0xAB)), "[0xab] [  0xAB] [0xAB**]");
+/// assert_eq!(format!("[{:^8}]", cfmt::Padded(runtime::CustomFormatter::new("x", &Hex(0xAB)))), "[  0xab  ]");
+/// assert_eq!(cfmt::format!("[{0:^8! :<x>}]", Hex(0xAB)), "[  0xab  ]");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Padded<T>(pub T);
+
+impl<T: core::fmt::Display> core::fmt::Display for Padded<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        pad_display(f, &self.0)
+    }
+}
+
+/// Format a value, padding the whole output with the fill, alignment and width of the formatter
+fn pad_display(f: &mut core::fmt::Formatter, value: &dyn core::fmt::Display) -> core::fmt::Result {
+    let chars = buffer::CountWrite::measure(format_args!("{}", value)).chars();
+
+    let padding = match f.width() {
+        Some(width) if width > chars => width - chars,
+        _ => return write!(f, "{}", value),
+    };
+
+    let (pre_padding, post_padding) = match f.align() {
+        Some(core::fmt::Alignment::Right) => (padding, 0),
+        Some(core::fmt::Alignment::Center) => (padding / 2, (padding + 1) / 2),
+        Some(core::fmt::Alignment::Left) | None => (0, padding),
+    };
+
+    let fill = f.fill();
+
+    for _ in 0..pre_padding {
+        core::fmt::Write::write_char(f, fill)?;
+    }
+
+    write!(f, "{}", value)?;
+
+    for _ in 0..post_padding {
+        core::fmt::Write::write_char(f, fill)?;
+    }
+
+    Ok(())
+}
+
+/// Creates a `String` using interpolation of runtime expressions
 #[macro_export]
-macro_rules! eprintln {
-    () => {{
-        ::std::eprintln!()
-    }};
+macro_rules! format {
     ($($arg:tt)*) => {{
-        $crate::fmt_inner!([::std::eprintln!], [], $($arg)*)
+        $crate::fmt_inner!([::std::format!], [], $($arg)*)
     }};
 }
 
-/// Writes formatted data into a buffer
+/// Creates a `&'static str` by formatting literal arguments at compile-time
+///
+/// All arguments must be string, character, boolean, integer or floating-point literals, and the supported format specifiers
+/// are `{}` and `{:?}`, plus `{:x}`, `{:X}`, `{:o}` and `{:b}` for non-negative integers. Other arguments and format specifiers,
+/// like captured identifiers or custom format specifiers, are a compilation error.
+///
+/// Literals are formatted like at runtime with the type given by their suffix, so `0.1f32` is formatted as an [`f32`],
+/// and a suffixed integer literal out of range for its type, like `300u8` or `-3u32`, is a compilation error.
+///
+/// The macro expands to a `&'static str` constant expression, so it can be used to initialize a constant.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// const BANNER: &str = cfmt::concat_format!("{} v{}.{} ({:?}) [{:x}]", "app", 1, 2, 'x', 255);
+///
+/// assert_eq!(BANNER, "app v1.2 ('x') [ff]");
+/// ```
+///
+/// The following statement doesn't compile since `x` is not a literal:
+///
+/// ```rust,compile_fail
+/// # use custom_format as cfmt;
+/// let x = 1;
+/// let s = cfmt::concat_format!("{}", x);
+/// ```
 #[macro_export]
-macro_rules! write {
-    ($dst:expr, $($arg:tt)*) => {{
-        $crate::fmt_inner!([::core::write!], [$dst], $($arg)*)
+macro_rules! concat_format {
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([], [], [concat], $($arg)*)
     }};
 }
 
-/// Write formatted data into a buffer, with a newline appended
+/// Creates a `Cow<'static, str>` using interpolation of runtime expressions, without allocation for literal-only format strings
+///
+/// When the format string has no arguments after the macro expansion, like `"{{literal}}"`, the unescaped string is borrowed.
+/// Otherwise, a new `String` is allocated like with the [`format!`] macro.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use std::borrow::Cow;
+///
+/// assert!(matches!(cfmt::format_cow!("{{literal}}"), Cow::Borrowed("{literal}")));
+/// assert!(matches!(cfmt::format_cow!("{0 :<upper>}", format_args!("abc")), Cow::Owned(s) if s == "ABC"));
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
 #[macro_export]
-macro_rules! writeln {
-    ($dst:expr) => {{
-        ::core::writeln!($dst)
-    }};
-    ($dst:expr, $($arg:tt)*) => {{
-        $crate::fmt_inner!([::core::writeln!], [$dst], $($arg)*)
+macro_rules! format_cow {
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::format_cow_inner!], [], $($arg)*)
     }};
 }
 
-/// Panics the current thread
+#[doc(hidden)]
 #[macro_export]
-macro_rules! panic {
-    () => {{
-        ::core::panic!()
-    }};
+macro_rules! format_cow_inner {
+    ($fmt:literal) => {
+        match ::core::format_args!($fmt).as_str() {
+            ::core::option::Option::Some(s) => $crate::alloc::borrow::Cow::Borrowed(s),
+            ::core::option::Option::None => $crate::alloc::borrow::Cow::Owned($crate::alloc::format!($fmt)),
+        }
+    };
+    ($($arg:tt)*) => {
+        $crate::alloc::borrow::Cow::Owned::<'static, str>($crate::alloc::format!($($arg)*))
+    };
+}
+
+/// Creates a `Vec<String>` by formatting each item of an iterator
+///
+/// The first argument is the iterator, and the second argument is a closure-like pattern binding each item,
+/// followed by the format string and its arguments. Identifiers bound by the pattern can be captured in the format string.
+///
+/// Each row is formatted like with the [`format!`] macro, so arguments are evaluated once per item.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use core::fmt;
+///
+/// struct Hex(u8);
+///
+/// impl cfmt::runtime::CustomFormat for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter, _: &str) -> fmt::Result {
+///         write!(f, "{:#04x}", self.0)
+///     }
+/// }
+///
+/// struct Row {
+///     name: &'static str,
+///     value: Hex,
+/// }
+///
+/// let rows = [Row { name: "a", value: Hex(1) }, Row { name: "bc", value: Hex(255) }];
+/// let lines = cfmt::format_rows!(rows, |Row { name, value }| "{name:<4}{value :<x>}");
+///
+/// assert_eq!(lines, ["a   0x01", "bc  0xff"]);
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[macro_export]
+macro_rules! format_rows {
+    ($items:expr, |$item:pat_param| $($arg:tt)*) => {{
+        ::core::iter::Iterator::collect::<$crate::alloc::vec::Vec<$crate::alloc::string::String>>(::core::iter::Iterator::map(
+            ::core::iter::IntoIterator::into_iter($items),
+            |$item| $crate::fmt_inner!([$crate::alloc::format!], [], $($arg)*),
+        ))
+    }};
+}
+
+/// Prints to the standard output
+#[cfg(not(feature = "no-std-print"))]
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::std::print!], [], $($arg)*)
+    }};
+}
+
+/// Prints to the standard output, with a newline
+#[cfg(not(feature = "no-std-print"))]
+#[macro_export]
+macro_rules! println {
+    () => {{
+        ::std::println!()
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::std::println!], [], $($arg)*)
+    }};
+}
+
+/// Prints to the global writer registered with [`io::set_global_writer`]
+#[cfg(feature = "no-std-print")]
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::global_print_fmt!], [], $($arg)*)
+    }};
+}
+
+/// Prints to the global writer registered with [`io::set_global_writer`], with a newline
+#[cfg(feature = "no-std-print")]
+#[macro_export]
+macro_rules! println {
+    () => {{
+        $crate::io::print_fmt(::core::format_args!("\n"))
+    }};
+    (only = $only:literal, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::global_print_fmt!], [], [only = $only, newline], $($arg)*)
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::global_print_fmt!], [], [newline], $($arg)*)
+    }};
+}
+
+#[cfg(feature = "no-std-print")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! global_print_fmt {
+    ($($arg:tt)*) => {
+        $crate::io::print_fmt(::core::format_args!($($arg)*))
+    };
+}
+
+/// Prints to a locked standard output, like [`std::io::StdoutLock`]
+///
+/// The first argument is the lock, which can be reused across calls for avoiding to lock the standard output for each call.
+///
+/// # Panics
+///
+/// Panics if writing to the lock fails, like the [`print!`] macro.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let stdout = std::io::stdout();
+/// let mut lock = stdout.lock();
+///
+/// for x in 0..3 {
+///     cfmt::print_locked!(lock, "{0 :<upper>} ", format_args!("x{}", x));
+/// }
+/// cfmt::println_locked!(lock);
+/// ```
+#[macro_export]
+macro_rules! print_locked {
+    ($lock:expr, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::print_locked_fmt!], [$lock], $($arg)*)
+    }};
+}
+
+/// Prints to a locked standard output, with a newline
+///
+/// See the [`print_locked!`] macro.
+#[macro_export]
+macro_rules! println_locked {
+    ($lock:expr) => {{
+        $crate::print_locked_fmt!($lock, "\n")
+    }};
+    ($lock:expr, only = $only:literal, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::print_locked_fmt!], [$lock], [only = $only, newline], $($arg)*)
+    }};
+    ($lock:expr, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::print_locked_fmt!], [$lock], [newline], $($arg)*)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! print_locked_fmt {
+    ($lock:expr, $($arg:tt)*) => {
+        if let ::core::result::Result::Err(e) = ::std::io::Write::write_fmt(&mut $lock, ::core::format_args!($($arg)*)) {
+            ::core::panic!("failed printing to stdout: {}", e)
+        }
+    };
+}
+
+/// Prints to the standard output, and flushes it
+///
+/// The standard output is locked once for both operations.
+///
+/// # Panics
+///
+/// Panics if writing to the standard output or flushing it fails.
+#[macro_export]
+macro_rules! print_flush {
+    ($($arg:tt)*) => {{
+        let stdout = ::std::io::stdout();
+        let mut lock = stdout.lock();
+        $crate::print_locked!(lock, $($arg)*);
+        if let ::core::result::Result::Err(e) = ::std::io::Write::flush(&mut lock) {
+            ::core::panic!("failed flushing stdout: {}", e)
+        }
+    }};
+}
+
+/// Prints to the standard output, with a newline, and flushes it
+///
+/// See the [`print_flush!`] macro.
+#[macro_export]
+macro_rules! println_flush {
+    () => {{
+        $crate::print_flush!("\n")
+    }};
+    ($($arg:tt)*) => {{
+        let stdout = ::std::io::stdout();
+        let mut lock = stdout.lock();
+        $crate::println_locked!(lock, $($arg)*);
+        if let ::core::result::Result::Err(e) = ::std::io::Write::flush(&mut lock) {
+            ::core::panic!("failed flushing stdout: {}", e)
+        }
+    }};
+}
+
+/// Prints to the standard output, interpreting compile-time format specifiers as ANSI styles
+///
+/// Each argument with a compile-time format specifier is wrapped in a [`style::Styled`] value,
+/// so any value implementing [`Display`](core::fmt::Display) can be styled.
+/// Runtime format specifiers are not affected.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let msg = "done";
+/// cfmt::cprint!("{msg :green,bold} ");
+/// cfmt::cprint!("{0:>3 :dim}%", 100);
+/// ```
+///
+/// The following statement doesn't compile since `"purple"` is not a valid style:
+///
+/// ```rust,compile_fail
+/// # use custom_format as cfmt;
+/// cfmt::cprint!("{0 :purple}", "error");
+/// ```
+#[cfg(feature = "compile-time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compile-time")))]
+#[macro_export]
+macro_rules! cprint {
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::std::print!], [], [style], $($arg)*)
+    }};
+}
+
+/// Prints to the standard output with a newline, interpreting compile-time format specifiers as ANSI styles
+///
+/// See the [`cprint!`] macro for more details.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let msg = "error";
+/// cfmt::cprintln!("{msg :red,bold}: {0 :underline}", "file not found");
+/// ```
+#[cfg(feature = "compile-time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compile-time")))]
+#[macro_export]
+macro_rules! cprintln {
+    () => {{
+        ::std::println!()
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::std::println!], [], [style], $($arg)*)
+    }};
+}
+
+/// Prints to the standard error
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::std::eprint!], [], $($arg)*)
+    }};
+}
+
+/// Prints to the standard error, with a newline
+#[macro_export]
+macro_rules! eprintln {
+    () => {{
+        ::std::eprintln!()
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::std::eprintln!], [], $($arg)*)
+    }};
+}
+
+/// Prints and returns the value of an expression, or prints a formatted message, for quick and dirty debugging
+///
+/// With a format string, the message is printed to the standard error after the file, line and column of the invocation,
+/// and custom format specifiers are supported. Otherwise, the arguments are forwarded to the standard `dbg!` macro,
+/// which prints their [`Debug`](core::fmt::Debug) representations and returns their values.
+///
+/// A literal first argument is always interpreted as a format string, so a single literal cannot be debugged with this macro.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let value = cfmt::dbg!(1 + 1);
+/// assert_eq!(value, 2);
+///
+/// // Prints "[src/main.rs:7:1] value = 0X2"
+/// cfmt::dbg!("value = {0 :<upper>}", format_args!("{:#x}", value));
+/// ```
+#[macro_export]
+macro_rules! dbg {
+    () => {{
+        ::std::dbg!()
+    }};
+    (only = $only:literal, $($arg:tt)*) => {{
+        ::std::eprintln!("[{}:{}:{}] {}", ::core::file!(), ::core::line!(), ::core::column!(), $crate::format_args!(only = $only, $($arg)*))
+    }};
+    ($fmt:literal $(, $($arg:tt)*)?) => {{
+        ::std::eprintln!("[{}:{}:{}] {}", ::core::file!(), ::core::line!(), ::core::column!(), $crate::format_args!($fmt $(, $($arg)*)?))
+    }};
+    ($($arg:tt)*) => {
+        ::std::dbg!($($arg)*)
+    };
+}
+
+/// Writes formatted data into a buffer
+#[macro_export]
+macro_rules! write {
+    ($dst:expr, $($arg:tt)*) => {{
+        $crate::fmt_inner!([::core::write!], [$dst], $($arg)*)
+    }};
+}
+
+/// Write formatted data into a buffer, with a newline appended
+#[macro_export]
+macro_rules! writeln {
+    ($dst:expr) => {{
+        ::core::writeln!($dst)
+    }};
+    ($dst:expr, $($arg:tt)*) => {{
+        $crate::fmt_inner!([::core::writeln!], [$dst], $($arg)*)
+    }};
+}
+
+/// Appends formatted data into a [`core::fmt::Write`] buffer, like a `String`, returning a `core::fmt::Result`
+///
+/// Unlike the [`format!`] macro, no new `String` is allocated, which is useful for building large outputs incrementally.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let mut s = String::from("values:");
+///
+/// for x in 0..3 {
+///     cfmt::format_into!(&mut s, " {0 :<upper>}", format_args!("{:#x}", x + 10)).unwrap();
+/// }
+///
+/// assert_eq!(s, "values: 0XA 0XB 0XC");
+/// ```
+#[macro_export]
+macro_rules! format_into {
+    ($dst:expr, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::fmt_write_fmt!], [$dst], $($arg)*)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fmt_write_fmt {
+    ($dst:expr, $($arg:tt)*) => {
+        ::core::fmt::Write::write_fmt(&mut $dst, ::core::format_args!($($arg)*))
+    };
+}
+
+/// Formats into a byte slice, like a stack buffer, returning the written `&str`
+///
+/// It returns a `Result<&str, core::fmt::Error>`, which is an error if the output doesn't fit in the buffer.
+/// No allocation is needed, which is useful on `no_std` targets without `alloc`. See [`buffer::SliceWriter`].
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let mut buf = [0u8; 16];
+/// let s = cfmt::format_to_slice!(&mut buf, "value: {0 :<upper>}", format_args!("{:#x}", 171));
+/// assert_eq!(s, Ok("value: 0XAB"));
+///
+/// let mut buf = [0u8; 4];
+/// assert!(cfmt::format_to_slice!(&mut buf, "{}", 123456).is_err());
+/// ```
+#[macro_export]
+macro_rules! format_to_slice {
+    ($buf:expr, $($arg:tt)*) => {{
+        let mut writer = $crate::buffer::SliceWriter::new($buf);
+        match $crate::format_into!(writer, $($arg)*) {
+            ::core::result::Result::Ok(()) => ::core::result::Result::Ok(writer.into_str()),
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    }};
+}
+
+/// Formats into a string with a fixed capacity of `N` bytes, stored inline without allocation
+///
+/// The first argument is the capacity. It returns a `Result<FixedString<N>, core::fmt::Error>`,
+/// which is an error if the output doesn't fit in the capacity. See [`buffer::FixedString`].
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let s = cfmt::format_fixed!(16, "value: {0 :<upper>}", format_args!("{:#x}", 171)).unwrap();
+/// assert_eq!(s, "value: 0XAB");
+///
+/// assert!(cfmt::format_fixed!(4, "{}", 123456).is_err());
+/// ```
+#[macro_export]
+macro_rules! format_fixed {
+    ($capacity:expr, $($arg:tt)*) => {{
+        let mut s = $crate::buffer::FixedString::<{ $capacity }>::new();
+        match $crate::format_into!(s, $($arg)*) {
+            ::core::result::Result::Ok(()) => ::core::result::Result::Ok(s),
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    }};
+}
+
+/// Writes formatted data into a [`std::io::Write`] writer, returning a `std::io::Result<()>`
+///
+/// Unlike the [`write!`] macro, the `write_fmt` method of the [`std::io::Write`] trait is always called,
+/// even if the writer also has a `write_fmt` method for another trait.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let mut buffer = Vec::new();
+/// let result: std::io::Result<()> = cfmt::io_write!(buffer, "{0 :<upper>}", format_args!("value"));
+///
+/// assert!(result.is_ok());
+/// assert_eq!(buffer, b"VALUE");
+/// ```
+#[macro_export]
+macro_rules! io_write {
+    ($dst:expr, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::io_write_fmt!], [$dst], $($arg)*)
+    }};
+}
+
+/// Writes formatted data into a [`std::io::Write`] writer, with a newline appended, returning a `std::io::Result<()>`
+///
+/// See the [`io_write!`] macro.
+#[macro_export]
+macro_rules! io_writeln {
+    ($dst:expr) => {{
+        $crate::io_write_fmt!($dst, "\n")
+    }};
+    ($dst:expr, only = $only:literal, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::io_write_fmt!], [$dst], [only = $only, newline], $($arg)*)
+    }};
+    ($dst:expr, $($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::io_write_fmt!], [$dst], [newline], $($arg)*)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! io_write_fmt {
+    ($dst:expr, $($arg:tt)*) => {
+        ::std::io::Write::write_fmt(&mut $dst, ::core::format_args!($($arg)*))
+    };
+}
+
+/// Writes formatted data into a [`std::io::Write`] writer, where runtime format specifiers may produce non-UTF8 output
+///
+/// Arguments with a runtime format specifier like `{x :<hex>}` are formatted with the [`bytes::CustomFormatBytes`] trait,
+/// which writes directly to the writer. Other arguments are formatted as text like with the [`io_write!`] macro.
+///
+/// Returns a `std::io::Result<()>`, with the first error of the writer or of the formatted values.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// let mut buffer = Vec::new();
+/// let payload = [0xC0, 0xFF, 0xEE];
+///
+/// cfmt::write_bytes!(buffer, "{0}:{payload :<raw>}:{payload :<hex>}", payload.len()).unwrap();
+///
+/// assert_eq!(buffer, b"3:\xC0\xFF\xEE:c0ffee");
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[macro_export]
+macro_rules! write_bytes {
+    ($dst:expr, only = $only:literal, $($arg:tt)*) => {{
+        match &mut $dst {
+            dst => {
+                let sink = $crate::bytes::ByteSink::new(dst);
+                $crate::fmt_inner!([$crate::write_bytes_fmt!], [sink], [only = $only, bytes], $($arg)*)
+            }
+        }
+    }};
+    ($dst:expr, $($arg:tt)*) => {{
+        match &mut $dst {
+            dst => {
+                let sink = $crate::bytes::ByteSink::new(dst);
+                $crate::fmt_inner!([$crate::write_bytes_fmt!], [sink], [bytes], $($arg)*)
+            }
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! write_bytes_fmt {
+    ($sink:expr, $($arg:tt)*) => {
+        $sink.write_fmt(::core::format_args!($($arg)*))
+    };
+}
+
+/// Logs to the web console, with the `console.log` function
+///
+/// The message is formatted once to a `String`, which is passed to the [`web_sys::console::log_1`] function.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use custom_format as cfmt;
+///
+/// let user = format_args!("ferris");
+/// cfmt::console_log!("logged in as {user :<upper>}");
+/// ```
+#[cfg(feature = "wasm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+#[macro_export]
+macro_rules! console_log {
+    () => {{
+        $crate::wasm::log(::core::format_args!(""))
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::console_fmt!], [$crate::wasm::log], $($arg)*)
+    }};
+}
+
+/// Logs to the web console, with the `console.error` function
+///
+/// See the [`console_log!`] macro.
+#[cfg(feature = "wasm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+#[macro_export]
+macro_rules! console_error {
+    () => {{
+        $crate::wasm::error(::core::format_args!(""))
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([$crate::console_fmt!], [$crate::wasm::error], $($arg)*)
+    }};
+}
+
+#[cfg(feature = "wasm")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! console_fmt {
+    ($log:expr, $($arg:tt)*) => {
+        $log(::core::format_args!($($arg)*))
+    };
+}
+
+/// Panics the current thread
+///
+/// The arguments are passed to the [`core::panic!`] macro as a [`core::fmt::Arguments`] value, including the custom formatters,
+/// so the panic message is never allocated and the macro can be used in `#![no_std]` crates with any kind of format specifier.
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// use custom_format as cfmt;
+///
+/// let value = "value";
+/// cfmt::panic!("invalid {0 :<upper>}", format_args!("{}", value));
+/// ```
+#[macro_export]
+macro_rules! panic {
+    () => {{
+        ::core::panic!()
+    }};
     ($($arg:tt)*) => {{
         $crate::fmt_inner!([::core::panic!], [], $($arg)*)
     }};
 }
+
+/// Indicates unfinished code by panicking with a message of "not yet implemented"
+#[macro_export]
+macro_rules! todo {
+    () => {{
+        ::core::todo!()
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::core::todo!], [], $($arg)*)
+    }};
+}
+
+/// Indicates unimplemented code by panicking with a message of "not implemented"
+#[macro_export]
+macro_rules! unimplemented {
+    () => {{
+        ::core::unimplemented!()
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::core::unimplemented!], [], $($arg)*)
+    }};
+}
+
+/// Indicates unreachable code by panicking with a message of "internal error: entered unreachable code"
+#[macro_export]
+macro_rules! unreachable {
+    () => {{
+        ::core::unreachable!()
+    }};
+    ($($arg:tt)*) => {{
+        $crate::fmt_inner!([::core::unreachable!], [], $($arg)*)
+    }};
+}
+
+/// Returns early with an error built from a formatted message
+///
+/// The error type of the enclosing function must implement `From<String>`, like `Box<dyn Error + Send + Sync>`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use std::error::Error;
+///
+/// fn parse(s: &str) -> Result<u8, Box<dyn Error>> {
+///     match s.parse() {
+///         Ok(x) => Ok(x),
+///         Err(_) => cfmt::bail!("invalid value: {0 :<upper>}", format_args!("{}", s)),
+///     }
+/// }
+///
+/// assert_eq!(parse("abc").unwrap_err().to_string(), "invalid value: ABC");
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[macro_export]
+macro_rules! bail {
+    ($($arg:tt)+) => {
+        return ::core::result::Result::Err(::core::convert::From::from($crate::fmt_inner!([$crate::alloc::format!], [], $($arg)+)))
+    };
+}
+
+/// Returns early with an error built from a formatted message if a condition is not satisfied
+///
+/// Without a message, the error contains the stringified condition. See the [`bail!`] macro.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// fn check(x: u8) -> Result<(), String> {
+///     cfmt::ensure!(x < 10, "too large: {0 :<upper>}", format_args!("{:#x}", x));
+///     cfmt::ensure!(x > 0);
+///     Ok(())
+/// }
+///
+/// assert_eq!(check(0xab).unwrap_err(), "too large: 0XAB");
+/// assert_eq!(check(0).unwrap_err(), "condition failed: `x > 0`");
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr $(,)?) => {
+        if !$cond {
+            return ::core::result::Result::Err(::core::convert::From::from($crate::alloc::string::String::from(::core::concat!(
+                "condition failed: `",
+                ::core::stringify!($cond),
+                "`"
+            ))));
+        }
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::bail!($($arg)+);
+        }
+    };
+}
+
+/// Asserts that a boolean expression is `true` at runtime
+///
+/// The optional message is only formatted if the assertion fails.
+#[macro_export]
+macro_rules! assert {
+    ($cond:expr $(,)?) => {{
+        ::core::assert!($cond)
+    }};
+    ($cond:expr, $($arg:tt)+) => {{
+        if !$cond {
+            $crate::panic!($($arg)+)
+        }
+    }};
+}
+
+/// Asserts that two expressions are equal to each other, using [`PartialEq`]
+///
+/// The optional message is only formatted if the assertion fails, followed by the [`Debug`](core::fmt::Debug) representations of the values.
+#[macro_export]
+macro_rules! assert_eq {
+    ($left:expr, $right:expr $(,)?) => {{
+        ::core::assert_eq!($left, $right)
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if !(*left_val == *right_val) {
+                    ::core::panic!(
+                        "assertion `left == right` failed: {}\n  left: {:?}\n right: {:?}",
+                        $crate::format_args!($($arg)+),
+                        left_val,
+                        right_val
+                    )
+                }
+            }
+        }
+    }};
+}
+
+/// Asserts that two expressions are not equal to each other, using [`PartialEq`]
+///
+/// The optional message is only formatted if the assertion fails, followed by the [`Debug`](core::fmt::Debug) representations of the values.
+#[macro_export]
+macro_rules! assert_ne {
+    ($left:expr, $right:expr $(,)?) => {{
+        ::core::assert_ne!($left, $right)
+    }};
+    ($left:expr, $right:expr, $($arg:tt)+) => {{
+        match (&$left, &$right) {
+            (left_val, right_val) => {
+                if *left_val == *right_val {
+                    ::core::panic!(
+                        "assertion `left != right` failed: {}\n  left: {:?}\n right: {:?}",
+                        $crate::format_args!($($arg)+),
+                        left_val,
+                        right_val
+                    )
+                }
+            }
+        }
+    }};
+}
+
+/// Asserts that a value formatted with a custom format specifier is equal to an expected string
+///
+/// A compile-time format specifier is written as a string literal, like `"%Y"`,
+/// and a runtime format specifier is enclosed in angle brackets, like `<"%Y">`, mirroring the format string syntax.
+///
+/// On failure, the panic message lists the format specifier, the type of the value and both strings.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::compile_time::{spec, CustomFormat};
+///
+/// use core::fmt;
+///
+/// struct Year(u16);
+///
+/// impl CustomFormat<{ spec("%Y") }> for Year {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, "{}", self.0)
+///     }
+/// }
+///
+/// impl cfmt::runtime::CustomFormat for Year {
+///     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+///         match spec {
+///             "%y" => write!(f, "{:02}", self.0 % 100),
+///             _ => Err(fmt::Error),
+///         }
+///     }
+/// }
+///
+/// cfmt::assert_custom_format_eq!(Year(1836), "%Y", "1836");
+/// cfmt::assert_custom_format_eq!(Year(1836), <"%y">, "36");
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[macro_export]
+macro_rules! assert_custom_format_eq {
+    ($value:expr, <$spec:literal>, $expected:expr $(,)?) => {{
+        $crate::assert_custom_format_eq!(@check $value, $spec, "runtime", |value| $crate::runtime::CustomFormatter::new($spec, value), $expected)
+    }};
+    ($value:expr, $spec:literal, $expected:expr $(,)?) => {{
+        $crate::assert_custom_format_eq!(@check $value, $spec, "compile-time", |value| $crate::custom_formatter!($spec, value), $expected)
+    }};
+    (@check $value:expr, $spec:literal, $kind:literal, |$v:ident| $formatter:expr, $expected:expr) => {{
+        match (&$value, &$expected) {
+            ($v, expected_val) => {
+                fn type_name_of<T: ?::core::marker::Sized>(_: &T) -> &'static str {
+                    ::core::any::type_name::<T>()
+                }
+
+                let actual_val = $crate::alloc::string::ToString::to_string(&$formatter);
+
+                if actual_val != ::core::convert::AsRef::<str>::as_ref(expected_val) {
+                    ::core::panic!(
+                        "assertion `format(value, spec) == expected` failed\n  spec: {:?} ({})\n  type: {}\n  left: {:?}\n right: {:?}",
+                        $spec,
+                        $kind,
+                        type_name_of($v),
+                        actual_val,
+                        ::core::convert::AsRef::<str>::as_ref(expected_val)
+                    )
+                }
+            }
+        }
+    }};
+}
+
+/// Asserts that a boolean expression is `true` at runtime, only when debug assertions are enabled
+///
+/// See the [`assert!`] macro.
+#[macro_export]
+macro_rules! debug_assert {
+    ($($arg:tt)*) => {{
+        if ::core::cfg!(debug_assertions) {
+            $crate::assert!($($arg)*)
+        }
+    }};
+}
+
+/// Asserts that two expressions are equal to each other, only when debug assertions are enabled
+///
+/// See the [`assert_eq!`] macro.
+#[macro_export]
+macro_rules! debug_assert_eq {
+    ($($arg:tt)*) => {{
+        if ::core::cfg!(debug_assertions) {
+            $crate::assert_eq!($($arg)*)
+        }
+    }};
+}
+
+/// Asserts that two expressions are not equal to each other, only when debug assertions are enabled
+///
+/// See the [`assert_ne!`] macro.
+#[macro_export]
+macro_rules! debug_assert_ne {
+    ($($arg:tt)*) => {{
+        if ::core::cfg!(debug_assertions) {
+            $crate::assert_ne!($($arg)*)
+        }
+    }};
+}
@@ -0,0 +1,73 @@
+//! Macros for the [`log`](https://docs.rs/log) crate, with custom format specifiers in the message.
+//!
+//! The message is formatted with the [`format_args!`](crate::format_args) macro of this crate and passed to the `log` macros,
+//! so that the arguments are only evaluated if the log level is enabled.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use custom_format as cfmt;
+//!
+//! let user = "alice";
+//! cfmt::log::info!("logged in: {0 :<upper>}", format_args!("{}", user));
+//! cfmt::log::warn!(target: "auth", "invalid password for {0 :<upper>}", format_args!("{}", user));
+//! ```
+
+#[doc(inline)]
+pub use crate::{log_debug as debug, log_error as error, log_info as info, log_trace as trace, log_warn as warn};
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_inner {
+    ([$lvl:expr], target: $target:expr, $($arg:tt)+) => {
+        $crate::log_crate::log!(target: $target, $lvl, "{}", $crate::format_args!($($arg)+))
+    };
+    ([$lvl:expr], $($arg:tt)+) => {
+        $crate::log_crate::log!($lvl, "{}", $crate::format_args!($($arg)+))
+    };
+}
+
+/// Logs a message at the error level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)+) => {
+        $crate::log_inner!([$crate::log_crate::Level::Error], $($arg)+)
+    };
+}
+
+/// Logs a message at the warn level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)+) => {
+        $crate::log_inner!([$crate::log_crate::Level::Warn], $($arg)+)
+    };
+}
+
+/// Logs a message at the info level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)+) => {
+        $crate::log_inner!([$crate::log_crate::Level::Info], $($arg)+)
+    };
+}
+
+/// Logs a message at the debug level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)+) => {
+        $crate::log_inner!([$crate::log_crate::Level::Debug], $($arg)+)
+    };
+}
+
+/// Logs a message at the trace level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_trace {
+    ($($arg:tt)+) => {
+        $crate::log_inner!([$crate::log_crate::Level::Trace], $($arg)+)
+    };
+}
@@ -2,8 +2,69 @@
 
 use core::fmt;
 
-/// Trait for custom formatting with runtime format checking
+pub use crate::buffer::CountWrite;
+
+#[cfg(feature = "alloc")]
+mod template;
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use template::{Argument, Rendered, Template, TemplateError};
+
+#[cfg(feature = "std")]
+use std::{boxed::Box, cell::RefCell, vec::Vec};
+
+/// Trait for custom formatting with runtime format checking.
+///
+/// The format specifier is passed byte-for-byte as written in the format string, without any Unicode normalization,
+/// unlike identifiers in the format string which must be normalized in Unicode NFC.
+/// Implementations should compare it as raw bytes, optionally after calling [`normalize_spec`].
+///
+/// Standard flags written before the custom separator, like in `{x:+#08.3 :<%a>}`, are set on the formatter passed to the implementation,
+/// which can read them with methods like [`fmt::Formatter::width`] or [`fmt::Formatter::sign_plus`], or propagate them with [`FormatterExt`].
+#[cfg_attr(
+    has_diagnostic_namespace,
+    diagnostic::on_unimplemented(
+        message = "type `{Self}` does not support runtime custom format specifiers",
+        label = "runtime custom format specifiers not implemented for `{Self}`",
+        note = "write `spec` instead of `<spec>` after the ` :` separator to use a compile-time format specifier"
+    )
+)]
 pub trait CustomFormat {
+    /// Returns whether an empty or whitespace-only format specifier is valid for this value (*default: `true`*).
+    ///
+    /// When `false`, formatting with such a specifier through a [`CustomFormatter`] panics in debug builds,
+    /// with a message naming the type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,should_panic
+    /// use custom_format as cfmt;
+    ///
+    /// use core::fmt;
+    ///
+    /// struct Hex(u8);
+    ///
+    /// impl cfmt::runtime::CustomFormat for Hex {
+    ///     fn empty_is_valid(&self) -> bool {
+    ///         false
+    ///     }
+    ///
+    ///     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+    ///         match spec {
+    ///             "x" => write!(f, "{:#02x}", self.0),
+    ///             _ => Err(fmt::Error),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// // Panics in debug builds, since the format specifier only contains whitespace
+    /// cfmt::println!("{0 :< >}", Hex(0));
+    /// ```
+    fn empty_is_valid(&self) -> bool {
+        true
+    }
+
     /// Formats the value using the given formatter.
     ///
     /// # Examples
@@ -51,6 +112,213 @@ pub trait CustomFormat {
     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result;
 }
 
+/// Implements [`runtime::CustomFormat`](CustomFormat) for a type, with a `match spec` block checked at runtime.
+///
+/// Each arm maps a format specifier to a closure or a function with the signature `fn(&Self, &mut fmt::Formatter) -> fmt::Result`,
+/// and other format specifiers return an error. The empty format specifier is only valid if it is one of the arms.
+/// Generic parameters of the implementation can be provided in brackets after the `impl` keyword, like in `impl[T: fmt::Display] Wrapper<T>`.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::fmt;
+///
+/// struct Hex(u8);
+///
+/// cfmt::impl_runtime_custom_format!(Hex, match spec {
+///     "x" => |this, f| write!(f, "{:#x}", this.0),
+///     "X" => |this, f| write!(f, "{:#X}", this.0),
+/// });
+///
+/// assert_eq!(cfmt::format!("{0 :<x>}, {0 :<X>}", Hex(0xAB)), "0xab, 0xAB");
+/// assert!(!cfmt::runtime::CustomFormat::empty_is_valid(&Hex(0)));
+/// ```
+#[macro_export]
+macro_rules! impl_runtime_custom_format {
+    (impl [$($generics:tt)*] $ty:ty, match spec { $($spec:literal => $func:expr),* $(,)? }) => {
+        impl<$($generics)*> $crate::runtime::CustomFormat for $ty {
+            fn empty_is_valid(&self) -> bool {
+                false $(|| $spec.is_empty())*
+            }
+
+            fn fmt(&self, f: &mut ::core::fmt::Formatter, spec: &str) -> ::core::fmt::Result {
+                match spec {
+                    $($spec => ($func as fn(&Self, &mut ::core::fmt::Formatter) -> ::core::fmt::Result)(self, f),)*
+                    _ => ::core::result::Result::Err(::core::fmt::Error),
+                }
+            }
+        }
+    };
+    ($ty:ty, match spec { $($spec:literal => $func:expr),* $(,)? }) => {
+        $crate::impl_runtime_custom_format!(impl[] $ty, match spec { $($spec => $func),* });
+    };
+}
+
+/// Forward custom formatting through a reference, so that borrowed values can be passed to the formatting macros.
+///
+/// The same forwarding is implemented for `&mut T`, and for `Box<T>`, `Rc<T>` and `Arc<T>` with the `alloc` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::fmt;
+/// use std::rc::Rc;
+///
+/// struct Hex(u8);
+///
+/// impl cfmt::runtime::CustomFormat for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+///         match spec {
+///             "x" => write!(f, "{:#x}", self.0),
+///             _ => Err(fmt::Error),
+///         }
+///     }
+/// }
+///
+/// let (value, boxed, shared) = (Hex(0xAB), Box::new(Hex(0xCD)), Rc::new(Hex(0xEF)));
+/// let borrowed = &&value;
+///
+/// assert_eq!(cfmt::format!("{borrowed :<x>}, {boxed :<x>}, {shared :<x>}"), "0xab, 0xcd, 0xef");
+/// ```
+impl<T: CustomFormat + ?Sized> CustomFormat for &T {
+    fn empty_is_valid(&self) -> bool {
+        (**self).empty_is_valid()
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        (**self).fmt(f, spec)
+    }
+}
+
+impl<T: CustomFormat + ?Sized> CustomFormat for &mut T {
+    fn empty_is_valid(&self) -> bool {
+        (**self).empty_is_valid()
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        (**self).fmt(f, spec)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: CustomFormat + ?Sized> CustomFormat for alloc::boxed::Box<T> {
+    fn empty_is_valid(&self) -> bool {
+        (**self).empty_is_valid()
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        (**self).fmt(f, spec)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: CustomFormat + ?Sized> CustomFormat for alloc::rc::Rc<T> {
+    fn empty_is_valid(&self) -> bool {
+        (**self).empty_is_valid()
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        (**self).fmt(f, spec)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: CustomFormat + ?Sized> CustomFormat for alloc::sync::Arc<T> {
+    fn empty_is_valid(&self) -> bool {
+        (**self).empty_is_valid()
+    }
+
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        (**self).fmt(f, spec)
+    }
+}
+
+/// Remove leading and trailing whitespace from a format specifier.
+///
+/// The format specifier is otherwise returned unchanged: no Unicode normalization is applied,
+/// so specifiers with combining characters are compared as raw bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format::runtime::normalize_spec;
+///
+/// assert_eq!(normalize_spec(" %Y\t"), "%Y");
+/// assert_eq!(normalize_spec("e\u{301}"), "e\u{301}");
+/// assert_ne!(normalize_spec("e\u{301}"), "\u{e9}");
+/// ```
+pub fn normalize_spec(spec: &str) -> &str {
+    spec.trim()
+}
+
+/// Error returned when checking a format specifier which is not supported, listing the supported format specifiers.
+///
+/// It is returned by the `check_spec` function generated by the [`define_specs!`](crate::define_specs) macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedSpec<'a> {
+    /// Unsupported format specifier
+    spec: &'a str,
+    /// Supported format specifiers
+    supported: &'static [&'static str],
+}
+
+impl<'a> UnsupportedSpec<'a> {
+    /// Check if a format specifier is one of the supported format specifiers
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use custom_format::runtime::UnsupportedSpec;
+    ///
+    /// assert!(UnsupportedSpec::check("x", &["x", "X"]).is_ok());
+    ///
+    /// let error = UnsupportedSpec::check("z", &["x", "X"]).unwrap_err();
+    /// assert_eq!(error.to_string(), "unsupported format specifier `z`, expected one of: `x`, `X`");
+    /// ```
+    pub fn check(spec: &'a str, supported: &'static [&'static str]) -> Result<(), Self> {
+        if supported.contains(&spec) {
+            Ok(())
+        } else {
+            Err(Self { spec, supported })
+        }
+    }
+
+    /// Returns the unsupported format specifier
+    pub fn spec(&self) -> &'a str {
+        self.spec
+    }
+
+    /// Returns the supported format specifiers
+    pub fn supported(&self) -> &'static [&'static str] {
+        self.supported
+    }
+}
+
+impl fmt::Display for UnsupportedSpec<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unsupported format specifier `{}`, expected one of: ", self.spec)?;
+
+        for (index, spec) in self.supported.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "`{}`", spec)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnsupportedSpec<'_> {}
+
 /// Wrapper for custom formatting via its [`Display`](core::fmt::Display) trait
 #[derive(Debug, Clone)]
 pub struct CustomFormatter<'a, T> {
@@ -69,6 +337,442 @@ impl<'a, T> CustomFormatter<'a, T> {
 
 impl<T: CustomFormat> fmt::Display for CustomFormatter<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        CustomFormat::fmt(self.value, f, self.spec)
+        fmt_checked(self.value, f, self.spec, core::any::type_name::<T>())
+    }
+}
+
+/// Formats a value with its [`CustomFormat`] implementation, panicking in debug builds if the format specifier is empty and not valid for the value
+fn fmt_checked<T: CustomFormat + ?Sized>(value: &T, f: &mut fmt::Formatter, spec: &str, type_name: &str) -> fmt::Result {
+    if cfg!(debug_assertions) && spec.trim().is_empty() && !value.empty_is_valid() {
+        panic!("empty format specifier for type `{}`", type_name);
+    }
+
+    value.fmt(f, spec)
+}
+
+/// Wrapper selecting the formatter of the empty runtime format specifier `{x :<>}`.
+///
+/// The [`EmptySpecCustom`] trait is implemented for this type and is selected when the value implements [`CustomFormat`],
+/// and the [`EmptySpecDisplay`] trait is implemented for a reference to this type and is selected otherwise via auto-ref.
+#[doc(hidden)]
+pub struct EmptySpec<'a, T>(&'a T);
+
+impl<'a, T> EmptySpec<'a, T> {
+    /// Construct a new [`EmptySpec`] value
+    pub fn new(value: &'a T) -> Self {
+        Self(value)
+    }
+}
+
+/// Select the custom formatter of an [`EmptySpec`] value
+#[doc(hidden)]
+pub trait EmptySpecCustom<'a, T> {
+    /// Return the custom formatter
+    fn formatter(&self) -> CustomFormatter<'a, T>;
+}
+
+impl<'a, T: CustomFormat> EmptySpecCustom<'a, T> for EmptySpec<'a, T> {
+    fn formatter(&self) -> CustomFormatter<'a, T> {
+        CustomFormatter::new("", self.0)
+    }
+}
+
+/// Select the value itself as formatter of an [`EmptySpec`] value, so that it is formatted with its [`Display`](core::fmt::Display) implementation
+#[doc(hidden)]
+pub trait EmptySpecDisplay<'a, T> {
+    /// Return the value
+    fn formatter(&self) -> &'a T;
+}
+
+impl<'a, T> EmptySpecDisplay<'a, T> for &EmptySpec<'a, T> {
+    fn formatter(&self) -> &'a T {
+        self.0
+    }
+}
+
+/// Helper macro for constructing the formatter of the empty runtime format specifier, falling back to the [`Display`](core::fmt::Display) trait
+#[doc(hidden)]
+#[macro_export]
+macro_rules! runtime_empty_spec_formatter {
+    ("", $value:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::runtime::{EmptySpecCustom as _, EmptySpecDisplay as _};
+        (&$crate::runtime::EmptySpec::new($value)).formatter()
+    }};
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<T: CustomFormat> CustomFormatter<'_, T> {
+    /// Render the formatted value to a new `String`, which is equivalent to calling `.to_string()`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use custom_format::runtime::CustomFormatter;
+    ///
+    /// assert_eq!(CustomFormatter::new("upper", &format_args!("{}", "abc")).render(), "ABC");
+    /// ```
+    pub fn render(&self) -> alloc::string::String {
+        alloc::string::ToString::to_string(self)
+    }
+}
+
+/// Trait for custom formatting of a pair of values with runtime format checking.
+///
+/// It is used for custom format specifiers referencing two arguments, like in `{a, b :<ratio>}`.
+/// The first argument is the implementing type, and the second argument is passed to the [`fmt`](CustomFormat2::fmt) method.
+pub trait CustomFormat2<Rhs: ?Sized = Self> {
+    /// Formats the pair of values using the given formatter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use custom_format as cfmt;
+    ///
+    /// use core::fmt;
+    ///
+    /// struct Count(u32);
+    ///
+    /// impl cfmt::runtime::CustomFormat2 for Count {
+    ///     fn fmt(&self, other: &Self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+    ///         match spec {
+    ///             "ratio" => write!(f, "{}:{}", self.0, other.0),
+    ///             "percent" => write!(f, "{}%", self.0 * 100 / other.0),
+    ///             _ => Err(fmt::Error),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let (done, total) = (Count(3), Count(4));
+    /// assert_eq!(cfmt::format!("{done, total :<ratio>}, {0, 1 :<percent>}", done, total), "3:4, 75%");
+    /// ```
+    fn fmt(&self, other: &Rhs, f: &mut fmt::Formatter, spec: &str) -> fmt::Result;
+}
+
+/// Wrapper for custom formatting of a pair of values via its [`Display`](core::fmt::Display) trait
+#[derive(Debug, Clone)]
+pub struct CustomFormatter2<'a, T, U> {
+    /// Format specifier
+    spec: &'static str,
+    /// First value to format
+    value: &'a T,
+    /// Second value to format
+    other: &'a U,
+}
+
+impl<'a, T, U> CustomFormatter2<'a, T, U> {
+    /// Construct a new [`CustomFormatter2`] value
+    pub fn new(spec: &'static str, value: &'a T, other: &'a U) -> Self {
+        Self { spec, value, other }
+    }
+}
+
+impl<T: CustomFormat2<U>, U> fmt::Display for CustomFormatter2<'_, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        CustomFormat2::fmt(self.value, self.other, f, self.spec)
+    }
+}
+
+/// Wrapper for custom formatting via its [`Display`](core::fmt::Display) trait, falling back to the [`Debug`](core::fmt::Debug) trait if the custom formatting fails.
+///
+/// It is used for the `{x :<spec>|?}` syntax. The custom formatting is tried beforehand without writing anything,
+/// so that a failing implementation doesn't leave a partial output, and the value is formatted twice on success.
+/// The options of the formatter, like the width or the precision, are only applied when writing the output.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::fmt;
+///
+/// #[derive(Debug)]
+/// struct Hex(u8);
+///
+/// impl cfmt::runtime::CustomFormat for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+///         match spec {
+///             "x" => write!(f, "{:#x}", self.0),
+///             _ => Err(fmt::Error),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(cfmt::format!("{0 :<x>|?}, {0 :<y>|?}", Hex(0xAB)), "0xab, Hex(171)");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DebugFallback<'a, T> {
+    /// Format specifier
+    spec: &'static str,
+    /// Value to format
+    value: &'a T,
+}
+
+impl<'a, T> DebugFallback<'a, T> {
+    /// Construct a new [`DebugFallback`] value
+    pub fn new(spec: &'static str, value: &'a T) -> Self {
+        Self { spec, value }
+    }
+}
+
+impl<T: CustomFormat + fmt::Debug> fmt::Display for DebugFallback<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let custom_formatter = CustomFormatter::new(self.spec, self.value);
+
+        match fmt::Write::write_fmt(&mut CountWrite::new(), format_args!("{}", custom_formatter)) {
+            Ok(()) => fmt::Display::fmt(&custom_formatter, f),
+            Err(_) => fmt::Debug::fmt(self.value, f),
+        }
+    }
+}
+
+/// Wrapper for custom formatting via its [`Display`](core::fmt::Display) trait, quoting and escaping the custom output like a [`Debug`](core::fmt::Debug) string.
+///
+/// It is used for the `{x :<spec?>}` syntax. The custom output is escaped while being written, so no allocation is needed.
+/// The whole quoted output is padded with the fill, alignment and width of the formatter, and the value is formatted twice
+/// when a width is provided.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// use core::fmt;
+///
+/// struct Name(&'static str);
+///
+/// impl cfmt::runtime::CustomFormat for Name {
+///     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+///         match spec {
+///             "full" => write!(f, "{} \"Jr\"", self.0),
+///             _ => Err(fmt::Error),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(cfmt::format!("{0 :<full>}, {0 :<full?>}", Name("John")), r#"John "Jr", "John \"Jr\"""#);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DebugQuoted<'a, T> {
+    /// Format specifier
+    spec: &'static str,
+    /// Value to format
+    value: &'a T,
+}
+
+impl<'a, T> DebugQuoted<'a, T> {
+    /// Construct a new [`DebugQuoted`] value
+    pub fn new(spec: &'static str, value: &'a T) -> Self {
+        Self { spec, value }
+    }
+}
+
+impl<T: CustomFormat> fmt::Display for DebugQuoted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_display(&QuotedOutput(CustomFormatter::new(self.spec, self.value)))
+    }
+}
+
+/// Quoted and escaped output of a custom formatter
+struct QuotedOutput<'a, T>(CustomFormatter<'a, T>);
+
+impl<T: CustomFormat> fmt::Display for QuotedOutput<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Write::write_char(f, '"')?;
+        fmt::write(&mut EscapeWriter { f }, format_args!("{}", self.0))?;
+        fmt::Write::write_char(f, '"')
+    }
+}
+
+/// Writer escaping the written string like the [`Debug`](core::fmt::Debug) implementation of `str`, before forwarding it to a formatter
+struct EscapeWriter<'a, 'b> {
+    /// Inner formatter
+    f: &'a mut fmt::Formatter<'b>,
+}
+
+impl fmt::Write for EscapeWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            // Single quotes don't need to be escaped in a string
+            match c {
+                '\'' => fmt::Write::write_char(self.f, c)?,
+                _ => c.escape_debug().try_for_each(|c| fmt::Write::write_char(self.f, c))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Extension methods for [`fmt::Formatter`], for propagating its options to nested formatters.
+///
+/// Formatting a value with `write!(f, "{}", value)` uses a new formatter without the options of `f`,
+/// like the width or the precision provided before the custom format specifier.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::runtime::FormatterExt;
+///
+/// use core::fmt;
+///
+/// struct Date(u8, u8);
+///
+/// impl cfmt::runtime::CustomFormat for Date {
+///     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+///         match spec {
+///             "%d" => f.forward(&self.0),
+///             "%D" => f.pad_display(&format_args!("{:02}/{:02}", self.0, self.1)),
+///             _ => Err(fmt::Error),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(cfmt::format!("[{0:03 :<%d>}], [{0:^9 :<%D>}]", Date(5, 7)), "[005], [  05/07  ]");
+/// ```
+pub trait FormatterExt {
+    /// Format a value with all the options of this formatter, like the width, precision and flags
+    fn forward(&mut self, value: &dyn fmt::Display) -> fmt::Result;
+
+    /// Format a value, padding the whole output with the fill, alignment and width of this formatter.
+    ///
+    /// The output is left-aligned by default. Other options are not applied, and the value is formatted twice
+    /// when a width is provided, since its length is measured beforehand.
+    fn pad_display(&mut self, value: &dyn fmt::Display) -> fmt::Result;
+}
+
+impl FormatterExt for fmt::Formatter<'_> {
+    fn forward(&mut self, value: &dyn fmt::Display) -> fmt::Result {
+        value.fmt(self)
+    }
+
+    fn pad_display(&mut self, value: &dyn fmt::Display) -> fmt::Result {
+        crate::pad_display(self, value)
+    }
+}
+
+/// Formats the arguments, optionally transforming the case of the output.
+///
+/// The following format specifiers are supported:
+///
+/// - `""`: write the arguments as is.
+/// - `"upper"`: convert the output to uppercase.
+/// - `"lower"`: convert the output to lowercase.
+///
+/// The output is transformed while being written, so no allocation is needed.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// assert_eq!(cfmt::format!("{0 :<upper>}", format_args!("hello {}", "world")), "HELLO WORLD");
+/// ```
+impl CustomFormat for fmt::Arguments<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+        match spec {
+            "" => f.write_fmt(*self),
+            "upper" => fmt::write(&mut CaseWriter { f, upper: true }, *self),
+            "lower" => fmt::write(&mut CaseWriter { f, upper: false }, *self),
+            _ => Err(fmt::Error),
+        }
+    }
+}
+
+/// Writer converting the case of the written string before forwarding it to a formatter
+struct CaseWriter<'a, 'b> {
+    /// Inner formatter
+    f: &'a mut fmt::Formatter<'b>,
+    /// Convert to uppercase if `true`, to lowercase otherwise
+    upper: bool,
+}
+
+impl fmt::Write for CaseWriter<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.upper {
+                c.to_uppercase().try_for_each(|c| fmt::Write::write_char(self.f, c))?;
+            } else {
+                c.to_lowercase().try_for_each(|c| fmt::Write::write_char(self.f, c))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ambient provider, formatting a value without argument
+#[cfg(feature = "std")]
+type Provider = Box<dyn Fn(&mut fmt::Formatter) -> fmt::Result>;
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// Ambient providers registered for the current thread
+    static PROVIDERS: RefCell<Vec<(&'static str, Provider)>> = RefCell::new(Vec::new());
+}
+
+/// Register an ambient provider for the current thread, replacing any previous provider with the same name.
+///
+/// The provider is called for each ambient specifier `{:<name>}` in the format string, which doesn't reference any argument.
+/// Registering or removing a provider from inside a provider panics.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+///
+/// cfmt::runtime::set_provider("pid", |f| write!(f, "{}", 42));
+/// assert_eq!(cfmt::format!("[{:<pid>}] {}", "started"), "[42] started");
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn set_provider(name: &'static str, provider: impl Fn(&mut fmt::Formatter) -> fmt::Result + 'static) {
+    PROVIDERS.with(|providers| {
+        let mut providers = providers.borrow_mut();
+        match providers.iter_mut().find(|(x, _)| *x == name) {
+            Some((_, old_provider)) => *old_provider = Box::new(provider),
+            None => providers.push((name, Box::new(provider))),
+        }
+    });
+}
+
+/// Remove an ambient provider for the current thread, returning `true` if it was registered
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub fn remove_provider(name: &str) -> bool {
+    PROVIDERS.with(|providers| {
+        let mut providers = providers.borrow_mut();
+        let len = providers.len();
+        providers.retain(|(x, _)| *x != name);
+        providers.len() != len
+    })
+}
+
+/// Wrapper for formatting an ambient value via its [`Display`](core::fmt::Display) trait.
+///
+/// Formatting fails if no provider is registered with its name for the current thread.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug, Clone)]
+pub struct AmbientFormatter {
+    /// Name of the provider
+    name: &'static str,
+}
+
+#[cfg(feature = "std")]
+impl AmbientFormatter {
+    /// Construct a new [`AmbientFormatter`] value
+    pub fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for AmbientFormatter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        PROVIDERS.with(|providers| match providers.borrow().iter().find(|(x, _)| *x == self.name) {
+            Some((_, provider)) => provider(f),
+            None => Err(fmt::Error),
+        })
     }
 }
@@ -0,0 +1,256 @@
+use super::CustomFormat;
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Error returned when parsing or rendering a [`Template`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The format string has an unmatched brace
+    InvalidFormatString,
+    /// The argument reference of a piece is not empty or an integer
+    InvalidArgument,
+    /// The custom specifier of a piece is not a runtime specifier like `<spec>`
+    InvalidSpecifier,
+    /// A piece references an argument which is not provided
+    MissingArgument(usize),
+    /// The number of arguments provided for rendering is different from the number of arguments of the template
+    ArgumentCount {
+        /// Number of arguments of the template
+        expected: usize,
+        /// Number of arguments provided
+        found: usize,
+    },
+    /// A piece with a custom specifier references a [`Display`](fmt::Display) argument, or conversely
+    ArgumentKind(usize),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidFormatString => f.write_str("invalid format string"),
+            Self::InvalidArgument => f.write_str("invalid argument reference in format string"),
+            Self::InvalidSpecifier => f.write_str("invalid custom specifier in format string: only runtime specifiers are supported"),
+            Self::MissingArgument(index) => write!(f, "missing argument for index {}", index),
+            Self::ArgumentCount { expected, found } => write!(f, "invalid number of arguments: expected {}, found {}", expected, found),
+            Self::ArgumentKind(index) => write!(f, "argument {} cannot be formatted with the specifier referencing it", index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TemplateError {}
+
+/// Piece of a parsed template
+#[derive(Debug, Clone, Copy)]
+enum Piece<'a> {
+    /// Literal text, with escaped braces already unescaped
+    Literal(&'a str),
+    /// Argument formatted with its `Display` implementation
+    Std(usize),
+    /// Argument formatted with its `CustomFormat` implementation and the provided specifier
+    Custom(usize, &'a str),
+}
+
+/// Kind of a template argument
+#[derive(Clone, Copy)]
+enum ArgumentKind<'a> {
+    /// Argument formatted with its `Display` implementation
+    Display(&'a dyn fmt::Display),
+    /// Argument formatted with its `CustomFormat` implementation, with the name of its type
+    Custom(&'a dyn CustomFormat, &'static str),
+}
+
+/// Argument provided at runtime for rendering a [`Template`]
+#[derive(Clone, Copy)]
+pub struct Argument<'a>(ArgumentKind<'a>);
+
+impl<'a> Argument<'a> {
+    /// Construct an argument formatted with its [`Display`](fmt::Display) implementation, for pieces like `{0}`
+    pub fn display<T: fmt::Display>(value: &'a T) -> Self {
+        Self(ArgumentKind::Display(value))
+    }
+
+    /// Construct an argument formatted with its [`CustomFormat`] implementation, for pieces like `{0 :<spec>}`
+    pub fn custom<T: CustomFormat>(value: &'a T) -> Self {
+        Self(ArgumentKind::Custom(value, core::any::type_name::<T>()))
+    }
+}
+
+impl fmt::Debug for Argument<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            ArgumentKind::Display(_) => f.write_str("Argument::Display(..)"),
+            ArgumentKind::Custom(..) => f.write_str("Argument::Custom(..)"),
+        }
+    }
+}
+
+/// Format string parsed at runtime, for rendering fully dynamic templates without macros.
+///
+/// The supported syntax is a subset of the macro syntax:
+///
+/// - `{{` and `}}` are escaped braces.
+/// - `{}` and `{N}` format the next or the `N`-th argument with its [`Display`](fmt::Display) implementation.
+/// - `{ :<spec>}` and `{N :<spec>}` format the next or the `N`-th argument with its [`CustomFormat`] implementation.
+///
+/// Named arguments, standard flags and compile-time specifiers are not supported, and a specifier cannot contain braces.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format::runtime::{Argument, CustomFormat, Template, TemplateError};
+///
+/// use core::fmt;
+///
+/// struct Hex(u8);
+///
+/// impl CustomFormat for Hex {
+///     fn fmt(&self, f: &mut fmt::Formatter, spec: &str) -> fmt::Result {
+///         match spec {
+///             "x" => write!(f, "{:#x}", self.0),
+///             _ => Err(fmt::Error),
+///         }
+///     }
+/// }
+///
+/// let (name, value) = ("value", Hex(0xAB));
+/// let args = [Argument::display(&name), Argument::custom(&value)];
+///
+/// let template = Template::new("{0}: {1 :<x>}", 2)?;
+/// assert_eq!(template.render(&args)?.to_string(), "value: 0xab");
+///
+/// assert_eq!(Template::new("{0} {2}", 2).unwrap_err(), TemplateError::MissingArgument(2));
+/// # Ok::<(), TemplateError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Template<'a> {
+    /// Parsed pieces
+    pieces: Vec<Piece<'a>>,
+    /// Number of arguments
+    arg_count: usize,
+}
+
+impl<'a> Template<'a> {
+    /// Parse a format string, checking that its argument references are lower than the number of arguments
+    pub fn new(format: &'a str, arg_count: usize) -> Result<Self, TemplateError> {
+        let mut pieces = Vec::new();
+        let mut next_index = 0;
+        let mut rest = format;
+
+        while let Some(position) = rest.find(|c| c == '{' || c == '}') {
+            let brace = &rest[position..position + 1];
+            let after = &rest[position + 1..];
+
+            if after.starts_with(brace) {
+                pieces.push(Piece::Literal(&rest[..position + 1]));
+                rest = &after[1..];
+                continue;
+            }
+
+            if brace == "}" {
+                return Err(TemplateError::InvalidFormatString);
+            }
+
+            if position > 0 {
+                pieces.push(Piece::Literal(&rest[..position]));
+            }
+
+            let end = after.find('}').ok_or(TemplateError::InvalidFormatString)?;
+            let inner = after[..end].trim_end();
+            rest = &after[end + 1..];
+
+            let (argument, spec) = match inner.find(" :") {
+                Some(separator) => (&inner[..separator], Some(&inner[separator + 2..])),
+                None => (inner, None),
+            };
+
+            let index = match argument.trim() {
+                "" => {
+                    next_index += 1;
+                    next_index - 1
+                }
+                argument if argument.bytes().all(|b| b.is_ascii_digit()) => argument.parse().map_err(|_| TemplateError::InvalidArgument)?,
+                _ => return Err(TemplateError::InvalidArgument),
+            };
+
+            if index >= arg_count {
+                return Err(TemplateError::MissingArgument(index));
+            }
+
+            pieces.push(match spec {
+                None => Piece::Std(index),
+                Some(spec) => match spec.strip_prefix('<').and_then(|spec| spec.strip_suffix('>')) {
+                    Some(spec) => Piece::Custom(index, spec),
+                    None => return Err(TemplateError::InvalidSpecifier),
+                },
+            });
+        }
+
+        if !rest.is_empty() {
+            pieces.push(Piece::Literal(rest));
+        }
+
+        Ok(Self { pieces, arg_count })
+    }
+
+    /// Returns the number of arguments of the template
+    pub fn arg_count(&self) -> usize {
+        self.arg_count
+    }
+
+    /// Render the template with the provided arguments, checking their number and kind.
+    ///
+    /// The returned value implements [`Display`](fmt::Display), and formatting it fails if a custom formatting implementation fails.
+    pub fn render<'b>(&'b self, args: &'b [Argument<'b>]) -> Result<Rendered<'b>, TemplateError> {
+        if args.len() != self.arg_count {
+            return Err(TemplateError::ArgumentCount { expected: self.arg_count, found: args.len() });
+        }
+
+        for piece in &self.pieces {
+            match *piece {
+                Piece::Literal(_) => (),
+                Piece::Std(index) => {
+                    if let ArgumentKind::Custom(..) = args[index].0 {
+                        return Err(TemplateError::ArgumentKind(index));
+                    }
+                }
+                Piece::Custom(index, _) => {
+                    if let ArgumentKind::Display(_) = args[index].0 {
+                        return Err(TemplateError::ArgumentKind(index));
+                    }
+                }
+            }
+        }
+
+        Ok(Rendered { pieces: &self.pieces, args })
+    }
+}
+
+/// Template rendered with its arguments, formatted via its [`Display`](fmt::Display) trait
+#[derive(Debug, Clone)]
+pub struct Rendered<'a> {
+    /// Parsed pieces
+    pieces: &'a [Piece<'a>],
+    /// Checked arguments
+    args: &'a [Argument<'a>],
+}
+
+impl fmt::Display for Rendered<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for piece in self.pieces {
+            match *piece {
+                Piece::Literal(literal) => f.write_str(literal)?,
+                Piece::Std(index) => match self.args[index].0 {
+                    ArgumentKind::Display(value) => value.fmt(f)?,
+                    ArgumentKind::Custom(..) => return Err(fmt::Error),
+                },
+                Piece::Custom(index, spec) => match self.args[index].0 {
+                    ArgumentKind::Custom(value, type_name) => super::fmt_checked(value, f, spec, type_name)?,
+                    ArgumentKind::Display(_) => return Err(fmt::Error),
+                },
+            }
+        }
+        Ok(())
+    }
+}
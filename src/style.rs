@@ -0,0 +1,163 @@
+//! Provides ANSI styling of values with compile-time checked format specifiers.
+//!
+//! A format specifier is a comma-separated list of styles, like `"red"` or `"red,bold"`, limited to 16 bytes without the `nightly-spec-str` feature.
+//!
+//! | Styles                                                                          | Description                          |
+//! |---------------------------------------------------------------------------------|--------------------------------------|
+//! | `black`, `red`, `green`, `yellow`, `blue`, `magenta`, `cyan`, `white`           | Foreground color                     |
+//! | `bright_black`, `bright_red`, ..., `bright_white`                               | Bright foreground color              |
+//! | `bg_black`, `bg_red`, ..., `bg_white`                                           | Background color                     |
+//! | `bold`, `dim`, `italic`, `underline`, `blink`, `reverse`, `hidden`, `strike`    | Text attribute                       |
+//!
+//! Invalid format specifiers are rejected when the formatting code is compiled.
+
+use crate::compile_time::{CustomFormat, Spec};
+
+use core::fmt;
+
+/// Wrapper applying ANSI styles to a value implementing [`Display`](core::fmt::Display).
+///
+/// The styles are selected by the format specifier, and are reset after the value is written.
+/// Standard format flags like the width are applied to the wrapped value.
+///
+/// The [`cprint!`](crate::cprint) and [`cprintln!`](crate::cprintln) macros wrap arguments with compile-time format specifiers automatically.
+///
+/// # Examples
+///
+/// ```rust
+/// use custom_format as cfmt;
+/// use custom_format::style::Styled;
+///
+/// assert_eq!(cfmt::format!("{0 :red}", Styled("error")), "\x1b[31merror\x1b[0m");
+/// assert_eq!(cfmt::format!("{0:>4 :bold,green}", Styled("ok")), "\x1b[1;32m  ok\x1b[0m");
+/// ```
+///
+/// The following statement doesn't compile since `"purple"` is not a valid style:
+///
+/// ```rust,compile_fail
+/// # use custom_format as cfmt;
+/// # use custom_format::style::Styled;
+/// cfmt::println!("{0 :purple}", Styled("error"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Styled<T>(pub T);
+
+impl<T: fmt::Display, const SPEC: Spec> CustomFormat<SPEC> for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (codes, len) = Sgr::<SPEC>::CODES;
+
+        f.write_str("\x1b[")?;
+        for (index, code) in codes[..len].iter().enumerate() {
+            if index > 0 {
+                f.write_str(";")?;
+            }
+            write!(f, "{}", code)?;
+        }
+        f.write_str("m")?;
+
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str("\x1b[0m")
+    }
+}
+
+/// Maximum number of styles in a format specifier
+const MAX_STYLES: usize = 8;
+
+/// Select Graphic Rendition parameters of a format specifier, computed at compile time
+struct Sgr<const SPEC: Spec>;
+
+impl<const SPEC: Spec> Sgr<SPEC> {
+    /// List of parameters and its length
+    #[cfg(not(feature = "nightly-spec-str"))]
+    const CODES: ([u8; MAX_STYLES], usize) = parse_styles(&SPEC.to_le_bytes());
+
+    /// List of parameters and its length
+    #[cfg(feature = "nightly-spec-str")]
+    const CODES: ([u8; MAX_STYLES], usize) = parse_styles(SPEC.as_bytes());
+}
+
+/// Parse a comma-separated list of styles from a format specifier
+const fn parse_styles(bytes: &[u8]) -> ([u8; MAX_STYLES], usize) {
+    let mut end = 0;
+    while end < bytes.len() && bytes[end] != 0 {
+        end += 1;
+    }
+
+    let mut codes = [0u8; MAX_STYLES];
+    let mut len = 0;
+
+    let mut start = 0;
+    let mut index = 0;
+    while index <= end {
+        if index == end || bytes[index] == b',' {
+            if len == MAX_STYLES {
+                #[allow(unconditional_panic, clippy::out_of_bounds_indexing)]
+                let _ = ["too many styles in format specifier"][usize::MAX];
+            }
+
+            codes[len] = style_code(bytes, start, index);
+            len += 1;
+            start = index + 1;
+        }
+        index += 1;
+    }
+
+    (codes, len)
+}
+
+/// Compute the Select Graphic Rendition parameter of a style
+const fn style_code(bytes: &[u8], start: usize, end: usize) -> u8 {
+    const COLORS: [&[u8]; 8] = [b"black", b"red", b"green", b"yellow", b"blue", b"magenta", b"cyan", b"white"];
+    const ATTRIBUTES: [(&[u8], u8); 8] =
+        [(b"bold", 1), (b"dim", 2), (b"italic", 3), (b"underline", 4), (b"blink", 5), (b"reverse", 7), (b"hidden", 8), (b"strike", 9)];
+
+    let mut index = 0;
+    while index < ATTRIBUTES.len() {
+        if eq_bytes(bytes, start, end, ATTRIBUTES[index].0) {
+            return ATTRIBUTES[index].1;
+        }
+        index += 1;
+    }
+
+    let (start, offset) = if starts_with(bytes, start, end, b"bright_") {
+        (start + 7, 90)
+    } else if starts_with(bytes, start, end, b"bg_") {
+        (start + 3, 40)
+    } else {
+        (start, 30)
+    };
+
+    let mut index = 0;
+    while index < COLORS.len() {
+        if eq_bytes(bytes, start, end, COLORS[index]) {
+            return offset + index as u8;
+        }
+        index += 1;
+    }
+
+    #[allow(unconditional_panic, clippy::out_of_bounds_indexing)]
+    let _ = ["invalid style in format specifier"][usize::MAX];
+    0
+}
+
+/// Check if `bytes[start..end]` is equal to `value`
+const fn eq_bytes(bytes: &[u8], start: usize, end: usize, value: &[u8]) -> bool {
+    end - start == value.len() && starts_with(bytes, start, end, value)
+}
+
+/// Check if `bytes[start..end]` starts with `prefix`
+const fn starts_with(bytes: &[u8], start: usize, end: usize, prefix: &[u8]) -> bool {
+    if end - start < prefix.len() {
+        return false;
+    }
+
+    let mut index = 0;
+    while index < prefix.len() {
+        if bytes[start + index] != prefix[index] {
+            return false;
+        }
+        index += 1;
+    }
+
+    true
+}
@@ -0,0 +1,101 @@
+//! Macros for the [`tracing`](https://docs.rs/tracing) crate, with custom format specifiers in the message.
+//!
+//! The fields before the message, like `target: "x"`, `%id` or `count = 1`, are passed unchanged to the `tracing` macros.
+//! The message is formatted with the [`format_args!`](crate::format_args) macro of this crate,
+//! so that custom format specifiers can be used like in `info!(%id, "started at {ts :%T}")`.
+//!
+//! The message is the first string literal directly following a comma, or starting the macro input.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use custom_format as cfmt;
+//!
+//! let id = 42;
+//! let user = format_args!("alice");
+//!
+//! cfmt::tracing::info!(%id, "logged in: {user :<upper>}");
+//! cfmt::tracing::event!(target: "auth", tracing::Level::WARN, count = 3, "invalid password for {0 :<upper>}", user);
+//! ```
+
+#[doc(inline)]
+pub use crate::{tracing_debug as debug, tracing_error as error, tracing_event as event, tracing_info as info, tracing_trace as trace, tracing_warn as warn};
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! tracing_inner {
+    // Start of a field or of the message
+    (@start [$($macro:tt)*] [$($field:tt)*] $fmt:literal $(, $($arg:tt)*)?) => {
+        $($macro)*($($field)* "{}", $crate::format_args!($fmt $(, $($arg)*)?))
+    };
+    (@start [$($macro:tt)*] [$($field:tt)*]) => {
+        $($macro)*($($field)*)
+    };
+    (@start [$($macro:tt)*] [$($field:tt)*] $tt:tt $($rest:tt)*) => {
+        $crate::tracing_inner!(@field [$($macro)*] [$($field)* $tt] $($rest)*)
+    };
+    // Remaining tokens of a field
+    (@field [$($macro:tt)*] [$($field:tt)*] , $($rest:tt)*) => {
+        $crate::tracing_inner!(@start [$($macro)*] [$($field)* ,] $($rest)*)
+    };
+    (@field [$($macro:tt)*] [$($field:tt)*] $tt:tt $($rest:tt)*) => {
+        $crate::tracing_inner!(@field [$($macro)*] [$($field)* $tt] $($rest)*)
+    };
+    (@field [$($macro:tt)*] [$($field:tt)*]) => {
+        $($macro)*($($field)*)
+    };
+}
+
+/// Constructs a new event, with the level as the first argument after the optional target and parent
+#[doc(hidden)]
+#[macro_export]
+macro_rules! tracing_event {
+    ($($arg:tt)+) => {
+        $crate::tracing_inner!(@start [$crate::tracing_crate::event!] [] $($arg)+)
+    };
+}
+
+/// Constructs an event at the error level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! tracing_error {
+    ($($arg:tt)+) => {
+        $crate::tracing_inner!(@start [$crate::tracing_crate::error!] [] $($arg)+)
+    };
+}
+
+/// Constructs an event at the warn level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! tracing_warn {
+    ($($arg:tt)+) => {
+        $crate::tracing_inner!(@start [$crate::tracing_crate::warn!] [] $($arg)+)
+    };
+}
+
+/// Constructs an event at the info level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! tracing_info {
+    ($($arg:tt)+) => {
+        $crate::tracing_inner!(@start [$crate::tracing_crate::info!] [] $($arg)+)
+    };
+}
+
+/// Constructs an event at the debug level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! tracing_debug {
+    ($($arg:tt)+) => {
+        $crate::tracing_inner!(@start [$crate::tracing_crate::debug!] [] $($arg)+)
+    };
+}
+
+/// Constructs an event at the trace level
+#[doc(hidden)]
+#[macro_export]
+macro_rules! tracing_trace {
+    ($($arg:tt)+) => {
+        $crate::tracing_inner!(@start [$crate::tracing_crate::trace!] [] $($arg)+)
+    };
+}
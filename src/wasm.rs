@@ -0,0 +1,14 @@
+//! Provides functions used by the [`console_log!`](crate::console_log) and [`console_error!`](crate::console_error) macros.
+
+use alloc::fmt::format;
+use core::fmt;
+
+/// Log formatted data to the web console, at the "log" level
+pub fn log(args: fmt::Arguments) {
+    web_sys::console::log_1(&format(args).as_str().into());
+}
+
+/// Log formatted data to the web console, at the "error" level
+pub fn error(args: fmt::Arguments) {
+    web_sys::console::error_1(&format(args).as_str().into());
+}